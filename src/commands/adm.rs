@@ -1,69 +1,1434 @@
-use serenity::all::{Color, CreateEmbed, CreateMessage, Member, Message};
-use tracing::error;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::{Context, Error, LoopchanConfig};
+use serenity::all::{Attachment, Color, CreateAttachment, CreateEmbed, CreateEmbedFooter, EmojiIdentifier, GuildChannel, GuildId, Member, ReactionType, Role, RoleId};
+use tracing::{error, warn};
+
+use crate::{commands::eco::format_ledger_entry, utils::banlist::{read_ban_list, write_ban_list, BanListEntry, BanListFormat}, handlers::events::{blacklist::{send_blacklist_notice, sweep_blacklisted_members, BlacklistSweep, SWEEP_REVIEW_SETTING}, roleshop::format_roleshop_item_details, welcomecard::UPLOADED_TEMPLATE_PATH}, utils::database::{blacklist::{add_user_in_blacklist_db, count_users_in_blacklist_db, get_all_users_in_blacklist_db, get_last_appeal_in_blacklist_db, get_user_in_blacklist_db, import_users_in_blacklist_db, list_users_in_blacklist_db, remove_user_in_blacklist_db}, settings::{reset_settings_in_settings_db, set_setting_in_settings_db}, cooldowns::reset_user_cooldowns_in_cooldowns_db, ledger::query_entries_in_ledger_db, audit::query_entries_in_audit_db, gambling::{block_user_in_gambling_db, unblock_user_in_gambling_db}, roleshop::{get_shop_item_in_roleshop_db, get_shop_items_in_roleshop_db, remove_shop_item_in_roleshop_db, save_shop_item_in_roleshop_db}}, Context, DataAuditEntry, DataBlacklistAppeal, DataMaintenanceWindow, DataBlacklistEntry, DataLedgerEntry, Error, PresenceEntry, PresenceKind, RoleShopItem};
+use crate::utils::config::reload_config;
+use crate::utils::basic::parse_duration;
+use crate::utils::presence::{show_presence, CustomPresence};
+use crate::utils::maintenance::{format_maintenance_message, sync_maintenance_presence, ManualMaintenance, DEFAULT_MAINTENANCE_MESSAGE};
+use crate::utils::database::maintenance::{add_to_allowlist_in_maintenance_db, create_window_in_maintenance_db, remove_from_allowlist_in_maintenance_db, remove_window_in_maintenance_db};
+use crate::utils::permissions::PermissionLevel;
 
 /// Bot ADM Commands
-#[poise::command(slash_command, subcommands("blacklist"), subcommand_required)]
+#[poise::command(slash_command, custom_data = "PermissionLevel::Staff", subcommands("blacklist", "resetcooldowns", "reload", "ledger", "audit", "shop", "gamblingban", "welcomecard", "maintenance", "presence"), subcommand_required)]
 pub async fn adm(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
 
-/// Blacklisting LIVE
+/// Blacklist (banned from PTL and everything related to it)
+#[poise::command(slash_command, subcommands("blacklist_add", "blacklist_add_id", "blacklist_remove", "blacklist_list", "blacklist_info", "blacklist_import", "blacklist_export", "blacklist_sweep"), subcommand_required)]
+pub async fn blacklist(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+const BLACKLIST_PAGE_SIZE: u64 = 10;
+
+fn format_blacklist_entry(entry: &DataBlacklistEntry) -> String {
+    let moderator: String = entry.moderator.map(|moderator| format!("<@{}>", moderator)).unwrap_or(String::from("Config.toml"));
+    let expires: String = entry.expires_at.map(|expires_at| format!("<t:{}:R>", expires_at)).unwrap_or(String::from("never"));
+    format!("<@{}> ({}) • by {} <t:{}:d> • expires {}\n-# {}", entry.discord_id, entry.discord_id, moderator, entry.created_at, expires, entry.reason.as_deref().unwrap_or("No reason"))
+}
+
+/// Saves blacklist entry, and DMs and bans user if they're in PTL server right now (otherwise they're banned once they join)
+async fn blacklist_user(
+    ctx: Context<'_>,
+    discord_id: u64,
+    reason: Option<String>,
+    days: Option<u64>
+) -> Result<(), Error> {
+    let expires_at: Option<u64> = days.map(|days| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + days * 86400);
+
+    let added: Result<usize, async_sqlite::Error> = add_user_in_blacklist_db(&ctx.data().db_client, discord_id, reason, Some(ctx.author().id.get()), expires_at).await;
+    if added.is_err() {
+        error!("Failed to blacklist {}: {}", discord_id, added.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to blacklist user. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    warn!("{} blacklisted {}", ctx.author().name, discord_id);
+
+    let guild_id: GuildId = ctx.data().config.get().guild.into();
+    let member: Result<Member, serenity::Error> = guild_id.member(ctx, discord_id).await;
+    if member.is_err() {
+        ctx.send(poise::CreateReply::default()
+            .content(format!("Successfully blacklisted <@{}>. They aren't in server, so they'll be banned once they join.", discord_id))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let member: Member = member.unwrap();
+
+    let entry: Option<DataBlacklistEntry> = get_user_in_blacklist_db(&ctx.data().db_client, discord_id).await?;
+    if entry.is_some() {
+        send_blacklist_notice(ctx.serenity_context(), &ctx.data().config.get(), &member.user, &entry.unwrap()).await;
+    }
+
+    let banned: Result<(), serenity::Error> = member.ban_with_reason(ctx, 0, "Blacklisted UserId (LIVE)").await;
+
+    if banned.is_err() {
+        error!("{}", banned.err().unwrap().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Blacklisted, but failed to ban member. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content("Successfully blacklisted.")
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Blacklist and ban member
+#[poise::command(slash_command, rename = "add")]
+pub async fn blacklist_add(
+    ctx: Context<'_>,
+    #[description = "Member to blacklist"] member: Member,
+    #[description = "Reason (shown to member)"] #[max_length = 500] reason: Option<String>,
+    #[description = "Lift blacklist after this many days (permanent if not set)"] #[min = 1] days: Option<u64>,
+) -> Result<(), Error> {
+    blacklist_user(ctx, member.user.id.get(), reason, days).await
+}
+
+/// Blacklist user by ID, they don't have to be in server
+#[poise::command(slash_command, rename = "add_id")]
+pub async fn blacklist_add_id(
+    ctx: Context<'_>,
+    #[description = "User ID"] user_id: String,
+    #[description = "Reason (shown to user)"] #[max_length = 500] reason: Option<String>,
+    #[description = "Lift blacklist after this many days (permanent if not set)"] #[min = 1] days: Option<u64>,
+) -> Result<(), Error> {
+    let discord_id: Option<u64> = user_id.trim().parse().ok().filter(|id: &u64| *id > 0);
+    if discord_id.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("User ID must be a number, like `908779319084589067`.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    blacklist_user(ctx, discord_id.unwrap(), reason, days).await
+}
+
+/// Remove user from blacklist and unban them
+#[poise::command(slash_command, rename = "remove")]
+pub async fn blacklist_remove(
+    ctx: Context<'_>,
+    #[description = "Blacklisted user"] user: serenity::model::user::User,
+) -> Result<(), Error> {
+    let discord_id: u64 = user.id.get();
+    let removed: Result<usize, async_sqlite::Error> = remove_user_in_blacklist_db(&ctx.data().db_client, discord_id).await;
+    if removed.is_err() {
+        error!("Failed to remove {} from blacklist: {}", discord_id, removed.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to remove user from blacklist. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    if removed.unwrap() == 0 {
+        ctx.send(poise::CreateReply::default()
+            .content("This user isn't blacklisted.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let guild_id: GuildId = ctx.data().config.get().guild.into();
+    let unbanned: Result<(), serenity::Error> = guild_id.unban(ctx, user.id).await;
+    if unbanned.is_err() {
+        // Ban could've been lifted manually already
+        warn!("Failed to unban {} after removing them from blacklist: {}", discord_id, unbanned.unwrap_err().to_string());
+    }
+
+    warn!("{} removed {} ({}) from blacklist", ctx.author().name, user.name, discord_id);
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Successfully removed <@{}> from blacklist.", discord_id))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// List blacklisted users
+#[poise::command(slash_command, rename = "list")]
+pub async fn blacklist_list(
+    ctx: Context<'_>,
+    #[description = "Page"] #[min = 1] page: Option<u64>,
+) -> Result<(), Error> {
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let total: Result<u64, async_sqlite::Error> = count_users_in_blacklist_db(db_client).await;
+    if total.is_err() {
+        error!("Failed to count blacklisted users: {}", total.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to get blacklist. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let total: u64 = total.unwrap();
+    let last_page: u64 = total.saturating_sub(1) / BLACKLIST_PAGE_SIZE + 1;
+    let page: u64 = page.unwrap_or(1).min(last_page);
+
+    let entries: Result<Vec<DataBlacklistEntry>, async_sqlite::Error> = list_users_in_blacklist_db(db_client, BLACKLIST_PAGE_SIZE, (page - 1) * BLACKLIST_PAGE_SIZE).await;
+    if entries.is_err() {
+        error!("Failed to list blacklisted users: {}", entries.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to get blacklist. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let entries: Vec<DataBlacklistEntry> = entries.unwrap();
+    let response: String = if entries.is_empty() {
+        "Nobody is blacklisted.".to_string()
+    } else {
+        entries.iter().map(format_blacklist_entry).collect::<Vec<String>>().join("\n")
+    };
+
+    ctx.send(poise::CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title("Blacklist")
+                .description(response)
+                .footer(CreateEmbedFooter::new(format!("Page {}/{} • {} users", page, last_page, total)))
+                .color(Color::from_rgb(255, 255, 255))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Show why and when user was blacklisted, and their last appeal
+#[poise::command(slash_command, rename = "info")]
+pub async fn blacklist_info(
+    ctx: Context<'_>,
+    #[description = "User"] user: serenity::model::user::User,
+) -> Result<(), Error> {
+    let entry: Result<Option<DataBlacklistEntry>, async_sqlite::Error> = get_user_in_blacklist_db(&ctx.data().db_client, user.id.get()).await;
+    if entry.is_err() {
+        error!("Failed to get {}'s blacklist entry: {}", user.id.get(), entry.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to get blacklist entry. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let mut response: String = match entry.unwrap() {
+        Some(entry) => format_blacklist_entry(&entry),
+        None => format!("<@{}> isn't blacklisted.", user.id.get())
+    };
+
+    let last_appeal: Result<Option<DataBlacklistAppeal>, async_sqlite::Error> = get_last_appeal_in_blacklist_db(&ctx.data().db_client, user.id.get()).await;
+    if last_appeal.is_err() {
+        error!("Failed to get {}'s last blacklist appeal: {}", user.id.get(), last_appeal.unwrap_err().to_string());
+    } else if let Some(appeal) = last_appeal.unwrap() {
+        let decision: String = match (appeal.accepted, appeal.moderator) {
+            (Some(accepted), Some(moderator)) => format!("{} by <@{}> <t:{}:R>", if accepted { "accepted" } else { "denied" }, moderator, appeal.decided_at.unwrap_or(0)),
+            _ => String::from("pending")
+        };
+        response.push_str(&format!("\n\n**Last appeal #{}** <t:{}:R> • {}\n>>> {}", appeal.id, appeal.created_at, decision, appeal.appeal));
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content(response)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Download blacklist as ban-list file for partner servers
+#[poise::command(slash_command, rename = "export")]
+pub async fn blacklist_export(
+    ctx: Context<'_>,
+    #[description = "File format (JSON if not set)"] format: Option<BanListFormat>,
+) -> Result<(), Error> {
+    let format: BanListFormat = format.unwrap_or(BanListFormat::Json);
+
+    let entries: Result<Vec<DataBlacklistEntry>, async_sqlite::Error> = get_all_users_in_blacklist_db(&ctx.data().db_client).await;
+    if entries.is_err() {
+        error!("Failed to get blacklist for export: {}", entries.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to get blacklist. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let entries: Vec<BanListEntry> = entries.unwrap().iter().map(BanListEntry::from).collect();
+
+    ctx.send(poise::CreateReply::default()
+        .content(format!("{} blacklisted users.", entries.len()))
+        .attachment(CreateAttachment::bytes(write_ban_list(&entries, format).into_bytes(), format!("blacklist.{}", format.extension())))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Add users from partner server's ban-list file (JSON or CSV), already blacklisted ones are skipped
+#[poise::command(slash_command, rename = "import")]
+pub async fn blacklist_import(
+    ctx: Context<'_>,
+    #[description = "Ban-list file (.json or .csv)"] file: Attachment,
+    #[description = "Reason for entries without one"] #[max_length = 500] reason: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let downloaded: Result<Vec<u8>, serenity::Error> = file.download().await;
+    if downloaded.is_err() {
+        error!("Failed to download ban-list {}: {}", file.url, downloaded.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to download file. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let text: String = String::from_utf8_lossy(&downloaded.unwrap()).to_string();
+    let entries: Result<Vec<BanListEntry>, String> = read_ban_list(&text, BanListFormat::from_filename(&file.filename));
+    if entries.is_err() {
+        ctx.send(poise::CreateReply::default()
+            .content(format!("Failed to read ban-list: {}", entries.unwrap_err()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let moderator: u64 = ctx.author().id.get();
+    let entries: Vec<DataBlacklistEntry> = entries.unwrap().into_iter()
+        // Already expired entries would be lifted right away
+        .filter(|entry| entry.expires_at.is_none_or(|expires_at| expires_at > now))
+        .map(|entry| DataBlacklistEntry {
+            discord_id: entry.id,
+            reason: entry.reason.or(reason.clone()),
+            moderator: Some(moderator),
+            created_at: entry.created_at.unwrap_or(now),
+            expires_at: entry.expires_at
+        })
+        .collect();
+    let total: usize = entries.len();
+
+    let imported: Result<usize, async_sqlite::Error> = import_users_in_blacklist_db(&ctx.data().db_client, entries).await;
+    if imported.is_err() {
+        error!("Failed to import ban-list: {}", imported.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to import ban-list. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let imported: usize = imported.unwrap();
+
+    // Partner's file isn't trusted, so members in it aren't banned until someone reviews them
+    if imported > 0 {
+        let marked: Result<usize, async_sqlite::Error> = set_setting_in_settings_db(&ctx.data().db_client, SWEEP_REVIEW_SETTING, file.filename.clone()).await;
+        if marked.is_err() {
+            error!("Failed to mark imported blacklist for review: {}", marked.unwrap_err().to_string());
+        }
+    }
+    let sweep: BlacklistSweep = sweep_blacklisted_members(ctx.serenity_context(), &ctx.data().config.get(), &ctx.data().db_client, true).await;
+    warn!("{} imported {} blacklisted users from {} ({} of them are in server)", ctx.author().name, imported, file.filename, sweep.pending.len() + sweep.conflicts.len());
+
+    let mut response: String = format!("Imported **{}** new blacklisted users ({} were already blacklisted or expired).", imported, total.saturating_sub(imported));
+    if !sweep.pending.is_empty() {
+        response.push_str(&format!("\n**{}** blacklisted users are in server and weren't banned yet, review them with `/adm blacklist sweep` and run it with `confirm: True` to ban them.", sweep.pending.len()));
+    }
+    if !sweep.conflicts.is_empty() {
+        response.push_str(&format!("\n⚠️ **{}** of them are owner or staff and won't be banned: {}", sweep.conflicts.len(), format_user_mentions(&sweep.conflicts)));
+    }
+    ctx.send(poise::CreateReply::default()
+        .content(response)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+const SWEEP_MENTIONS_LIMIT: usize = 30;
+
+fn format_user_mentions(ids: &[u64]) -> String {
+    let mut mentions: String = ids.iter().take(SWEEP_MENTIONS_LIMIT).map(|id| format!("<@{}>", id)).collect::<Vec<String>>().join(", ");
+    if ids.len() > SWEEP_MENTIONS_LIMIT {
+        mentions.push_str(&format!(" and {} more", ids.len() - SWEEP_MENTIONS_LIMIT));
+    }
+    mentions
+}
+
+/// Show blacklisted users who are in server, `confirm` bans them (owner and staff are never banned)
+#[poise::command(slash_command, rename = "sweep")]
+pub async fn blacklist_sweep(
+    ctx: Context<'_>,
+    #[description = "Ban them (only shows them if not set)"] confirm: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let confirm: bool = confirm.unwrap_or(false);
+
+    let sweep: BlacklistSweep = sweep_blacklisted_members(ctx.serenity_context(), &ctx.data().config.get(), &ctx.data().db_client, !confirm).await;
+    if confirm {
+        // Automatic sweeps can ban again
+        let reviewed: Result<usize, async_sqlite::Error> = reset_settings_in_settings_db(&ctx.data().db_client, SWEEP_REVIEW_SETTING).await;
+        if reviewed.is_err() {
+            error!("Failed to mark imported blacklist as reviewed: {}", reviewed.unwrap_err().to_string());
+        }
+        warn!("{} confirmed blacklist sweep, {} members were banned", ctx.author().name, sweep.banned.len());
+    }
+
+    let mut response: String = String::new();
+    if !sweep.banned.is_empty() {
+        response.push_str(&format!("Banned **{}** blacklisted members: {}\n", sweep.banned.len(), format_user_mentions(&sweep.banned)));
+    }
+    if !sweep.pending.is_empty() {
+        if confirm {
+            response.push_str(&format!("Failed to ban **{}** members, check terminal logs: {}\n", sweep.pending.len(), format_user_mentions(&sweep.pending)));
+        } else {
+            response.push_str(&format!("**{}** blacklisted members are in server: {}\nRun this command with `confirm: True` to ban them.\n", sweep.pending.len(), format_user_mentions(&sweep.pending)));
+        }
+    }
+    if !sweep.conflicts.is_empty() {
+        response.push_str(&format!("⚠️ **{}** blacklisted members are owner or staff and won't be banned: {}\n", sweep.conflicts.len(), format_user_mentions(&sweep.conflicts)));
+    }
+    if response.is_empty() {
+        response = String::from("No blacklisted members are in server.");
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content(response)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Reset member's cooldowns (all of them, or only one action like `eco.work`, `fishing.fish`)
 #[poise::command(slash_command)]
-pub async fn blacklist(
+pub async fn resetcooldowns(
     ctx: Context<'_>,
-    #[description = "Member to blacklist"]
-    member: Member,
+    #[description = "Member"] user: serenity::model::user::User,
+    #[description = "Action (e.g. eco.work, fishing.fish, command.eco balance)"] action: Option<String>,
 ) -> Result<(), Error> {
-    let toml_string: Result<String, std::io::Error> = tokio::fs::read_to_string("Config.toml").await;
-    if toml_string.is_err() {
-        error!("Failed to read your config.toml file: {}", toml_string.unwrap_err().to_string());
+    let reset: Result<usize, async_sqlite::Error> = reset_user_cooldowns_in_cooldowns_db(&ctx.data().db_client, user.id.get(), action).await;
+
+    if reset.is_err() {
+        error!("Failed to reset {}'s cooldowns: {}", user.id.get(), reset.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to reset cooldowns. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Successfully reset <@{}>'s cooldowns.\n-# Removed: {}", user.id, reset.unwrap()))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Reload Config.toml without restarting (it's also reloaded automatically once saved)
+#[poise::command(slash_command)]
+pub async fn reload(ctx: Context<'_>) -> Result<(), Error> {
+    let reloaded: Result<Vec<String>, String> = reload_config(&ctx.data().config).await;
+
+    if reloaded.is_err() {
+        let error: String = reloaded.unwrap_err();
+        error!("Failed to reload config: {}", error);
+        ctx.send(poise::CreateReply::default()
+            .content(format!("Config wasn't reloaded, current one is kept.\n-# {}", error))
+            .ephemeral(true)
+        ).await?;
         return Ok(());
     }
 
-    let mut loopchans_config: LoopchanConfig = toml::from_str(&toml_string.unwrap()).unwrap();
-    loopchans_config.blacklist.push(member.user.id.get());
+    let changed: Vec<String> = reloaded.unwrap();
+    let response: String = if changed.is_empty() {
+        String::from("Config is already up to date.")
+    } else {
+        format!("Successfully reloaded config.\n-# Changed: {}", changed.iter().map(|key| format!("`{}`", key)).collect::<Vec<String>>().join(", "))
+    };
+
+    ctx.send(poise::CreateReply::default()
+        .content(response)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+fn parse_date_to_timestamp(date: &str, end_of_day: bool) -> Option<u64> {
+    let date: chrono::NaiveDate = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let time: chrono::NaiveDateTime = if end_of_day { date.and_hms_opt(23, 59, 59)? } else { date.and_hms_opt(0, 0, 0)? };
+    Some(time.and_utc().timestamp() as u64)
+}
 
-    let written: Result<(), std::io::Error> = tokio::fs::write("Config.toml", toml::to_string(&loopchans_config).unwrap()).await;
+/// Query economy ledger by member and/or time range
+#[poise::command(slash_command)]
+pub async fn ledger(
+    ctx: Context<'_>,
+    #[description = "Member"] user: Option<serenity::model::user::User>,
+    #[description = "From date (YYYY-MM-DD, UTC)"] since: Option<String>,
+    #[description = "To date (YYYY-MM-DD, UTC)"] until: Option<String>,
+) -> Result<(), Error> {
+    let since_timestamp: Option<u64> = since.as_deref().and_then(|date| parse_date_to_timestamp(date, false));
+    let until_timestamp: Option<u64> = until.as_deref().and_then(|date| parse_date_to_timestamp(date, true));
 
-    if written.is_err() {
-        error!("{}", written.err().unwrap().to_string());
+    if (since.is_some() && since_timestamp.is_none()) || (until.is_some() && until_timestamp.is_none()) {
         ctx.send(poise::CreateReply::default()
-            .content("Failed to write new toml. Check terminal logs.")
+            .content("Dates must be in `YYYY-MM-DD` format.")
             .ephemeral(true)
         ).await?;
         return Ok(());
     }
 
-    let sent_message: Result<Message, serenity::Error> = member.user.direct_message(ctx,
-        CreateMessage::default()
-            .embed(
-                CreateEmbed::default()
-                    .description("Hello! You have been blacklisted from **PARKOUR: The Loop**.\nThis means that you will not be able to access any of the content related to **PTL**, or it's community.\nReasons of blacklisting are not being disclosed, if you were blacklisted this means that you should know what you did.\nCurrently appealing a blacklist is not possible. However, we will let you know if that changes ever.")
-                    .color(Color::from_rgb(255, 0, 0))
-            )
+    let entries: Result<Vec<DataLedgerEntry>, async_sqlite::Error> = query_entries_in_ledger_db(
+        &ctx.data().db_client,
+        user.as_ref().map(|user| user.id.get()),
+        since_timestamp,
+        until_timestamp,
+        20
     ).await;
 
-    if sent_message.is_err() {
-        error!("Failed to send blacklist message to {}: {}", member.user.id.get(), sent_message.unwrap_err().to_string());
+    if entries.is_err() {
+        error!("Failed to query ledger: {}", entries.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to query ledger. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let entries: Vec<DataLedgerEntry> = entries.unwrap();
+    let response: String = if entries.is_empty() {
+        "No entries found.".to_string()
     } else {
-        sent_message.unwrap().reply(ctx, "You can run command ```/blacklist_check``` in loopchan's DMs to get your blacklist status and appeal server once it becomes available.").await?;
+        entries.iter().map(|entry| format!("<@{}> {}", entry.discord_id, format_ledger_entry(entry))).collect::<Vec<String>>().join("\n")
+    };
+
+    ctx.send(poise::CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title("Ledger")
+                .description(response)
+                .color(Color::from_rgb(255, 255, 255))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+fn format_audit_entry(entry: &DataAuditEntry) -> String {
+    let target: String = entry.target.map(|target| format!(" → <@{}>", target)).unwrap_or_default();
+    let arguments: String = if entry.arguments.is_empty() { String::new() } else { format!(" `{}`", entry.arguments.replace('`', "'")) };
+    format!("**#{}** <t:{}:f> <@{}> {}{}{}\n-# {}", entry.id, entry.created_at, entry.actor, entry.command, target, arguments, entry.outcome)
+}
+
+/// Query audit log of privileged actions by actor, target and/or time range
+#[poise::command(slash_command)]
+pub async fn audit(
+    ctx: Context<'_>,
+    #[description = "Who used command"] actor: Option<serenity::model::user::User>,
+    #[description = "Who command was used on"] target: Option<serenity::model::user::User>,
+    #[description = "From date (YYYY-MM-DD, UTC)"] since: Option<String>,
+    #[description = "To date (YYYY-MM-DD, UTC)"] until: Option<String>,
+) -> Result<(), Error> {
+    let since_timestamp: Option<u64> = since.as_deref().and_then(|date| parse_date_to_timestamp(date, false));
+    let until_timestamp: Option<u64> = until.as_deref().and_then(|date| parse_date_to_timestamp(date, true));
+
+    if (since.is_some() && since_timestamp.is_none()) || (until.is_some() && until_timestamp.is_none()) {
+        ctx.send(poise::CreateReply::default()
+            .content("Dates must be in `YYYY-MM-DD` format.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
     }
 
-    let banned: Result<(), serenity::Error> = member.ban_with_reason(ctx, 0, "Blacklisted UserId (LIVE)").await;
+    let entries: Result<Vec<DataAuditEntry>, async_sqlite::Error> = query_entries_in_audit_db(
+        &ctx.data().db_client,
+        actor.as_ref().map(|user| user.id.get()),
+        target.as_ref().map(|user| user.id.get()),
+        since_timestamp,
+        until_timestamp,
+        15
+    ).await;
 
-    if banned.is_err() {
-        error!("{}", banned.err().unwrap().to_string());
+    if entries.is_err() {
+        error!("Failed to query audit log: {}", entries.unwrap_err().to_string());
         ctx.send(poise::CreateReply::default()
-            .content("Failed to ban blacklisted user. Check terminal logs.")
+            .content("Failed to query audit log. Check terminal logs.")
             .ephemeral(true)
         ).await?;
         return Ok(());
     }
 
+    let entries: Vec<DataAuditEntry> = entries.unwrap();
+    let mut response: String = if entries.is_empty() {
+        "No entries found.".to_string()
+    } else {
+        entries.iter().map(format_audit_entry).collect::<Vec<String>>().join("\n")
+    };
+    // Embed description limit
+    if response.chars().count() > 4096 {
+        response = format!("{}…", response.chars().take(4095).collect::<String>());
+    }
+
     ctx.send(poise::CreateReply::default()
-        .content("Successfully blacklisted.")
+        .embed(
+            CreateEmbed::default()
+                .title("Audit Log")
+                .description(response)
+                .color(Color::from_rgb(255, 255, 255))
+        )
         .ephemeral(true)
     ).await?;
-    
+
+    Ok(())
+}
+
+/// Role Shop catalogue
+#[poise::command(slash_command, subcommands("shop_add", "shop_edit", "shop_remove", "shop_list"), subcommand_required)]
+pub async fn shop(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Returns reason why bot can't sell this role, if there is one
+async fn validate_shop_role(ctx: Context<'_>, role: &Role) -> Result<Option<String>, Error> {
+    let guild_id: GuildId = ctx.data().config.get().guild.into();
+
+    if role.guild_id != guild_id {
+        return Ok(Some("Role must be from PTL server.".to_string()));
+    }
+    if role.id.get() == guild_id.get() {
+        return Ok(Some("Can't sell @everyone role.".to_string()));
+    }
+    if role.managed {
+        return Ok(Some("Role is managed by an integration and can't be given to members.".to_string()));
+    }
+
+    let bot_member: Member = guild_id.member(ctx, ctx.framework().bot_id).await?;
+    let roles: HashMap<RoleId, Role> = guild_id.roles(ctx).await?;
+    let bot_top_position: u16 = bot_member.roles.iter()
+        .filter_map(|role_id| roles.get(role_id))
+        .map(|role| role.position)
+        .max()
+        .unwrap_or(0);
+
+    if role.position >= bot_top_position {
+        return Ok(Some("Role must be below Loopchan's highest role, otherwise it can't be given to members.".to_string()));
+    }
+
+    Ok(None)
+}
+
+/// Parses `<:name:id>` and makes sure emoji exists in PTL server
+async fn fetch_shop_emoji(ctx: Context<'_>, emoji: &str) -> Option<EmojiIdentifier> {
+    let parsed_emoji: Option<EmojiIdentifier> = serenity::utils::parse_emoji(emoji);
+    if parsed_emoji.is_none() {
+        return None;
+    }
+    let parsed_emoji: EmojiIdentifier = parsed_emoji.unwrap();
+
+    let guild_id: GuildId = ctx.data().config.get().guild.into();
+    let fetched_emoji: Result<serenity::all::Emoji, serenity::Error> = guild_id.emoji(ctx, parsed_emoji.id).await;
+    if fetched_emoji.is_err() {
+        warn!("Failed to fetch {} emoji for shop item: {}", parsed_emoji.id, fetched_emoji.unwrap_err().to_string());
+        return None;
+    }
+
+    Some(parsed_emoji)
+}
+
+/// Add a role to shop
+#[poise::command(slash_command, rename = "add")]
+pub async fn shop_add(
+    ctx: Context<'_>,
+    #[description = "Role to sell"] role: Role,
+    #[description = "Price"] #[min = 1] price: u32,
+    #[description = "Description"] #[max_length = 100] description: String,
+    #[description = "Icon emoji from this server (e.g. <:LoopchanTraceur:1376642201647583356>)"] emoji: String,
+    #[description = "Name in shop (role name if not set)"] display_name: Option<String>,
+    #[description = "Rent role for this many hours"] #[min = 1] duration: Option<u64>,
+    #[description = "Minimum level to buy role"] #[min = 1] min_level: Option<u64>,
+    #[description = "How many members can own role at once"] #[min = 1] stock: Option<u64>,
+    #[description = "Part of price returned when selling back (0.5 = 50%)"] #[min = 0] #[max = 1] sell_back: Option<f32>,
+) -> Result<(), Error> {
+    let invalid_reason: Option<String> = validate_shop_role(ctx, &role).await?;
+    if invalid_reason.is_some() {
+        ctx.send(poise::CreateReply::default()
+            .content(invalid_reason.unwrap())
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let icon: Option<EmojiIdentifier> = fetch_shop_emoji(ctx, &emoji).await;
+    if icon.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("Emoji must be a custom emoji from PTL server.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let icon: EmojiIdentifier = icon.unwrap();
+
+    let existing_item: Result<Option<RoleShopItem>, async_sqlite::Error> = get_shop_item_in_roleshop_db(&ctx.data().db_client, role.id.get()).await;
+    if existing_item.is_err() {
+        error!("Failed to get shop item with ID {}: {}", role.id.get(), existing_item.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to check shop. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    if existing_item.unwrap().is_some() {
+        ctx.send(poise::CreateReply::default()
+            .content("This role is already in shop, use `/adm shop edit` instead.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let item: RoleShopItem = RoleShopItem {
+        id: role.id.get(),
+        display_name: display_name.unwrap_or(role.name.clone()),
+        icon_id: icon.id.get(),
+        icon_name: icon.name,
+        description,
+        price,
+        duration,
+        min_level,
+        stock,
+        sell_back
+    };
+
+    let saved: Result<usize, async_sqlite::Error> = save_shop_item_in_roleshop_db(&ctx.data().db_client, item.clone()).await;
+    if saved.is_err() {
+        error!("Failed to add {} role to shop: {}", role.id.get(), saved.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to add role to shop. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Successfully added <:{}:{}> **{}** (<@&{}>) for **${}** to shop.{}", item.icon_name, item.icon_id, item.display_name, item.id, item.price, format_roleshop_item_details(&item)))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Edit role in shop (set 0 to remove duration, level requirement, stock or sell back)
+#[poise::command(slash_command, rename = "edit")]
+pub async fn shop_edit(
+    ctx: Context<'_>,
+    #[description = "Role in shop"] role: Role,
+    #[description = "Price"] #[min = 1] price: Option<u32>,
+    #[description = "Description"] #[max_length = 100] description: Option<String>,
+    #[description = "Icon emoji from this server (e.g. <:LoopchanTraceur:1376642201647583356>)"] emoji: Option<String>,
+    #[description = "Name in shop"] display_name: Option<String>,
+    #[description = "Rent role for this many hours (0 for permanent)"] duration: Option<u64>,
+    #[description = "Minimum level to buy role (0 for none)"] min_level: Option<u64>,
+    #[description = "How many members can own role at once (0 for unlimited)"] stock: Option<u64>,
+    #[description = "Part of price returned when selling back (0 to disable)"] #[min = 0] #[max = 1] sell_back: Option<f32>,
+) -> Result<(), Error> {
+    let item: Result<Option<RoleShopItem>, async_sqlite::Error> = get_shop_item_in_roleshop_db(&ctx.data().db_client, role.id.get()).await;
+    if item.is_err() {
+        error!("Failed to get shop item with ID {}: {}", role.id.get(), item.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to check shop. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let item: Option<RoleShopItem> = item.unwrap();
+    if item.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("This role isn't in shop, use `/adm shop add` instead.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let mut item: RoleShopItem = item.unwrap();
+
+    let invalid_reason: Option<String> = validate_shop_role(ctx, &role).await?;
+    if invalid_reason.is_some() {
+        ctx.send(poise::CreateReply::default()
+            .content(invalid_reason.unwrap())
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if emoji.is_some() {
+        let icon: Option<EmojiIdentifier> = fetch_shop_emoji(ctx, &emoji.unwrap()).await;
+        if icon.is_none() {
+            ctx.send(poise::CreateReply::default()
+                .content("Emoji must be a custom emoji from PTL server.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+        let icon: EmojiIdentifier = icon.unwrap();
+        item.icon_id = icon.id.get();
+        item.icon_name = icon.name;
+    }
+
+    if price.is_some() { item.price = price.unwrap(); }
+    if description.is_some() { item.description = description.unwrap(); }
+    if display_name.is_some() { item.display_name = display_name.unwrap(); }
+    if duration.is_some() { item.duration = duration.filter(|duration| *duration > 0); }
+    if min_level.is_some() { item.min_level = min_level.filter(|min_level| *min_level > 0); }
+    if stock.is_some() { item.stock = stock.filter(|stock| *stock > 0); }
+    if sell_back.is_some() { item.sell_back = sell_back.filter(|sell_back| *sell_back > 0.0); }
+
+    let saved: Result<usize, async_sqlite::Error> = save_shop_item_in_roleshop_db(&ctx.data().db_client, item.clone()).await;
+    if saved.is_err() {
+        error!("Failed to edit {} role in shop: {}", role.id.get(), saved.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to edit role in shop. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Successfully edited <:{}:{}> **{}** (<@&{}>) • **${}**\n*{}*{}", item.icon_name, item.icon_id, item.display_name, item.id, item.price, item.description, format_roleshop_item_details(&item)))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Remove role from shop (members who bought it keep it)
+#[poise::command(slash_command, rename = "remove")]
+pub async fn shop_remove(
+    ctx: Context<'_>,
+    #[description = "Role in shop"] role: Role,
+) -> Result<(), Error> {
+    let removed: Result<usize, async_sqlite::Error> = remove_shop_item_in_roleshop_db(&ctx.data().db_client, role.id.get()).await;
+    if removed.is_err() {
+        error!("Failed to remove {} role from shop: {}", role.id.get(), removed.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to remove role from shop. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if removed.unwrap() == 0 {
+        ctx.send(poise::CreateReply::default()
+            .content("This role isn't in shop.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Successfully removed <@&{}> from shop.", role.id.get()))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// List all roles in shop
+#[poise::command(slash_command, rename = "list")]
+pub async fn shop_list(
+    ctx: Context<'_>
+) -> Result<(), Error> {
+    let items: Result<Vec<RoleShopItem>, async_sqlite::Error> = get_shop_items_in_roleshop_db(&ctx.data().db_client).await;
+    if items.is_err() {
+        error!("Failed to get shop items: {}", items.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to get shop items. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let items: Vec<RoleShopItem> = items.unwrap();
+
+    let response: String = if items.is_empty() {
+        "Shop is empty.".to_string()
+    } else {
+        items.iter().map(|item| format!("<:{}:{}> **{}** (<@&{}>) • **${}**{}", item.icon_name, item.icon_id, item.display_name, item.id, item.price, format_roleshop_item_details(item))).collect::<Vec<String>>().join("\n\n")
+    };
+
+    ctx.send(poise::CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title("Role Shop Catalogue")
+                .description(response)
+                .color(Color::from_rgb(255, 255, 255))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Ban (or unban) member from coinflip, slots and blackjack
+#[poise::command(slash_command)]
+pub async fn gamblingban(
+    ctx: Context<'_>,
+    #[description = "Member"] user: serenity::model::user::User,
+    #[description = "Ban from gambling"] banned: bool,
+    #[description = "Reason (shown to member)"] #[max_length = 200] reason: Option<String>,
+) -> Result<(), Error> {
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let changed: Result<usize, async_sqlite::Error> = if banned {
+        block_user_in_gambling_db(db_client, user.id.get(), true, reason).await
+    } else {
+        unblock_user_in_gambling_db(db_client, user.id.get(), true).await
+    };
+
+    if changed.is_err() {
+        error!("Failed to change {}'s gambling ban: {}", user.id.get(), changed.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to change gambling ban. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(poise::CreateReply::default()
+        .content(if banned { format!("Successfully banned <@{}> from gambling.", user.id) } else { format!("Successfully unbanned <@{}> from gambling.", user.id) })
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Welcome card settings (override Config.toml)
+#[poise::command(slash_command, subcommands("welcomecard_set", "welcomecard_reset"), subcommand_required)]
+pub async fn welcomecard(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Change welcome card settings, preview them with `/debug welcomecard`
+#[poise::command(slash_command, rename = "set")]
+pub async fn welcomecard_set(
+    ctx: Context<'_>,
+    #[description = "Where to send welcome cards"] #[channel_types("Text")] channel: Option<GuildChannel>,
+    #[description = "Reaction emoji (e.g. <a:wave:1367090774453522502> or 👋), \"none\" to not react"] reaction: Option<String>,
+    #[description = "Message, {mention}, {username} and {member_count} are replaced"] #[max_length = 1000] message: Option<String>,
+    #[description = "New template image (2048x1024 PNG works best)"] template: Option<Attachment>,
+) -> Result<(), Error> {
+    if channel.is_none() && reaction.is_none() && message.is_none() && template.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("Nothing to change.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.defer_ephemeral().await?;
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let mut settings: Vec<(&str, String)> = vec![];
+    let mut changes: Vec<String> = vec![];
+
+    if channel.is_some() {
+        let channel: GuildChannel = channel.unwrap();
+        if channel.guild_id.get() != ctx.data().config.get().guild {
+            ctx.send(poise::CreateReply::default()
+                .content("Channel must be from PTL server.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+        settings.push(("welcomecard.channel", channel.id.get().to_string()));
+        changes.push(format!("Channel: <#{}>", channel.id.get()));
+    }
+
+    if reaction.is_some() {
+        let reaction: String = reaction.unwrap().trim().to_string();
+        if reaction == "none" {
+            changes.push(String::from("Reaction: none"));
+        } else {
+            let parsed_reaction: Result<ReactionType, _> = ReactionType::try_from(reaction.as_str());
+            if parsed_reaction.is_err() {
+                ctx.send(poise::CreateReply::default()
+                    .content("Reaction must be an emoji, like 👋 or <a:wave:1367090774453522502>.")
+                    .ephemeral(true)
+                ).await?;
+                return Ok(());
+            }
+            changes.push(format!("Reaction: {}", parsed_reaction.unwrap()));
+        }
+        settings.push(("welcomecard.reaction", reaction));
+    }
+
+    if message.is_some() {
+        let message: String = message.unwrap();
+        changes.push(format!("Message: {}", message));
+        settings.push(("welcomecard.message", message));
+    }
+
+    if template.is_some() {
+        let template: Attachment = template.unwrap();
+        let downloaded: Result<Vec<u8>, serenity::Error> = template.download().await;
+        if downloaded.is_err() {
+            error!("Failed to download welcome card template {}: {}", template.url, downloaded.unwrap_err().to_string());
+            ctx.send(poise::CreateReply::default()
+                .content("Failed to download template. Check terminal logs.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+        let decoded: Result<image::DynamicImage, image::ImageError> = image::load_from_memory(&downloaded.unwrap());
+        if decoded.is_err() {
+            ctx.send(poise::CreateReply::default()
+                .content("Template must be an image.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+        let decoded: image::DynamicImage = decoded.unwrap();
+        // Re-encoded as PNG, so whatever was uploaded it can be opened same way as layout's template
+        let saved: Result<(), image::ImageError> = decoded.save(UPLOADED_TEMPLATE_PATH);
+        if saved.is_err() {
+            error!("Failed to save welcome card template: {}", saved.unwrap_err().to_string());
+            ctx.send(poise::CreateReply::default()
+                .content("Failed to save template. Check terminal logs.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+        changes.push(format!("Template: {} ({}x{})", template.filename, decoded.width(), decoded.height()));
+        settings.push(("welcomecard.template", UPLOADED_TEMPLATE_PATH.to_string()));
+    }
+
+    for (key, value) in settings {
+        let saved: Result<usize, async_sqlite::Error> = set_setting_in_settings_db(db_client, key, value).await;
+        if saved.is_err() {
+            error!("Failed to save {} setting: {}", key, saved.unwrap_err().to_string());
+            ctx.send(poise::CreateReply::default()
+                .content("Failed to save welcome card settings. Check terminal logs.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    }
+
+    warn!("{} changed welcome card settings: {}", ctx.author().name, changes.join(", "));
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Updated welcome card settings:\n{}\n-# Preview with `/debug welcomecard`.", changes.join("\n")))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Go back to welcome card settings from Config.toml
+#[poise::command(slash_command, rename = "reset")]
+pub async fn welcomecard_reset(ctx: Context<'_>) -> Result<(), Error> {
+    let reset: Result<usize, async_sqlite::Error> = reset_settings_in_settings_db(&ctx.data().db_client, "welcomecard.").await;
+    if reset.is_err() {
+        error!("Failed to reset welcome card settings: {}", reset.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to reset welcome card settings. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    // Uploaded template is kept on disk, it just isn't used anymore
+    warn!("{} reset welcome card settings", ctx.author().name);
+    ctx.send(poise::CreateReply::default()
+        .content("Welcome card settings are back to ones from Config.toml.")
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Maintenance (closes Loopchan for everyone except owner and allowlist)
+#[poise::command(slash_command, subcommands("maintenance_on", "maintenance_off", "maintenance_schedule", "maintenance_unschedule", "maintenance_allow", "maintenance_disallow", "maintenance_status"), subcommand_required)]
+pub async fn maintenance(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Start maintenance right now
+#[poise::command(slash_command, rename = "on")]
+pub async fn maintenance_on(
+    ctx: Context<'_>,
+    #[description = "How long (e.g. 30m, 2h, 1d), until turned off if not set"] until: Option<String>,
+    #[description = "Message shown to members (default one if not set)"] #[max_length = 500] message: Option<String>,
+) -> Result<(), Error> {
+    let duration: Option<u64> = until.as_deref().and_then(parse_duration).filter(|seconds| *seconds > 0);
+    if until.is_some() && duration.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("Invalid duration, use something like `30m`, `2h` or `1d`.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let until: Option<u64> = duration.map(|duration| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + duration);
+
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let mut saved: Result<usize, async_sqlite::Error> = reset_settings_in_settings_db(db_client, "maintenance.").await;
+    if saved.is_ok() {
+        saved = set_setting_in_settings_db(db_client, "maintenance.enabled", String::from("true")).await;
+    }
+    if saved.is_ok() && until.is_some() {
+        saved = set_setting_in_settings_db(db_client, "maintenance.until", until.unwrap().to_string()).await;
+    }
+    if saved.is_ok() && message.is_some() {
+        saved = set_setting_in_settings_db(db_client, "maintenance.message", message.clone().unwrap()).await;
+    }
+    if saved.is_err() {
+        error!("Failed to save maintenance: {}", saved.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to start maintenance. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    {
+        let mut state = ctx.data().maintenance.lock().await;
+        state.manual = Some(ManualMaintenance { until, message });
+        sync_maintenance_presence(ctx.serenity_context(), &mut state, &ctx.data().config.get(), &ctx.data().db_client, &ctx.data().presence).await;
+    }
+
+    warn!("{} started maintenance (until {:?})", ctx.author().name, until);
+    ctx.send(poise::CreateReply::default()
+        .content(match until {
+            Some(until) => format!("Maintenance started, it ends <t:{}:R>.", until),
+            None => String::from("Maintenance started, use `/adm maintenance off` to end it.")
+        })
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// End maintenance started with `/adm maintenance on`
+#[poise::command(slash_command, rename = "off")]
+pub async fn maintenance_off(ctx: Context<'_>) -> Result<(), Error> {
+    let reset: Result<usize, async_sqlite::Error> = reset_settings_in_settings_db(&ctx.data().db_client, "maintenance.").await;
+    if reset.is_err() {
+        error!("Failed to end maintenance: {}", reset.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to end maintenance. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let still_active: bool = {
+        let mut state = ctx.data().maintenance.lock().await;
+        state.manual = None;
+        let config = ctx.data().config.get();
+        sync_maintenance_presence(ctx.serenity_context(), &mut state, &config, &ctx.data().db_client, &ctx.data().presence).await;
+        state.active(&config, SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()).is_some()
+    };
+
+    warn!("{} ended maintenance", ctx.author().name);
+    ctx.send(poise::CreateReply::default()
+        .content(if still_active {
+            "Maintenance was turned off, but it's still active because of `maintenance` in Config.toml or scheduled window."
+        } else {
+            "Maintenance ended."
+        })
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Schedule maintenance window
+#[poise::command(slash_command, rename = "schedule")]
+pub async fn maintenance_schedule(
+    ctx: Context<'_>,
+    #[description = "Start (YYYY-MM-DD HH:MM, UTC)"] start: String,
+    #[description = "How long (e.g. 30m, 2h, 1d)"] duration: String,
+    #[description = "Message shown to members (default one if not set)"] #[max_length = 500] message: Option<String>,
+) -> Result<(), Error> {
+    let starts_at: Option<u64> = chrono::NaiveDateTime::parse_from_str(&start, "%Y-%m-%d %H:%M").ok()
+        .map(|start| start.and_utc().timestamp() as u64);
+    let duration: Option<u64> = parse_duration(&duration).filter(|seconds| *seconds > 0);
+    if starts_at.is_none() || duration.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("Start must be in `YYYY-MM-DD HH:MM` format (UTC) and duration like `30m`, `2h` or `1d`.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let starts_at: u64 = starts_at.unwrap();
+    let ends_at: u64 = starts_at + duration.unwrap();
+    if ends_at <= SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() {
+        ctx.send(poise::CreateReply::default()
+            .content("This window has already ended.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let window_id: Result<i64, async_sqlite::Error> = create_window_in_maintenance_db(&ctx.data().db_client, starts_at, ends_at, message.clone(), ctx.author().id.get()).await;
+    if window_id.is_err() {
+        error!("Failed to schedule maintenance: {}", window_id.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to schedule maintenance. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let window_id: i64 = window_id.unwrap();
+
+    {
+        let mut state = ctx.data().maintenance.lock().await;
+        state.windows.push(DataMaintenanceWindow { id: window_id, starts_at, ends_at, message, created_by: ctx.author().id.get() });
+        state.windows.sort_by_key(|window| window.starts_at);
+        sync_maintenance_presence(ctx.serenity_context(), &mut state, &ctx.data().config.get(), &ctx.data().db_client, &ctx.data().presence).await;
+    }
+
+    warn!("{} scheduled maintenance window #{} ({} - {})", ctx.author().name, window_id, starts_at, ends_at);
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Scheduled maintenance window **#{}** from <t:{}:f> to <t:{}:f>.", window_id, starts_at, ends_at))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Remove scheduled maintenance window (also ends it if it's active)
+#[poise::command(slash_command, rename = "unschedule")]
+pub async fn maintenance_unschedule(
+    ctx: Context<'_>,
+    #[description = "Window ID (see /adm maintenance status)"] id: i64,
+) -> Result<(), Error> {
+    let removed: Result<usize, async_sqlite::Error> = remove_window_in_maintenance_db(&ctx.data().db_client, id).await;
+    if removed.is_err() {
+        error!("Failed to remove maintenance window #{}: {}", id, removed.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to remove maintenance window. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    if removed.unwrap() == 0 {
+        ctx.send(poise::CreateReply::default()
+            .content("There's no such maintenance window.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    {
+        let mut state = ctx.data().maintenance.lock().await;
+        state.windows.retain(|window| window.id != id);
+        sync_maintenance_presence(ctx.serenity_context(), &mut state, &ctx.data().config.get(), &ctx.data().db_client, &ctx.data().presence).await;
+    }
+
+    warn!("{} removed maintenance window #{}", ctx.author().name, id);
+    ctx.send(poise::CreateReply::default()
+        .content(format!("Removed maintenance window **#{}**.", id))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Let member or everyone with role use Loopchan during maintenance
+#[poise::command(slash_command, rename = "allow")]
+pub async fn maintenance_allow(
+    ctx: Context<'_>,
+    #[description = "Member"] user: Option<serenity::model::user::User>,
+    #[description = "Role (e.g. QA testers)"] role: Option<Role>,
+) -> Result<(), Error> {
+    let (id, kind, mention) = match (&user, &role) {
+        (Some(user), None) => (user.id.get(), "user", format!("<@{}>", user.id.get())),
+        (None, Some(role)) => (role.id.get(), "role", format!("<@&{}>", role.id.get())),
+        _ => {
+            ctx.send(poise::CreateReply::default()
+                .content("Choose either member or role.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    };
+
+    let added: Result<usize, async_sqlite::Error> = add_to_allowlist_in_maintenance_db(&ctx.data().db_client, id, kind).await;
+    if added.is_err() {
+        error!("Failed to add {} to maintenance allowlist: {}", id, added.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to update maintenance allowlist. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    {
+        let mut state = ctx.data().maintenance.lock().await;
+        if kind == "role" {
+            state.allowed_roles.insert(id);
+        } else {
+            state.allowed_users.insert(id);
+        }
+    }
+
+    warn!("{} added {} {} to maintenance allowlist", ctx.author().name, kind, id);
+    ctx.send(poise::CreateReply::default()
+        .content(format!("{} can now use Loopchan during maintenance.", mention))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Remove member or role from maintenance allowlist
+#[poise::command(slash_command, rename = "disallow")]
+pub async fn maintenance_disallow(
+    ctx: Context<'_>,
+    #[description = "Member"] user: Option<serenity::model::user::User>,
+    #[description = "Role"] role: Option<Role>,
+) -> Result<(), Error> {
+    let (id, mention) = match (&user, &role) {
+        (Some(user), None) => (user.id.get(), format!("<@{}>", user.id.get())),
+        (None, Some(role)) => (role.id.get(), format!("<@&{}>", role.id.get())),
+        _ => {
+            ctx.send(poise::CreateReply::default()
+                .content("Choose either member or role.")
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    };
+
+    let removed: Result<usize, async_sqlite::Error> = remove_from_allowlist_in_maintenance_db(&ctx.data().db_client, id).await;
+    if removed.is_err() {
+        error!("Failed to remove {} from maintenance allowlist: {}", id, removed.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to update maintenance allowlist. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    {
+        let mut state = ctx.data().maintenance.lock().await;
+        state.allowed_users.remove(&id);
+        state.allowed_roles.remove(&id);
+    }
+
+    warn!("{} removed {} from maintenance allowlist", ctx.author().name, id);
+    ctx.send(poise::CreateReply::default()
+        .content(if removed.unwrap() > 0 {
+            format!("{} was removed from maintenance allowlist.", mention)
+        } else {
+            format!("{} isn't in maintenance allowlist.", mention)
+        })
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Current maintenance, scheduled windows and allowlist
+#[poise::command(slash_command, rename = "status")]
+pub async fn maintenance_status(ctx: Context<'_>) -> Result<(), Error> {
+    let config = ctx.data().config.get();
+    let state = ctx.data().maintenance.lock().await;
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    let status: String = match state.active(&config, now) {
+        Some(active) => format!("🔧 **Active**\n{}", format_maintenance_message(&active)),
+        None => String::from("Not active")
+    };
+    let windows: String = if state.windows.is_empty() {
+        String::from("None")
+    } else {
+        state.windows.iter()
+            .map(|window| format!("**#{}** <t:{}:f> - <t:{}:f> by <@{}>\n-# {}", window.id, window.starts_at, window.ends_at, window.created_by, window.message.as_deref().unwrap_or(DEFAULT_MAINTENANCE_MESSAGE)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let allowlist: Vec<String> = state.allowed_users.iter().map(|id| format!("<@{}>", id))
+        .chain(state.allowed_roles.iter().map(|id| format!("<@&{}>", id)))
+        .collect();
+    drop(state);
+
+    ctx.send(poise::CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title("Maintenance")
+                .description(status)
+                .field("Scheduled windows", windows, false)
+                .field("Allowlist", if allowlist.is_empty() { String::from("Only owner") } else { format!("<@{}> (owner), {}", config.owner, allowlist.join(", ")) }, false)
+                .footer(CreateEmbedFooter::new(if config.maintenance { "maintenance = true in Config.toml" } else { "maintenance = false in Config.toml" }))
+                .color(Color::from_rgb(255, 255, 255))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Bot presence
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner", subcommands("presence_set", "presence_clear"), subcommand_required)]
+pub async fn presence(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Show custom status instead of rotation from Config.toml
+#[poise::command(slash_command, rename = "set")]
+pub async fn presence_set(
+    ctx: Context<'_>,
+    #[description = "Status ({member_count}, {fish_caught} and {boost_event} are replaced)"] #[max_length = 128] text: String,
+    #[description = "Kind (custom status if not set)"] kind: Option<PresenceKind>,
+    #[description = "How long (e.g. 30m, 2h, 1d), until cleared if not set"] duration: Option<String>,
+) -> Result<(), Error> {
+    let seconds: Option<u64> = duration.as_deref().and_then(parse_duration).filter(|seconds| *seconds > 0);
+    if duration.is_some() && seconds.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("Invalid duration, use something like `30m`, `2h` or `1d`.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let until: Option<u64> = seconds.map(|seconds| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + seconds);
+
+    {
+        let config = ctx.data().config.get();
+        let maintenance = ctx.data().maintenance.lock().await;
+        let mut presence = ctx.data().presence.lock().await;
+        presence.custom = Some(CustomPresence { entry: PresenceEntry::new(kind.unwrap_or(PresenceKind::Custom), text.clone()), until });
+        let maintenance_active: bool = maintenance.active(&config, SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()).is_some();
+        show_presence(ctx.serenity_context(), &config, &ctx.data().db_client, maintenance_active, &mut presence, false).await;
+    }
+
+    warn!("{} set custom presence \"{}\" (until {:?})", ctx.author().name, text, until);
+    ctx.send(poise::CreateReply::default()
+        .content(match until {
+            Some(until) => format!("Custom status set, rotation continues <t:{}:R>.", until),
+            None => String::from("Custom status set, use `/adm presence clear` to go back to rotation.")
+        })
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Go back to rotation from Config.toml
+#[poise::command(slash_command, rename = "clear")]
+pub async fn presence_clear(ctx: Context<'_>) -> Result<(), Error> {
+    {
+        let config = ctx.data().config.get();
+        let maintenance = ctx.data().maintenance.lock().await;
+        let mut presence = ctx.data().presence.lock().await;
+        presence.custom = None;
+        let maintenance_active: bool = maintenance.active(&config, SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()).is_some();
+        show_presence(ctx.serenity_context(), &config, &ctx.data().db_client, maintenance_active, &mut presence, false).await;
+    }
+
+    warn!("{} cleared custom presence", ctx.author().name);
+    ctx.send(poise::CreateReply::default()
+        .content("Custom status cleared.")
+        .ephemeral(true)
+    ).await?;
+
     Ok(())
-}
\ No newline at end of file
+}
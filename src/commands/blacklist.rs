@@ -0,0 +1,37 @@
+use serenity::all::{Color, CreateEmbed};
+use tracing::error;
+
+use crate::{handlers::events::blacklist::{blacklist_components, blacklist_embed}, utils::database::blacklist::get_user_in_blacklist_db, Context, DataBlacklistEntry, Error};
+
+/// Check if you're blacklisted from PTL
+#[poise::command(slash_command, dm_only)]
+pub async fn blacklist_check(ctx: Context<'_>) -> Result<(), Error> {
+    let entry: Result<Option<DataBlacklistEntry>, async_sqlite::Error> = get_user_in_blacklist_db(&ctx.data().db_client, ctx.author().id.get()).await;
+    if entry.is_err() {
+        error!("Failed to check {}'s blacklist status: {}", ctx.author().id.get(), entry.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("❌ Failed to check your blacklist status! Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+        ).await?;
+        return Ok(());
+    }
+
+    let reply: poise::CreateReply = match entry.unwrap() {
+        Some(entry) => poise::CreateReply::default()
+            .embed(blacklist_embed(&entry, &ctx.data().config.get()))
+            .components(blacklist_components(&ctx.data().config.get())),
+        None => poise::CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("You are not blacklisted from **PARKOUR: The Loop**. <a:haphap:1367093618967318618>")
+                    .color(Color::from_rgb(100, 255, 100))
+            )
+    };
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
@@ -1,10 +1,11 @@
-use serenity::all::{ButtonStyle, Color, CreateActionRow, CreateButton, CreateEmbed, CreateMessage};
+use serenity::all::{ButtonStyle, Color, CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateMessage, Member};
 
-use crate::{Context, Error};
+use crate::{handlers::events::welcomecard::{format_welcome_message, get_welcomecard_settings, render_welcomecard, WelcomecardSettings}, Context, Error};
+use crate::utils::permissions::PermissionLevel;
 use tracing::error;
 
 /// Bot Debug Commands
-#[poise::command(slash_command, subcommands("ping", "register", "wordgen", "postverificationchannellink", "clearlogs"), subcommand_required)]
+#[poise::command(slash_command, subcommands("ping", "register", "wordgen", "postverificationchannellink", "clearlogs", "welcomecard"), subcommand_required)]
 pub async fn debug(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
 
 /// Check bot latency
@@ -22,7 +23,7 @@ pub async fn ping(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-#[poise::command(slash_command)]
+#[poise::command(slash_command, custom_data = "PermissionLevel::Staff")]
 pub async fn wordgen(
     ctx: Context<'_>,
     #[min_length = 1] #[max_length = 254] #[description = "Amount"] amount: u8
@@ -38,7 +39,7 @@ pub async fn wordgen(
     Ok(())
 }
 
-#[poise::command(slash_command)]
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner")]
 pub async fn clearlogs(
     ctx: Context<'_>,
 ) -> Result<(), Error> {
@@ -80,11 +81,11 @@ pub async fn clearlogs(
     Ok(())
 }
 
-#[poise::command(slash_command)]
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner")]
 pub async fn postverificationchannellink(
     ctx: Context<'_>,
 ) -> Result<(), Error> {
-    let loopchans_config = &ctx.data().config;
+    let loopchans_config = ctx.data().config.get();
     ctx.channel_id().send_message(ctx, CreateMessage::default()
         .add_embed(
             CreateEmbed::default()
@@ -109,7 +110,53 @@ pub async fn postverificationchannellink(
     Ok(())
 }
 
-#[poise::command(slash_command)]
+/// Preview welcome card without posting it to welcomes channel
+#[poise::command(slash_command, custom_data = "PermissionLevel::Staff")]
+pub async fn welcomecard(
+    ctx: Context<'_>,
+    #[description = "Member to render welcome card for (you if not set)"] member: Option<Member>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user: serenity::all::User = match member {
+        Some(member) => member.user,
+        None => ctx.author().clone()
+    };
+    let data: &crate::Data = ctx.data();
+    let member_count: u64 = ctx.serenity_context().cache.guild(data.config.get().guild).map(|guild| guild.member_count).unwrap_or(0);
+    let settings: WelcomecardSettings = get_welcomecard_settings(data).await;
+
+    let card: Result<(Vec<u8>, &str), Error> = render_welcomecard(&user, member_count, data.config.get().welcomecard.animated.unwrap_or(false), settings.template.as_ref()).await;
+    if card.is_err() {
+        error!("Failed to render {}'s welcome card preview: {}", user.name, card.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to render welcome card. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let (bytes, extension): (Vec<u8>, &str) = card.unwrap();
+    let attachment: CreateAttachment = CreateAttachment::bytes(bytes, format!("welcomecard{}.{}", user.id.get(), extension));
+
+    let channel: String = settings.channel.map(|channel| format!("<#{}>", channel)).unwrap_or(String::from("not set"));
+    let reaction: String = settings.reaction.map(|reaction| reaction.to_string()).unwrap_or(String::from("none"));
+
+    ctx.send(poise::CreateReply::default()
+        .content(format!("-# Channel: {} • Reaction: {}", channel, reaction))
+        .embed(
+            CreateEmbed::default()
+                .description(format_welcome_message(&settings.message, &user, member_count))
+                .attachment(attachment.filename.clone())
+                .color(Color::from_rgb(255, 255, 255))
+        )
+        .attachment(attachment)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner")]
 pub async fn register(ctx: Context<'_>) -> Result<(), Error> {
     poise::builtins::register_application_commands_buttons(ctx).await?;
     Ok(())
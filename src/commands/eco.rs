@@ -1,23 +1,42 @@
-use std::{time::Duration, vec};
+use std::{time::{Duration, Instant}, vec};
 
-use crate::{utils::{basic::generate_emoji_progressbar, database::economy::increment_user_balance_in_eco_db}, Context, Error, RoleShopItem};
+use crate::{handlers::events::roleshop::{format_roleshop_item_details, sell_back_price}, utils::{basic::generate_emoji_progressbar, database::{ledger::{change_user_balance_in_eco_db, get_user_entries_in_ledger_db, LedgerReason}, roleshop::{count_role_owners_in_roleshop_db, get_shop_items_in_roleshop_db, get_user_role_purchases_in_roleshop_db}}}, Context, DataLedgerDailyTotal, DataLedgerEntry, DataRolePurchase, Error, RoleShopItem};
+use crate::utils::permissions::PermissionLevel;
 
-use poise::{CooldownConfig, CreateReply};
+use poise::CreateReply;
 use rand::Rng;
-use serenity::all::{Color, CreateActionRow, CreateButton, CreateEmbed, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, ReactionType};
+use serenity::all::{ButtonStyle, Color, CreateActionRow, CreateAllowedMentions, CreateAttachment, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, ReactionType};
 use tracing::{error, info};
 
+use crate::commands::inventory::{BACKGROUND_SLOT, BADGE_SLOT};
+use crate::handlers::events::leaderboard::{build_leaderboard_page, get_guild_member_ids};
+use crate::handlers::events::blackjack::{blackjack_components, blackjack_embed, finish_blackjack_game, BLACKJACK_TIMEOUT};
+use crate::utils::database::gambling::{block_user_in_gambling_db, get_user_gambling_block_in_gambling_db, place_bet_in_gambling_db, settle_bet_in_gambling_db, unblock_user_in_gambling_db, BetResult};
+use crate::utils::basic::{fish_size_and_value, gini_coefficient, item_from_id, median};
+use crate::utils::profilecard::{render_profile_card, ProfileCard};
+use crate::utils::render::download_image;
+use crate::utils::database::fishing::get_user_fishes_in_fishing_db;
+use crate::utils::database::inventory::get_user_equipped_items_in_inventory_db;
+use crate::utils::database::linking::get_roblox_id_in_users_db_by_discord_id;
+use crate::{DataEquippedItem, DataFish, ItemEffect};
+use crate::utils::charts::render_minted_burned_chart;
+use crate::utils::database::fishing::count_catches_by_type_in_fishing_db;
+use crate::utils::database::ledger::{count_active_earners_in_ledger_db, get_daily_totals_in_ledger_db};
+use crate::utils::gambling::{apply_house_edge, flip_coin, spin_slots, BlackjackGame, BlackjackOutcome};
+use crate::utils::database::cooldowns::{reset_user_cooldowns_in_cooldowns_db, try_start_user_cooldown_in_cooldowns_db};
 use crate::utils::database::economy::{
     create_user_in_eco_db,
     get_user_balance_in_eco_db,
     get_user_level_and_experience_in_eco_db,
     update_user_level_and_experience_in_eco_db,
-    build_balance_leaderboard_from_eco_db,
-    build_level_leaderboard_from_eco_db,
     get_user_placement_in_balance_leaderboard,
-    get_user_placement_in_level_leaderboard
+    get_user_placement_in_level_leaderboard,
+    get_all_balances_in_eco_db,
+    transfer_user_balance_in_eco_db
 };
 
+pub const WORK_COOLDOWN_ACTION: &str = "eco.work";
+
 pub fn exp_needed_to_next_level(current_level: u64) -> u64 {
     let level: f64 = current_level as f64;
     return (5.0 * (level.powf(2.5)) + (100.0 * level) + 100.0).ceil() as u64;
@@ -94,10 +113,10 @@ pub async fn give_user_eco_exp(
 }
 
 /// Economics Commands
-#[poise::command(slash_command, subcommands("balance", "level", "modify_data", "leaderboard", "roleshop", "work"), subcommand_required)]
+#[poise::command(slash_command, subcommands("balance", "level", "modify_data", "modify_balance", "leaderboard", "roleshop", "work", "pay", "history", "coinflip", "slots", "blackjack", "gambling_optout", "stats", "profile"), subcommand_required)]
 pub async fn eco(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
 
-#[poise::command(slash_command)]
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner")]
 pub async fn modify_data(
     ctx: Context<'_>,
     #[description = "Member"] user: Option<serenity::model::user::User>,
@@ -183,6 +202,51 @@ pub async fn modify_data(
     Ok(())
 }
 
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner")]
+pub async fn modify_balance(
+    ctx: Context<'_>,
+    #[description = "Member"] user: serenity::model::user::User,
+    #[description = "Amount to add (negative to take)"] amount: i64,
+    #[description = "Reason (saved in ledger)"] #[max_length = 200] reason: Option<String>
+) -> Result<(), Error> {
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let user_id: u64 = user.id.get();
+
+    create_user_in_eco_db(db_client, user_id).await?;
+
+    let reference: String = match reason {
+        Some(reason) => format!("by:{} {}", ctx.author().id, reason),
+        None => format!("by:{}", ctx.author().id)
+    };
+
+    let changed: Result<Option<u64>, async_sqlite::Error> = change_user_balance_in_eco_db(db_client, user_id, amount, LedgerReason::Admin, Some(reference)).await;
+
+    if changed.is_err() {
+        error!("Failed to modify {}'s balance: {}", user_id, changed.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .content("Failed to modify balance! (check console~)")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let new_balance: Option<u64> = changed.unwrap();
+    if new_balance.is_none() {
+        ctx.send(CreateReply::default()
+            .content("User doesn't have enough coins to take that much.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    info!("{} modified {}'s balance by {} (now {})", ctx.author().id, user_id, amount, new_balance.unwrap());
+    ctx.send(CreateReply::default()
+        .content(format!("Successful\n-# New balance: {}", new_balance.unwrap()))
+    ).await?;
+
+    Ok(())
+}
+
 /// Check your balance or balance of other member
 #[poise::command(slash_command)]
 pub async fn balance(
@@ -276,7 +340,7 @@ pub async fn level(
     let level: u64 = level_and_exp_checks.0.unwrap();
     let experience: u64 = level_and_exp_checks.1.unwrap();
     let experience_needed: u64 = exp_needed_to_next_level(level);
-    let progressbar: String = generate_emoji_progressbar(experience, experience_needed, custom_data.config.leveling.progrees_bar_size, &custom_data.config.progressbar_emojis);
+    let progressbar: String = generate_emoji_progressbar(experience, experience_needed, custom_data.config.get().leveling.progrees_bar_size, &custom_data.config.get().progressbar_emojis);
     let percentage: f64 = experience as f64/experience_needed as f64 *100.0;
     let percentage_text: String = format!("` {}% `", percentage.floor() as u64);
 
@@ -302,87 +366,33 @@ pub enum LeaderboardType {
 #[poise::command(slash_command, aliases("lb", "top"))]
 pub async fn leaderboard(
     ctx: Context<'_>,
-    #[description = "Leaderboard Type"] lbtype: LeaderboardType
+    #[description = "Leaderboard Type"] lbtype: LeaderboardType,
+    #[description = "Page to open (opens page with your placement if not set)"] page: Option<u64>
 ) -> Result<(), Error> {
-    let db_client = &ctx.data().db_client;
-    if lbtype == LeaderboardType::Level {
-        let lb: Result<Vec<(u64, u64, u64)>, async_sqlite::Error> = build_level_leaderboard_from_eco_db(db_client).await;
-
-        let mut response = String::from("");
-        for (index, (discord_id, level, experience)) in lb.unwrap().iter().enumerate() {
-            let placement_emoji: &str;
-            if index == 0 {
-                placement_emoji = "<a:WINNER:1367093328864346122>";
-            } else if index == 1 {
-                placement_emoji = ":second_place:";
-            } else if index == 2 {
-                placement_emoji = ":third_place:";
-            } else {
-                placement_emoji = "";
-            }
-
-            let experience_needed: u64 = exp_needed_to_next_level(*level);
-            let custom_data = &ctx.data();
-            let progressbar: String = generate_emoji_progressbar(*experience, experience_needed, custom_data.config.leveling.progress_bar_in_leaderboard_size, &custom_data.config.progressbar_emojis);
-
-            response.push_str(&format!("{} **{}.** <@{}> •\n<:LoopchanLevel:1368298876842279072> Level: {}\n<:LoopchanExp:1368298874803982479> Experience: {}/{}{}\n\n", placement_emoji, index + 1, discord_id, level, experience, experience_needed, progressbar));
-        }
-
-        response.push_str("-# Leaderboard is limited to 5 places.");
-        let placement: Result<u8, async_sqlite::Error> = get_user_placement_in_level_leaderboard(db_client, ctx.author().id.get()).await;
-        if placement.is_ok() {
-            response.push_str(&format!("\n-# Your placement is #{}. <a:haphap:1367093618967318618>", placement.unwrap()));
-        } else {
-            response.push_str("\n-# Failed to fetch your placement.");
-            error!("Failed to fetch {}'s placement: {}", ctx.author().name, placement.unwrap_err().to_string());
-        }
-        
+    let leaderboard = build_leaderboard_page(
+        ctx.serenity_context(),
+        ctx.data(),
+        &lbtype,
+        page.map(|page| page.saturating_sub(1)),
+        ctx.author().id.get()
+    ).await;
+
+    if leaderboard.is_err() {
+        error!("Failed to build leaderboard for {}: {}", ctx.author().name, leaderboard.unwrap_err().to_string());
         ctx.send(CreateReply::default()
             .embed(CreateEmbed::default()
-                .title("<a:qtstar:1367089440073318501> Level Leaderboard")
-                .description(response)
-                .color(Color::from_rgb(255, 255, 255))
+                .description("❌ Failed to load leaderboard! Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
             )
+            .ephemeral(true)
         ).await?;
-
         return Ok(());
     }
+    let (embed, components) = leaderboard.unwrap();
 
-    let lb: Result<Vec<(u64, u64)>, async_sqlite::Error> = build_balance_leaderboard_from_eco_db(db_client).await;
-
-    let mut response = String::from("");
-
-    for (index, (discord_id, balance)) in lb.unwrap().iter().enumerate() {
-        if index == 0 {
-            response.push_str(&format!("<a:WINNER:1367093328864346122> **1.** <@{}> •\n<:LoopchanCoin:1368311103238570025> Balance: {}\n\n", discord_id, balance));
-            continue;
-        }
-        if index == 1 {
-            response.push_str(&format!(":second_place: **2.** <@{}> •\n<:LoopchanCoin:1368311103238570025> Balance: {}\n\n", discord_id, balance,));
-            continue;
-        }
-        if index == 2 {
-            response.push_str(&format!(":third_place: **3.** <@{}> •\n<:LoopchanCoin:1368311103238570025> Balance: {}\n\n", discord_id, balance));
-            continue;
-        }
-        response.push_str(&format!("**{}.** <@{}> •\n<:LoopchanCoin:1368311103238570025> Balance: {}\n\n", index + 1, discord_id, balance));
-    }
-
-    response.push_str("-# Leaderboard is limited to 5 places.");
-    let placement: Result<u8, async_sqlite::Error> = get_user_placement_in_balance_leaderboard(db_client, ctx.author().id.get()).await;
-    if placement.is_ok() {
-        response.push_str(&format!("\n-# Your placement is #{}.", placement.unwrap()));
-    } else {
-        response.push_str("\n-# Failed to fetch your placement.");
-        error!("Failed to fetch {}'s placement: {}", ctx.author().name, placement.unwrap_err().to_string());
-    }
-    
     ctx.send(CreateReply::default()
-        .embed(CreateEmbed::default()
-            .title("<a:qtstar:1367089440073318501> Balance Leaderboard")
-            .description(response)
-            .color(Color::from_rgb(255, 255, 255))
-        )
+        .embed(embed)
+        .components(components)
     ).await?;
 
     Ok(())
@@ -393,14 +403,53 @@ pub async fn leaderboard(
 pub async fn roleshop(
     ctx: Context<'_>
 ) -> Result<(), Error> {
-    let loopchans_config: &crate::LoopchanConfig = &ctx.data().config;
-    let shop_items: &Vec<RoleShopItem> = &loopchans_config.economy.shop_items;
+    let loopchans_config: std::sync::Arc<crate::LoopchanConfig> = ctx.data().config.get();
+    let db_client = &ctx.data().db_client;
+
+    let shop_items: Result<Vec<RoleShopItem>, async_sqlite::Error> = get_shop_items_in_roleshop_db(db_client).await;
+    if shop_items.is_err() {
+        error!("Failed to get shop items: {}", shop_items.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("Failed to open role shop! Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let shop_items: Vec<RoleShopItem> = shop_items.unwrap();
+
+    if shop_items.is_empty() {
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .title("Role Shop")
+                    .description("Role shop is empty right now, come back later!")
+                    .color(Color::from_rgb(255, 255, 255))
+            )
+        ).await?;
+        return Ok(());
+    }
+
+    let purchases: Result<Vec<DataRolePurchase>, async_sqlite::Error> = get_user_role_purchases_in_roleshop_db(db_client, ctx.author().id.get()).await;
+    let purchases: Vec<DataRolePurchase> = match purchases {
+        Ok(purchases) => purchases,
+        Err(err) => {
+            error!("Failed to get {}'s role purchases: {}", ctx.author().id.get(), err.to_string());
+            vec![]
+        }
+    };
+
+    let mut sell_buttons: Vec<CreateButton> = vec![];
+    let mut owned_response: String = String::new();
 
     let mut options_vec: Vec<CreateSelectMenuOption> = vec![];
 
     let mut response: String = String::new();
-    let mut item_index: u8 = 0;
-    for item in shop_items {
+    let mut item_index: usize = 0;
+    for item in &shop_items {
         item_index += 1;
 
         let item_emoji: ReactionType = ReactionType::Custom {
@@ -409,23 +458,64 @@ pub async fn roleshop(
             name: Some(item.icon_name.clone())
         };
 
-        options_vec.push(
-            CreateSelectMenuOption::new(format!("{} • ${}", &item.display_name, &item.price), format!("roleshop.{}", item.id))
-                .emoji(item_emoji)
-                .description(&item.description)
-        );
+        // Select menus can't have more than 25 options
+        if options_vec.len() < 25 {
+            options_vec.push(
+                CreateSelectMenuOption::new(format!("{} • ${}", &item.display_name, &item.price), format!("roleshop.{}", item.id))
+                    .emoji(item_emoji)
+                    .description(&item.description)
+            );
+        }
+
+        let mut details: String = format_roleshop_item_details(item);
+        if item.stock.is_some() {
+            let owners: Result<u64, async_sqlite::Error> = count_role_owners_in_roleshop_db(db_client, item.id).await;
+            if owners.is_ok() {
+                details.push_str(&format!("\nIn stock: **{}/{}**", item.stock.unwrap().saturating_sub(owners.unwrap()), item.stock.unwrap()));
+            }
+        }
 
-        response.push_str(&format!("<:{}:{}> **{}.** {} • *${}*\n*{}*\n\n",
+        response.push_str(&format!("<:{}:{}> **{}.** {} • *${}*\n*{}*{}\n\n",
             item.icon_name,
             item.icon_id,
             item_index,
             item.display_name,
             item.price,
             item.description,
+            details,
         ));
+
+        let purchase: Option<&DataRolePurchase> = purchases.iter().find(|purchase| purchase.role_id == item.id);
+        if purchase.is_some() {
+            let purchase: &DataRolePurchase = purchase.unwrap();
+            let expiry_text: String = match purchase.expires_at {
+                Some(expires_at) => format!(", expires <t:{}:R>", expires_at),
+                None => String::new()
+            };
+            owned_response.push_str(&format!("<:{}:{}> {} • bought <t:{}:R>{}\n",
+                item.icon_name,
+                item.icon_id,
+                item.display_name,
+                purchase.purchased_at,
+                expiry_text
+            ));
+
+            // Discord allows 5 buttons per row, keep the rest of rows for selector and boost link
+            if item.sell_back.is_some() && sell_buttons.len() < 15 {
+                sell_buttons.push(
+                    CreateButton::new(format!("roleshop.sell.{}", item.id))
+                        .label(format!("Sell {} • ${}", item.display_name, sell_back_price(item)))
+                        .style(ButtonStyle::Secondary)
+                );
+            }
+        }
+    }
+
+    if !owned_response.is_empty() {
+        response.push_str(&format!("**=====**\n**Your roles:**\n{}", owned_response));
     }
 
-    let components: Vec<CreateActionRow> = vec![
+    let mut components: Vec<CreateActionRow> = vec![
         CreateActionRow::SelectMenu(
             CreateSelectMenu::new("roleshop.selector",
                 CreateSelectMenuKind::String {
@@ -442,6 +532,10 @@ pub async fn roleshop(
         )
     ];
 
+    for row in sell_buttons.chunks(5) {
+        components.push(CreateActionRow::Buttons(row.to_vec()));
+    }
+
     if loopchans_config.economy.shop_not_level_3_warn {
         ctx.send(CreateReply::default()
             .embed(CreateEmbed::default()
@@ -470,21 +564,34 @@ pub async fn roleshop(
 pub async fn work(
     ctx: Context<'_>,
 ) -> Result<(), Error> {
-    let economy_config = &ctx.data().config.economy;
+    let economy_config = &ctx.data().config.get().economy;
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let author_id: u64 = ctx.author().id.get();
     let on_cooldown: i32;
 
     {
-        let mut cooldown_tracker = ctx.command().cooldowns.lock().unwrap();
+        let remaining_cooldown: Result<Option<Duration>, async_sqlite::Error> = try_start_user_cooldown_in_cooldowns_db(db_client, author_id, WORK_COOLDOWN_ACTION.to_string(), Duration::from_secs(economy_config.work_cooldown*60)).await;
+        if remaining_cooldown.is_err() {
+            error!("Failed to check {}'s work cooldown: {}", author_id, remaining_cooldown.unwrap_err().to_string());
 
-        let mut cooldown_durations: CooldownConfig = CooldownConfig::default();
-        cooldown_durations.user = Some(Duration::from_secs(economy_config.work_cooldown*60));
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description("❌ Failed to check your cooldown. Please try again later, if the issue persists contact <@908779319084589067>")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .ephemeral(true)
+            ).await?;
+
+            return Ok(());
+        }
 
-        match cooldown_tracker.remaining_cooldown(ctx.cooldown_context(), &cooldown_durations) {
+        match remaining_cooldown.unwrap() {
             Some(remaining) => {
                 on_cooldown = remaining.as_secs() as i32;
             }
             None => {
-                cooldown_tracker.start_cooldown(ctx.cooldown_context());
                 on_cooldown = -1;
             },
         }
@@ -532,10 +639,8 @@ pub async fn work(
         return Ok(());
     }
 
-    let author_id: u64 = ctx.author().id.get();
-
     let add_to_balance: u64 = rand::rng().random_range(economy_config.work_payment[0]..economy_config.work_payment[1]).try_into().unwrap();
-    let incremented_check: Result<usize, async_sqlite::Error> = increment_user_balance_in_eco_db(&ctx.data().db_client, author_id, add_to_balance).await;
+    let incremented_check: Result<Option<u64>, async_sqlite::Error> = change_user_balance_in_eco_db(db_client, author_id, add_to_balance as i64, LedgerReason::Work, None).await;
 
     if incremented_check.is_err() {
         error!("Failed to update {}'s balance: {}", author_id, incremented_check.unwrap_err().to_string());
@@ -550,7 +655,7 @@ pub async fn work(
                 .ephemeral(true)
         ).await?;
 
-        ctx.set_invocation_data(true).await; // cancel cooldown (hopefully)
+        reset_user_cooldowns_in_cooldowns_db(db_client, author_id, Some(WORK_COOLDOWN_ACTION.to_string())).await?; // cancel cooldown
 
         return Ok(());
     }
@@ -567,4 +672,855 @@ pub async fn work(
     ).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn calculate_transfer_fee(amount: u64, fee: Option<f32>) -> u64 {
+    if fee.is_none() {
+        return 0;
+    }
+    (amount as f64 * fee.unwrap() as f64).ceil() as u64
+}
+
+/// Pay coins to other member
+#[poise::command(slash_command)]
+pub async fn pay(
+    ctx: Context<'_>,
+    #[description = "Member"] user: serenity::model::user::User,
+    #[description = "Amount of coins"] #[min = 1] amount: u64,
+    #[description = "Note for receiver"] #[max_length = 200] note: Option<String>
+) -> Result<(), Error> {
+    let custom_data = ctx.data();
+    let economy_config = &custom_data.config.get().economy;
+    let author = ctx.author();
+
+    if user.id == author.id {
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ You can't pay yourself.")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    if user.bot {
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ You can't pay bots.")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    if amount < economy_config.pay_min || amount > economy_config.pay_max {
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description(format!("❌ You can only pay from **${}** to **${}** at once.", economy_config.pay_min, economy_config.pay_max))
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    let fee: u64 = calculate_transfer_fee(amount, economy_config.pay_fee);
+
+    let balance_check: Result<u64, async_sqlite::Error> = get_user_balance_in_eco_db(&custom_data.db_client, author.id.get()).await;
+    if balance_check.is_err() {
+        error!("Failed to check {}'s balance: {}", author.id, balance_check.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ Failed to check your balance. Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if balance_check.unwrap() < amount+fee {
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description(format!("❌ Insufficient Funds! You need **${}** *(including ${} fee)*.", amount+fee, fee))
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let note_text: String = match &note {
+        Some(note) => format!("\nNote: *{}*", note),
+        None => String::new()
+    };
+
+    let reply: poise::ReplyHandle<'_> = ctx.send(CreateReply::default()
+        .embed(CreateEmbed::default()
+            .title("Payment")
+            .description(format!("**Are you sure** you want to pay **${}** to <@{}>?\nFee: **${}**\nTotal: **${}**{}", amount, user.id, fee, amount+fee, note_text))
+            .color(Color::from_rgb(255, 255, 255))
+        )
+        .components(vec![
+            CreateActionRow::Buttons(vec![
+                CreateButton::new("eco.pay.confirm")
+                    .label("Pay")
+                    .style(ButtonStyle::Success),
+                CreateButton::new("eco.pay.cancel")
+                    .label("Cancel")
+                    .style(ButtonStyle::Danger),
+        ])])
+        .ephemeral(true)
+    ).await?;
+
+    let interaction = reply
+        .message()
+        .await?
+        .await_component_interaction(ctx)
+        .author_id(author.id)
+        .timeout(Duration::new(60, 0))
+        .await;
+
+    if interaction.is_none() {
+        reply.edit(ctx, CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("Timed out.")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .components(vec![])
+        ).await?;
+        return Ok(());
+    }
+
+    let interaction = interaction.unwrap();
+
+    if interaction.data.custom_id == "eco.pay.cancel" {
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::default()
+                    .description("Cancelled.")
+                    .color(Color::from_rgb(255, 100, 100))
+                )
+                .components(vec![])
+        )).await?;
+        return Ok(());
+    }
+
+    let transfer: Result<Option<i64>, async_sqlite::Error> = transfer_user_balance_in_eco_db(&custom_data.db_client, author.id.get(), user.id.get(), amount, fee, note).await;
+
+    if transfer.is_err() {
+        error!("Failed to transfer ${} from {} to {}: {}", amount, author.id, user.id, transfer.unwrap_err().to_string());
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::default()
+                    .description("❌ Failed to transfer coins. Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+                )
+                .components(vec![])
+        )).await?;
+        return Ok(());
+    }
+
+    let transfer_id: Option<i64> = transfer.unwrap();
+    if transfer_id.is_none() {
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::default()
+                    .description("❌ Insufficient Funds!")
+                    .color(Color::from_rgb(255, 100, 100))
+                )
+                .components(vec![])
+        )).await?;
+        return Ok(());
+    }
+
+    info!("{} paid ${} (+${} fee) to {} (transfer #{})", author.id, amount, fee, user.id, transfer_id.unwrap());
+
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::default()
+                .description(format!("✅ Successfully paid **${}** to <@{}>!\n-# Transfer #{}", amount, user.id, transfer_id.unwrap()))
+                .color(Color::from_rgb(100, 255, 100))
+            )
+            .components(vec![])
+    )).await?;
+
+    ctx.send(CreateReply::default()
+        .content(format!("<@{}> sent **${}** to <@{}>!{}", author.id, amount, user.id, note_text))
+        // Note is written by payer, so only recipient is pinged
+        .allowed_mentions(CreateAllowedMentions::new().users(vec![user.id]))
+    ).await?;
+
+    Ok(())
+}
+
+pub fn format_ledger_entry(entry: &DataLedgerEntry) -> String {
+    let sign: &str = if entry.amount >= 0 { "+" } else { "-" };
+    let reference: String = match &entry.reference {
+        Some(reference) => format!(" • `{}`", reference),
+        None => String::new()
+    };
+    format!("`#{}` <t:{}:R> **{}${}** • {}{}\n-# Balance after: ${}",
+        entry.id,
+        entry.created_at,
+        sign,
+        entry.amount.unsigned_abs(),
+        entry.reason,
+        reference,
+        entry.balance_after
+    )
+}
+
+const HISTORY_PAGE_SIZE: u64 = 10;
+
+/// Check history of your balance changes
+#[poise::command(slash_command)]
+pub async fn history(
+    ctx: Context<'_>,
+    #[description = "Page"] #[min = 1] page: Option<u64>
+) -> Result<(), Error> {
+    let author_id: u64 = ctx.author().id.get();
+    let page: u64 = page.unwrap_or(1);
+
+    let entries: Result<Vec<DataLedgerEntry>, async_sqlite::Error> = get_user_entries_in_ledger_db(&ctx.data().db_client, author_id, HISTORY_PAGE_SIZE, (page-1)*HISTORY_PAGE_SIZE).await;
+
+    if entries.is_err() {
+        error!("Failed to get {}'s ledger entries: {}", author_id, entries.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ Failed to get your history. Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let entries: Vec<DataLedgerEntry> = entries.unwrap();
+    if entries.is_empty() {
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .title("Balance History")
+                .description(if page == 1 { "Your balance has never changed yet." } else { "There is nothing on this page." })
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let response: String = entries.iter().map(format_ledger_entry).collect::<Vec<String>>().join("\n");
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::default()
+            .title(format!("Balance History{}", if page != 1 { format!(" | Page {}", page) } else { "".to_string() }))
+            .description(response)
+            .color(Color::from_rgb(255, 255, 255))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+const GAMBLING_OPTOUT_MIN_DURATION: u64 = 24*60*60; // Opting back in is only possible after a day
+
+/// Checks limits and opt-outs before placing a bet, replies with reason if user can't gamble
+async fn can_user_gamble(ctx: Context<'_>, bet: u64) -> Result<bool, Error> {
+    let economy_config = &ctx.data().config.get().economy;
+    let author_id: u64 = ctx.author().id.get();
+
+    if bet < economy_config.gamble_min_bet || bet > economy_config.gamble_max_bet {
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description(format!("❌ You can only bet from **${}** to **${}** at once.", economy_config.gamble_min_bet, economy_config.gamble_max_bet))
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(false);
+    }
+
+    let block: Result<Option<(bool, Option<String>, u64)>, async_sqlite::Error> = get_user_gambling_block_in_gambling_db(&ctx.data().db_client, author_id).await;
+    if block.is_err() {
+        error!("Failed to check {}'s gambling block: {}", author_id, block.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ Failed to place your bet. Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(false);
+    }
+
+    let block: Option<(bool, Option<String>, u64)> = block.unwrap();
+    if block.is_some() {
+        let (banned, reason, _) = block.unwrap();
+        let description: String = if banned {
+            format!("❌ You were banned from gambling by staff.{}", reason.map(|reason| format!("\nReason: *{}*", reason)).unwrap_or_default())
+        } else {
+            "❌ You opted out of gambling. You can opt back in with `/eco gambling_optout`.".to_string()
+        };
+
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description(description)
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Replies with reason why bet wasn't placed, returns balance after if it was
+async fn handle_bet_result(ctx: Context<'_>, result: Result<BetResult, async_sqlite::Error>) -> Result<Option<u64>, Error> {
+    let description: String = match result {
+        Ok(BetResult::Placed(balance_after)) => return Ok(Some(balance_after)),
+        Ok(BetResult::InsufficientFunds) => "❌ Insufficient Funds!".to_string(),
+        Ok(BetResult::LossCapReached) => format!("❌ You've reached your daily loss limit of **${}**. Come back tomorrow!", ctx.data().config.get().economy.gamble_daily_loss_cap.unwrap_or(0)),
+        Err(err) => {
+            error!("Failed to place {}'s bet: {}", ctx.author().id.get(), err.to_string());
+            "❌ Failed to place your bet. Please try again later, if the issue persists contact <@908779319084589067>".to_string()
+        }
+    };
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::default()
+            .description(description)
+            .color(Color::from_rgb(255, 100, 100))
+        )
+        .ephemeral(true)
+    ).await?;
+    Ok(None)
+}
+
+/// Flip a coin, double or nothing
+#[poise::command(slash_command)]
+pub async fn coinflip(
+    ctx: Context<'_>,
+    #[description = "Amount of coins to bet"] #[min = 1] bet: u64,
+    #[description = "Heads or tails"] #[choices("Heads", "Tails")] side: &'static str
+) -> Result<(), Error> {
+    if !can_user_gamble(ctx, bet).await? {
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    let economy_config = &ctx.data().config.get().economy;
+    let won: bool = flip_coin();
+    let landed_on: &str = if won { side } else if side == "Heads" { "Tails" } else { "Heads" };
+    let payout: u64 = if won { apply_house_edge(bet, bet*2, economy_config.gamble_house_edge) } else { 0 };
+
+    let result: Result<BetResult, async_sqlite::Error> = settle_bet_in_gambling_db(&ctx.data().db_client, ctx.author().id.get(), bet, payout, "coinflip", economy_config.gamble_daily_loss_cap).await;
+    let balance_after: Option<u64> = handle_bet_result(ctx, result).await?;
+    if balance_after.is_none() {
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    let (description, color): (String, Color) = if won {
+        (format!("🪙 The coin landed on **{}**!\n✅ You won **${}**", landed_on, payout), Color::from_rgb(100, 255, 100))
+    } else {
+        (format!("🪙 The coin landed on **{}**...\n❌ You lost **${}**", landed_on, bet), Color::from_rgb(255, 100, 100))
+    };
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::default()
+            .title("Coinflip")
+            .description(format!("{}\n-# Your balance: **${}**", description, balance_after.unwrap()))
+            .color(color)
+        )
+    ).await?;
+
+    Ok(())
+}
+
+/// Spin the slot machine
+#[poise::command(slash_command)]
+pub async fn slots(
+    ctx: Context<'_>,
+    #[description = "Amount of coins to bet"] #[min = 1] bet: u64
+) -> Result<(), Error> {
+    if !can_user_gamble(ctx, bet).await? {
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    let economy_config = &ctx.data().config.get().economy;
+    let (reels, raw_payout) = spin_slots(bet);
+    let payout: u64 = apply_house_edge(bet, raw_payout, economy_config.gamble_house_edge);
+
+    let result: Result<BetResult, async_sqlite::Error> = settle_bet_in_gambling_db(&ctx.data().db_client, ctx.author().id.get(), bet, payout, "slots", economy_config.gamble_daily_loss_cap).await;
+    let balance_after: Option<u64> = handle_bet_result(ctx, result).await?;
+    if balance_after.is_none() {
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    let (description, color): (String, Color) = if payout > bet {
+        (format!("✅ You won **${}**", payout), Color::from_rgb(100, 255, 100))
+    } else if payout > 0 {
+        (format!("😐 You got **${}** back", payout), Color::from_rgb(255, 255, 255))
+    } else {
+        (format!("❌ You lost **${}**", bet), Color::from_rgb(255, 100, 100))
+    };
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::default()
+            .title("🎰 Slots")
+            .description(format!("# {} {} {}\n{}\n-# Your balance: **${}**", reels[0], reels[1], reels[2], description, balance_after.unwrap()))
+            .color(color)
+        )
+    ).await?;
+
+    Ok(())
+}
+
+/// Play a round of blackjack against Loopchan
+#[poise::command(slash_command)]
+pub async fn blackjack(
+    ctx: Context<'_>,
+    #[description = "Amount of coins to bet"] #[min = 1] bet: u64
+) -> Result<(), Error> {
+    let custom_data = ctx.data();
+    let author_id: u64 = ctx.author().id.get();
+
+    if !can_user_gamble(ctx, bet).await? {
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    // Game is reserved before bet is placed, so running command twice at once can't start two games
+    let expired_game: Option<BlackjackGame> = {
+        let mut games = custom_data.blackjack_games.lock().await;
+        let existing_game: Option<&BlackjackGame> = games.get(&author_id);
+        if existing_game.is_some_and(|game| !game.bet_placed || game.started_at.elapsed() <= BLACKJACK_TIMEOUT) {
+            drop(games);
+            ctx.send(CreateReply::default()
+                .embed(CreateEmbed::default()
+                    .description("❌ Finish your current blackjack game first!")
+                    .color(Color::from_rgb(255, 100, 100))
+                )
+                .ephemeral(true)
+            ).await?;
+            ctx.set_invocation_data(true).await;
+            return Ok(());
+        }
+        games.insert(author_id, BlackjackGame::new(bet))
+    };
+    if let Some(mut expired_game) = expired_game {
+        let outcome: BlackjackOutcome = expired_game.stand();
+        finish_blackjack_game(&custom_data.config.get(), &custom_data.db_client, author_id, &expired_game, &outcome).await;
+    }
+
+    let result: Result<BetResult, async_sqlite::Error> = place_bet_in_gambling_db(&custom_data.db_client, author_id, bet, "blackjack", custom_data.config.get().economy.gamble_daily_loss_cap).await;
+    if handle_bet_result(ctx, result).await?.is_none() {
+        custom_data.blackjack_games.lock().await.remove(&author_id);
+        ctx.set_invocation_data(true).await;
+        return Ok(());
+    }
+
+    let (game, embed): (Option<BlackjackGame>, CreateEmbed) = {
+        let mut games = custom_data.blackjack_games.lock().await;
+        let game: &mut BlackjackGame = games.get_mut(&author_id).unwrap();
+        game.bet_placed = true;
+        game.started_at = Instant::now();
+        let embed: CreateEmbed = blackjack_embed(game, None);
+
+        // Naturals are decided right away
+        if BlackjackGame::is_natural(&game.player) || BlackjackGame::is_natural(&game.dealer) {
+            (games.remove(&author_id), embed)
+        } else {
+            (None, embed)
+        }
+    };
+
+    if let Some(mut game) = game {
+        let outcome: BlackjackOutcome = if BlackjackGame::is_natural(&game.player) { game.stand() } else { BlackjackOutcome::Lose };
+        let payout: Option<u64> = finish_blackjack_game(&custom_data.config.get(), &custom_data.db_client, author_id, &game, &outcome).await;
+
+        if payout.is_none() {
+            ctx.send(CreateReply::default()
+                .embed(
+                    blackjack_embed(&game, Some((&outcome, 0)))
+                        .description("❌ Failed to pay out your winnings. Please contact <@908779319084589067> to receive them.")
+                        .color(Color::from_rgb(255, 100, 100))
+                )
+            ).await?;
+            return Ok(());
+        }
+
+        ctx.send(CreateReply::default()
+            .embed(blackjack_embed(&game, Some((&outcome, payout.unwrap()))))
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+        .components(blackjack_components(author_id))
+    ).await?;
+
+    Ok(())
+}
+
+/// Opt out of (or back into) coinflip, slots and blackjack
+#[poise::command(slash_command)]
+pub async fn gambling_optout(
+    ctx: Context<'_>,
+    #[description = "Disable gambling for yourself"] opt_out: bool
+) -> Result<(), Error> {
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let author_id: u64 = ctx.author().id.get();
+
+    let block: Result<Option<(bool, Option<String>, u64)>, async_sqlite::Error> = get_user_gambling_block_in_gambling_db(db_client, author_id).await;
+    if block.is_err() {
+        error!("Failed to check {}'s gambling block: {}", author_id, block.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ Failed to check your gambling status. Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let block: Option<(bool, Option<String>, u64)> = block.unwrap();
+
+    if block.as_ref().is_some_and(|block| block.0) {
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ You were banned from gambling by staff, this can't be changed.")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if !opt_out && block.is_some() {
+        let opted_out_at: u64 = block.unwrap().2;
+        let can_opt_in_at: u64 = opted_out_at + GAMBLING_OPTOUT_MIN_DURATION;
+        if std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() < can_opt_in_at {
+            ctx.send(CreateReply::default()
+                .embed(CreateEmbed::default()
+                    .description(format!("❌ You can opt back in <t:{}:R>.", can_opt_in_at))
+                    .color(Color::from_rgb(255, 100, 100))
+                )
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    }
+
+    let changed: Result<usize, async_sqlite::Error> = if opt_out {
+        block_user_in_gambling_db(db_client, author_id, false, None).await
+    } else {
+        unblock_user_in_gambling_db(db_client, author_id, false).await
+    };
+
+    if changed.is_err() {
+        error!("Failed to change {}'s gambling opt-out: {}", author_id, changed.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ Failed to change your gambling status. Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::default()
+            .description(if opt_out { "✅ You opted out of gambling. You can opt back in after 24 hours." } else { "✅ You can gamble again. Please play responsibly!" })
+            .color(Color::from_rgb(100, 255, 100))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+const STATS_CHART_DAYS: u64 = 30;
+const STATS_ACTIVE_EARNER_DAYS: u64 = 7;
+
+/// Server-wide economy statistics
+#[poise::command(slash_command, custom_data = "PermissionLevel::Staff")]
+pub async fn stats(
+    ctx: Context<'_>
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let now: u64 = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    let today: u64 = now / 86400;
+    let first_day: u64 = today + 1 - STATS_CHART_DAYS;
+
+    let balances: Result<Vec<u64>, async_sqlite::Error> = get_all_balances_in_eco_db(db_client).await;
+    let totals: Result<Vec<DataLedgerDailyTotal>, async_sqlite::Error> = get_daily_totals_in_ledger_db(db_client, first_day * 86400).await;
+    let active_earners: Result<u64, async_sqlite::Error> = count_active_earners_in_ledger_db(db_client, now - STATS_ACTIVE_EARNER_DAYS * 86400).await;
+    let fishes: Result<Vec<(String, u64)>, async_sqlite::Error> = count_catches_by_type_in_fishing_db(db_client).await;
+
+    if balances.is_err() || totals.is_err() || active_earners.is_err() || fishes.is_err() {
+        let err: String = [
+            balances.err().map(|err| err.to_string()),
+            totals.err().map(|err| err.to_string()),
+            active_earners.err().map(|err| err.to_string()),
+            fishes.err().map(|err| err.to_string())
+        ].into_iter().flatten().collect::<Vec<String>>().join(" | ");
+        error!("Failed to collect economy statistics: {}", err);
+
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ Failed to collect economy statistics. Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+        ).await?;
+        return Ok(());
+    }
+
+    let balances: Vec<u64> = balances.unwrap();
+    let totals: Vec<DataLedgerDailyTotal> = totals.unwrap();
+    let total_coins: u64 = balances.iter().sum();
+
+    // Transfers only move coins between members, so only their fees count as burned
+    let mut days: Vec<(u64, u64, u64)> = (first_day..=today).map(|day| (day, 0, 0)).collect();
+    let mut reasons: Vec<(String, u64, u64)> = vec![];
+    for total in &totals {
+        let (minted, burned): (u64, u64) = if total.reason == LedgerReason::Transfer.as_str() {
+            (0, total.burned.saturating_sub(total.minted))
+        } else {
+            (total.minted, total.burned)
+        };
+
+        if total.day >= first_day && total.day <= today {
+            let day = &mut days[(total.day - first_day) as usize];
+            day.1 += minted;
+            day.2 += burned;
+        }
+
+        match reasons.iter_mut().find(|reason| reason.0 == total.reason) {
+            Some(reason) => { reason.1 += minted; reason.2 += burned; },
+            None => reasons.push((total.reason.clone(), minted, burned))
+        }
+    }
+
+    let minted_total: u64 = days.iter().map(|day| day.1).sum();
+    let burned_total: u64 = days.iter().map(|day| day.2).sum();
+    let reasons_text: String = if reasons.is_empty() {
+        "*Nothing happened yet*".to_string()
+    } else {
+        reasons.iter().map(|(reason, minted, burned)| format!("**{}**: +${} / -${}", reason, minted, burned)).collect::<Vec<String>>().join("\n")
+    };
+
+    let fishes: Vec<(String, u64)> = fishes.unwrap();
+    let fishes_text: String = if fishes.is_empty() {
+        "*No fish caught yet*".to_string()
+    } else {
+        fishes.iter().map(|(fish_type, amount)| format!("**{}**: {}", fish_type, amount)).collect::<Vec<String>>().join("\n")
+    };
+
+    let embed: CreateEmbed = CreateEmbed::default()
+        .title("📊 Economy Statistics")
+        .field("Coins in circulation", format!("${}", total_coins), true)
+        .field("Median balance", format!("${}", median(&balances)), true)
+        .field("Gini coefficient", format!("{:.3}", gini_coefficient(&balances)), true)
+        .field("Members with balance", format!("{}", balances.len()), true)
+        .field(format!("Active earners ({}d)", STATS_ACTIVE_EARNER_DAYS), format!("{}", active_earners.unwrap()), true)
+        .field(format!("Net change ({}d)", STATS_CHART_DAYS), format!("{}${}", if minted_total >= burned_total { "+" } else { "-" }, minted_total.abs_diff(burned_total)), true)
+        .field(format!("Minted / burned by source ({}d)", STATS_CHART_DAYS), reasons_text, false)
+        .field("Fish caught", fishes_text, false)
+        .color(Color::from_rgb(255, 255, 255));
+
+    let chart: Result<Vec<u8>, image::ImageError> = render_minted_burned_chart(&days);
+    if chart.is_err() {
+        error!("Failed to render economy chart: {}", chart.unwrap_err().to_string());
+        ctx.send(CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    ctx.send(CreateReply::default()
+        .attachment(CreateAttachment::bytes(chart.unwrap(), "economy.png"))
+        .embed(embed.image("attachment://economy.png"))
+    ).await?;
+
+    Ok(())
+}
+
+/// Your profile card, or profile card of other member
+#[poise::command(slash_command)]
+pub async fn profile(
+    ctx: Context<'_>,
+    #[description = "Member"] user: Option<serenity::model::user::User>
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let custom_data = ctx.data();
+    let db_client: &async_sqlite::Client = &custom_data.db_client;
+
+    let nuser: &serenity::model::user::User = if user.is_none() {
+        ctx.author()
+    } else {
+        &user.unwrap()
+    };
+    let nuser_id: u64 = nuser.id.get();
+
+    create_user_in_eco_db(db_client, nuser_id).await?;
+    let member_ids: Option<String> = get_guild_member_ids(ctx.serenity_context(), custom_data);
+
+    let balance: Result<u64, async_sqlite::Error> = get_user_balance_in_eco_db(db_client, nuser_id).await;
+    let level_and_exp: Result<(u64, u64), String> = match get_user_level_and_experience_in_eco_db(db_client, nuser_id).await {
+        Ok((Ok(level), Ok(experience))) => Ok((level, experience)),
+        Ok((level, experience)) => Err([level.err(), experience.err()].into_iter().flatten().map(|err| err.to_string()).collect::<Vec<String>>().join(" | ")),
+        Err(err) => Err(err.to_string())
+    };
+    let level_placement: Result<Option<u64>, async_sqlite::Error> = get_user_placement_in_level_leaderboard(db_client, nuser_id, member_ids.clone()).await;
+    let balance_placement: Result<Option<u64>, async_sqlite::Error> = get_user_placement_in_balance_leaderboard(db_client, nuser_id, member_ids).await;
+    let fishes: Result<Vec<DataFish>, async_sqlite::Error> = get_user_fishes_in_fishing_db(db_client, nuser_id).await;
+    let purchases: Result<Vec<DataRolePurchase>, async_sqlite::Error> = get_user_role_purchases_in_roleshop_db(db_client, nuser_id).await;
+    let shop_items: Result<Vec<RoleShopItem>, async_sqlite::Error> = get_shop_items_in_roleshop_db(db_client).await;
+    let equipped: Result<Vec<DataEquippedItem>, async_sqlite::Error> = get_user_equipped_items_in_inventory_db(db_client, nuser_id).await;
+
+    if balance.is_err() || level_and_exp.is_err() || level_placement.is_err() || balance_placement.is_err() || fishes.is_err() || purchases.is_err() || shop_items.is_err() || equipped.is_err() {
+        let err: String = [
+            balance.err().map(|err| err.to_string()),
+            level_and_exp.err(),
+            level_placement.err().map(|err| err.to_string()),
+            balance_placement.err().map(|err| err.to_string()),
+            fishes.err().map(|err| err.to_string()),
+            purchases.err().map(|err| err.to_string()),
+            shop_items.err().map(|err| err.to_string()),
+            equipped.err().map(|err| err.to_string())
+        ].into_iter().flatten().collect::<Vec<String>>().join(" | ");
+        error!("Failed to collect {}'s profile: {}", nuser_id, err);
+
+        ctx.send(CreateReply::default()
+            .embed(CreateEmbed::default()
+                .description("❌ Failed to load profile. Please try again later, if the issue persists contact <@908779319084589067>")
+                .color(Color::from_rgb(255, 100, 100))
+            )
+        ).await?;
+        return Ok(());
+    }
+
+    let (level, experience): (u64, u64) = level_and_exp.unwrap();
+
+    // Most valuable fish, fishes with broken modifiers are skipped
+    let mut best_fish: Option<(String, String, u64)> = None;
+    for fish in fishes.unwrap() {
+        let size_and_value: Result<(f32, u64), std::io::Error> = fish_size_and_value(&fish, &custom_data.config.get().economy.fishes, &custom_data.config.get().economy.fishes_modifiers);
+        if size_and_value.is_err() {
+            continue;
+        }
+        let (size, value): (f32, u64) = size_and_value.unwrap();
+        if best_fish.as_ref().is_none_or(|best| value > best.2) {
+            best_fish = Some((fish.r#type.clone(), size.to_string(), value));
+        }
+    }
+
+    let shop_items: Vec<RoleShopItem> = shop_items.unwrap();
+    let roles: Vec<String> = purchases.unwrap().iter()
+        .filter_map(|purchase| shop_items.iter().find(|item| item.id == purchase.role_id))
+        .map(|item| item.display_name.clone())
+        .collect();
+
+    let equipped: Vec<DataEquippedItem> = equipped.unwrap();
+    let equipped_effect = |slot: &str| -> Option<ItemEffect> {
+        let equipped_item: &DataEquippedItem = equipped.iter().find(|equipped_item| equipped_item.slot == slot)?;
+        item_from_id(&equipped_item.item_id, &custom_data.config.get().economy.items).ok()?.effect
+    };
+    let background: Option<String> = match equipped_effect(BACKGROUND_SLOT) {
+        Some(ItemEffect::Background { path }) => Some(path),
+        _ => None
+    };
+    let badge: Option<String> = match equipped_effect(BADGE_SLOT) {
+        Some(ItemEffect::Badge { emoji }) => Some(emoji),
+        _ => None
+    };
+
+    let roblox_id: Option<u64> = get_roblox_id_in_users_db_by_discord_id(db_client, nuser_id).await.ok().filter(|roblox_id| *roblox_id != 0);
+
+    let card: ProfileCard = ProfileCard {
+        username: nuser.name.clone(),
+        display_name: nuser.display_name().to_string(),
+        avatar_url: nuser.static_face(),
+        level,
+        experience,
+        experience_needed: exp_needed_to_next_level(level),
+        balance: balance.unwrap(),
+        level_placement: level_placement.unwrap(),
+        balance_placement: balance_placement.unwrap(),
+        roblox_id,
+        best_fish,
+        roles,
+        background
+    };
+    let fingerprint: u64 = card.fingerprint();
+
+    let cached: Option<Vec<u8>> = custom_data.profile_cards.lock().await.get(&nuser_id)
+        .filter(|(cached_fingerprint, _)| *cached_fingerprint == fingerprint)
+        .map(|(_, bytes)| bytes.clone());
+
+    let bytes: Vec<u8> = if cached.is_some() {
+        cached.unwrap()
+    } else {
+        let avatar: Option<Vec<u8>> = download_image(&card.avatar_url).await;
+        let roblox_username: Option<String> = match roblox_id {
+            Some(roblox_id) => custom_data.roblox_client.user_details(roblox_id).await.ok().map(|details| details.username),
+            None => None
+        };
+
+        // Fallback renders (no avatar or Roblox username) aren't cached, so next /profile tries fetching them again
+        let complete: bool = avatar.is_some() && (roblox_id.is_none() || roblox_username.is_some());
+
+        // Rendering is slow, so it's kept off async runtime
+        let rendered: Result<Vec<u8>, Error> = match tokio::task::spawn_blocking(move || {
+            render_profile_card(&card, avatar.as_deref(), roblox_username.as_deref())
+        }).await {
+            Ok(rendered) => rendered,
+            Err(err) => Err(err.into())
+        };
+        if rendered.is_err() {
+            error!("Failed to render {}'s profile card: {}", nuser_id, rendered.unwrap_err().to_string());
+            ctx.send(CreateReply::default()
+                .embed(CreateEmbed::default()
+                    .description("❌ Failed to render profile. Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+                )
+            ).await?;
+            return Ok(());
+        }
+
+        let rendered: Vec<u8> = rendered.unwrap();
+        if complete {
+            custom_data.profile_cards.lock().await.insert(nuser_id, (fingerprint, rendered.clone()));
+        }
+        rendered
+    };
+
+    let title: String = match badge {
+        Some(badge) => format!("{} {}'s Profile", badge, nuser.name),
+        None => format!("{}'s Profile", nuser.name)
+    };
+
+    ctx.send(CreateReply::default()
+        .attachment(CreateAttachment::bytes(bytes, "profile.png"))
+        .embed(CreateEmbed::default()
+            .title(title)
+            .image("attachment://profile.png")
+            .color(Color::from_rgb(255, 255, 255))
+        )
+    ).await?;
+
+    Ok(())
+}
@@ -4,15 +4,18 @@
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use futures::{Stream, StreamExt};
-use poise::{CooldownConfig, CreateReply};
+use poise::CreateReply;
 use rand::{distr::{weighted::WeightedIndex, Distribution}, rng, Rng};
 use serenity::{all::{ButtonStyle, Color, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage}, json};
 use tracing::{error, warn};
 use uuid::Uuid;
 
-use crate::{utils::{basic::{fish_from_name, fishmodifier_from_name, fishmodifiers_from_datafishmodifiers, get_fishes_names_from_fishes, remove_whitespace}, database::{economy::get_user_level_in_eco_db, fishing::{get_user_fishes_in_fishing_db, give_fish_to_user_in_fishing_db}}}, Context, DataFish, Error, FishModifier};
+use crate::{utils::{basic::{fish_from_name, fishmodifier_from_name, fishmodifiers_from_datafishmodifiers, get_fishes_names_from_fishes, item_from_id, remove_whitespace}, database::{cooldowns::{reset_user_cooldowns_in_cooldowns_db, try_start_user_cooldown_in_cooldowns_db}, economy::get_user_level_in_eco_db, fishing::{catch_fish_in_fishing_db, get_user_fishes_in_fishing_db, give_fish_to_user_in_fishing_db}, inventory::use_equipped_item_in_inventory_db}}, Context, DataFish, Error, FishModifier, ItemEffect};
+use crate::utils::permissions::PermissionLevel;
 
-use super::eco::{exp_needed_to_next_level, give_user_eco_exp};
+use super::{eco::{exp_needed_to_next_level, give_user_eco_exp}, inventory::BAIT_SLOT};
+
+pub const FISH_COOLDOWN_ACTION: &str = "fishing.fish";
 
 /// Fishing Commands
 #[poise::command(slash_command, subcommands("give_fish", "inventory", "fish", "throwaway"), subcommand_required)]
@@ -99,9 +102,9 @@ pub async fn get_inventory_embeds_after_interaction(
             continue;
         }
 
-        let actual_fish = fish_from_name(&fish.r#type, &data.config.economy.fishes).unwrap();
+        let actual_fish = fish_from_name(&fish.r#type, &data.config.get().economy.fishes).unwrap();
 
-        let modifiers: Result<Vec<crate::FishModifier>, std::io::Error> = fishmodifiers_from_datafishmodifiers(&fish.modifiers, data.config.economy.fishes_modifiers.clone());
+        let modifiers: Result<Vec<crate::FishModifier>, std::io::Error> = fishmodifiers_from_datafishmodifiers(&fish.modifiers, data.config.get().economy.fishes_modifiers.clone());
         if modifiers.is_err() {
             error!("Failed to decode modifiers of fish {}: {}", fish.uuid, modifiers.unwrap_err().to_string());
 
@@ -185,9 +188,9 @@ pub async fn get_inventory_embeds(
             continue;
         }
 
-        let actual_fish = fish_from_name(&fish.r#type, &custom_data.config.economy.fishes).unwrap();
+        let actual_fish = fish_from_name(&fish.r#type, &custom_data.config.get().economy.fishes).unwrap();
 
-        let modifiers: Result<Vec<crate::FishModifier>, std::io::Error> = fishmodifiers_from_datafishmodifiers(&fish.modifiers, custom_data.config.economy.fishes_modifiers.clone());
+        let modifiers: Result<Vec<crate::FishModifier>, std::io::Error> = fishmodifiers_from_datafishmodifiers(&fish.modifiers, custom_data.config.get().economy.fishes_modifiers.clone());
         if modifiers.is_err() {
             error!("Failed to decode modifiers of fish {}: {}", fish.uuid, modifiers.unwrap_err().to_string());
 
@@ -313,14 +316,14 @@ async fn fish_type_autocomplete_handler<'a>(
     ctx: Context<'_>,
     partial: &'a str,
 ) -> impl Stream<Item = String> + 'a {
-    let fish_names: Vec<String> = get_fishes_names_from_fishes(&ctx.data().config.economy.fishes); // TODO: Cache this in data to make autocomplete faster
+    let fish_names: Vec<String> = get_fishes_names_from_fishes(&ctx.data().config.get().economy.fishes); // TODO: Cache this in data to make autocomplete faster
 
     futures::stream::iter(fish_names)
         .filter(move |name| futures::future::ready(name.starts_with(partial)))
         .map(|name| name.to_string())
 }
 
-#[poise::command(slash_command)]
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner")]
 pub async fn give_fish(
     ctx: Context<'_>,
     #[description = "Select type only from autocomplete (otherwise datalose (haha. get it? datalose!))"]
@@ -330,7 +333,7 @@ pub async fn give_fish(
     #[description = "Fish Modifiers (separated by comma! (\",\"))"]
     modifiers: Option<String>,
 ) -> Result<(), Error> {
-    let actual_fish: Result<crate::Fish, std::io::Error> = fish_from_name(&r#type, &ctx.data().config.economy.fishes);
+    let actual_fish: Result<crate::Fish, std::io::Error> = fish_from_name(&r#type, &ctx.data().config.get().economy.fishes);
     if actual_fish.is_err() {
         error!("failed to give fish: {}", actual_fish.unwrap_err().to_string());
         ctx.send(CreateReply::default()
@@ -394,11 +397,23 @@ pub async fn _fish(
     ctx: Context<'_>
 ) -> Result<(), Error> {
     let custom_data = &ctx.data();
-    let loopchans_config = &custom_data.config;
+    let loopchans_config = custom_data.config.get();
 
     let author_id: u64 = ctx.author().id.get();
 
-    if rand::rng().random_bool(loopchans_config.economy.fish_fail_chance.into()) {
+    let mut fail_chance: f32 = loopchans_config.economy.fish_fail_chance;
+    let used_bait: Result<Option<String>, async_sqlite::Error> = use_equipped_item_in_inventory_db(&custom_data.db_client, author_id, BAIT_SLOT.to_string()).await;
+    if used_bait.is_err() {
+        // Fishing without bait is better than not fishing at all
+        warn!("Failed to use {}'s fishing bait: {}", author_id, used_bait.unwrap_err().to_string());
+    } else if used_bait.as_ref().unwrap().is_some() {
+        let bait: Result<crate::Item, std::io::Error> = item_from_id(used_bait.as_ref().unwrap().as_ref().unwrap(), &loopchans_config.economy.items);
+        if let Ok(crate::Item { effect: Some(ItemEffect::FishingBait { fail_chance_multiplier, .. }), .. }) = bait {
+            fail_chance = (fail_chance*fail_chance_multiplier).clamp(0.0, 1.0);
+        }
+    }
+
+    if rand::rng().random_bool(fail_chance.into()) {
         let users_lvl = get_user_level_in_eco_db(&custom_data.db_client, author_id).await;
         if users_lvl.is_err() {
             error!("Failed to check {}'s level: {}", author_id, users_lvl.unwrap_err().to_string());
@@ -412,6 +427,7 @@ pub async fn _fish(
             ).await?;
     
             ctx.set_invocation_data(true).await; // cancel cooldown (hopefully)
+            reset_user_cooldowns_in_cooldowns_db(&custom_data.db_client, author_id, Some(FISH_COOLDOWN_ACTION.to_string())).await?;
     
             return Ok(());
         }
@@ -431,6 +447,7 @@ pub async fn _fish(
             ).await?;
 
             ctx.set_invocation_data(true).await; // cancel cooldown (hopefully)
+            reset_user_cooldowns_in_cooldowns_db(&custom_data.db_client, author_id, Some(FISH_COOLDOWN_ACTION.to_string())).await?;
 
             return Ok(());
         }
@@ -519,7 +536,7 @@ pub async fn _fish(
 
     let uuid = Uuid::new_v4().to_string();
 
-    let successfully_gave_fish: Result<usize, async_sqlite::Error> = give_fish_to_user_in_fishing_db(&custom_data.db_client, author_id, DataFish {
+    let successfully_gave_fish: Result<usize, async_sqlite::Error> = catch_fish_in_fishing_db(&custom_data.db_client, author_id, DataFish {
         uuid: uuid.clone(),
         modifiers: catched_modifiers_serialized,
         r#type: catched_fish.name.clone(),
@@ -538,6 +555,7 @@ pub async fn _fish(
         ).await?;
 
         ctx.set_invocation_data(true).await; // cancel cooldown (hopefully)
+        reset_user_cooldowns_in_cooldowns_db(&custom_data.db_client, author_id, Some(FISH_COOLDOWN_ACTION.to_string())).await?;
 
         return Ok(());
     }
@@ -614,7 +632,7 @@ pub async fn _fishminigame(
     ctx: Context<'_>
 ) -> Result<(), Error> {
     let custom_data = &ctx.data();
-    let loopchans_config = &custom_data.config;
+    let loopchans_config = custom_data.config.get();
 
     let mut components = empty_fishing_minigame_matrix();
 
@@ -759,7 +777,7 @@ pub async fn _fishminigame(
 
     let author_id = ctx.author().id.get();
 
-    let successfully_gave_fish: Result<usize, async_sqlite::Error> = give_fish_to_user_in_fishing_db(&custom_data.db_client, author_id, DataFish {
+    let successfully_gave_fish: Result<usize, async_sqlite::Error> = catch_fish_in_fishing_db(&custom_data.db_client, author_id, DataFish {
         uuid: uuid.clone(),
         modifiers: catched_modifiers_serialized,
         r#type: catched_fish.name.clone(),
@@ -778,6 +796,7 @@ pub async fn _fishminigame(
         ).await?;
 
         ctx.set_invocation_data(true).await; // cancel cooldown (hopefully)
+        reset_user_cooldowns_in_cooldowns_db(&custom_data.db_client, author_id, Some(FISH_COOLDOWN_ACTION.to_string())).await?;
 
         return Ok(());
     }
@@ -820,27 +839,42 @@ pub async fn fish(
     minigame: Option<bool>
 ) -> Result<(), Error> {
     let custom_data: &crate::Data = &ctx.data();
-    let economy_config: &crate::EconomyConfig = &custom_data.config.economy;
+    let economy_config: &crate::EconomyConfig = &custom_data.config.get().economy;
+    let db_client: &async_sqlite::Client = &custom_data.db_client;
+    let author_id: u64 = ctx.author().id.get();
     let on_cooldown: i32;
 
     {
-        let mut cooldown_tracker = ctx.command().cooldowns.lock().unwrap();
-
-        let mut cooldown_durations: CooldownConfig = CooldownConfig::default();
-        cooldown_durations.user = Some(Duration::from_secs(economy_config.fish_cooldown*60));
+        let mut cooldown_duration: Duration = Duration::from_secs(economy_config.fish_cooldown*60);
 
         if minigame.is_some() {
             if minigame.unwrap() {
-                cooldown_durations.user = Some(Duration::from_secs(economy_config.fish_cooldown_mg*60));
+                cooldown_duration = Duration::from_secs(economy_config.fish_cooldown_mg*60);
             }
         }
 
-        match cooldown_tracker.remaining_cooldown(ctx.cooldown_context(), &cooldown_durations) {
+        let remaining_cooldown: Result<Option<Duration>, async_sqlite::Error> = try_start_user_cooldown_in_cooldowns_db(db_client, author_id, FISH_COOLDOWN_ACTION.to_string(), cooldown_duration).await;
+        if remaining_cooldown.is_err() {
+            error!("Failed to check {}'s fishing cooldown: {}", author_id, remaining_cooldown.unwrap_err().to_string());
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description("Failed to check your cooldown! Please try again later, if the issue persists contact <@908779319084589067>")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .ephemeral(true)
+            ).await?;
+
+            return Ok(());
+        }
+
+        match remaining_cooldown.unwrap() {
             Some(remaining) => {
                 on_cooldown = remaining.as_secs() as i32;
             }
             None => {
-                cooldown_tracker.start_cooldown(ctx.cooldown_context());
                 on_cooldown = -1;
             },
         }
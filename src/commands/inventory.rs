@@ -0,0 +1,562 @@
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use poise::CreateReply;
+use serenity::all::Color;
+use serenity::all::CreateEmbed;
+use tracing::error;
+
+use crate::{utils::{basic::item_from_id, database::{cooldowns::{get_user_remaining_cooldown_in_cooldowns_db, reset_user_cooldowns_in_cooldowns_db}, economy::create_user_in_eco_db, inventory::{buy_item_in_inventory_db, equip_item_in_inventory_db, get_user_equipped_items_in_inventory_db, get_user_items_in_inventory_db, give_item_to_user_in_inventory_db, take_item_from_user_in_inventory_db, unequip_item_in_inventory_db}}}, Context, DataEquippedItem, DataInventoryItem, Error, Item, ItemEffect, ItemKind};
+
+use crate::utils::permissions::PermissionLevel;
+
+use super::{eco::{give_user_eco_exp, WORK_COOLDOWN_ACTION}, fishing::FISH_COOLDOWN_ACTION};
+
+pub const BAIT_SLOT: &str = "bait";
+pub const BACKGROUND_SLOT: &str = "background";
+pub const BADGE_SLOT: &str = "badge";
+
+/// Slot that item goes into once used, items without slot are applied instantly
+pub fn item_slot(effect: &ItemEffect) -> Option<&'static str> {
+    match effect {
+        ItemEffect::FishingBait { .. } => Some(BAIT_SLOT),
+        ItemEffect::Background { .. } => Some(BACKGROUND_SLOT),
+        ItemEffect::Badge { .. } => Some(BADGE_SLOT),
+        _ => None
+    }
+}
+
+fn format_item_kind(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Consumable => "Consumable",
+        ItemKind::Cosmetic => "Cosmetic",
+        ItemKind::Collectible => "Collectible"
+    }
+}
+
+async fn item_autocomplete_handler<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let item_ids: Vec<String> = ctx.data().config.get().economy.items.iter().map(|item| item.id.clone()).collect();
+
+    futures::stream::iter(item_ids)
+        .filter(move |id| futures::future::ready(id.starts_with(partial)))
+        .map(|id| id.to_string())
+}
+
+async fn buyable_item_autocomplete_handler<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let item_ids: Vec<String> = ctx.data().config.get().economy.items.iter().filter(|item| item.price.is_some()).map(|item| item.id.clone()).collect();
+
+    futures::stream::iter(item_ids)
+        .filter(move |id| futures::future::ready(id.starts_with(partial)))
+        .map(|id| id.to_string())
+}
+
+async fn send_unknown_item(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.send(CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .description("❌ Unknown item! Please select one from the list.")
+                .color(Color::from_rgb(255, 100, 100))
+        )
+        .ephemeral(true)
+    ).await?;
+    Ok(())
+}
+
+/// Inventory Commands
+#[poise::command(slash_command, subcommands("view", "use_item", "unequip", "shop", "buy", "give"), subcommand_required)]
+pub async fn inventory(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// See your items
+#[poise::command(slash_command)]
+pub async fn view(
+    ctx: Context<'_>,
+    #[description = "Member"] user: Option<serenity::model::user::User>
+) -> Result<(), Error> {
+    let user: &serenity::model::user::User = user.as_ref().unwrap_or(ctx.author());
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let config_items: &Vec<Item> = &ctx.data().config.get().economy.items;
+
+    let items: Result<Vec<DataInventoryItem>, async_sqlite::Error> = get_user_items_in_inventory_db(db_client, user.id.get()).await;
+    let equipped: Result<Vec<DataEquippedItem>, async_sqlite::Error> = get_user_equipped_items_in_inventory_db(db_client, user.id.get()).await;
+
+    if items.is_err() || equipped.is_err() {
+        if items.is_err() {
+            error!("Failed to get {}'s items: {}", user.id.get(), items.unwrap_err().to_string());
+        } else {
+            error!("Failed to get {}'s equipped items: {}", user.id.get(), equipped.unwrap_err().to_string());
+        }
+
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("Failed to find items! Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+
+        return Ok(());
+    }
+    let items: Vec<DataInventoryItem> = items.unwrap();
+    let equipped: Vec<DataEquippedItem> = equipped.unwrap();
+
+    if items.is_empty() && equipped.is_empty() {
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .title(format!("🎒 {}'s Inventory", user.name))
+                    .description("Inventory is empty 😥\nYou can buy items with ```/inventory shop```")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+        ).await?;
+
+        return Ok(());
+    }
+
+    let mut response: String = String::new();
+    for data_item in &items {
+        let item: Result<Item, std::io::Error> = item_from_id(&data_item.item_id, config_items);
+        if item.is_err() {
+            // Item was removed from config, but someone still has it
+            response.push_str(&format!("❔ **{}** x{}\n", data_item.item_id, data_item.quantity));
+            continue;
+        }
+        let item: Item = item.unwrap();
+        response.push_str(&format!("{} **{}** x{} • *{}*\n", item.emoji, item.display_name, data_item.quantity, format_item_kind(item.kind)));
+    }
+
+    if !equipped.is_empty() {
+        response.push_str("**=====**\n**Equipped:**\n");
+        for equipped_item in &equipped {
+            let item_name: String = match item_from_id(&equipped_item.item_id, config_items) {
+                Ok(item) => format!("{} {}", item.emoji, item.display_name),
+                Err(_) => equipped_item.item_id.clone()
+            };
+            let uses_text: String = match equipped_item.remaining_uses {
+                Some(remaining_uses) => format!(" ({} uses left)", remaining_uses),
+                None => String::new()
+            };
+            response.push_str(&format!("*{}:* **{}**{}\n", equipped_item.slot, item_name, uses_text));
+        }
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title(format!("🎒 {}'s Inventory", user.name))
+                .description(response)
+                .color(Color::from_rgb(255, 255, 255))
+        )
+    ).await?;
+
+    Ok(())
+}
+
+/// Use or equip an item from your inventory
+#[poise::command(slash_command, rename = "use")]
+pub async fn use_item(
+    ctx: Context<'_>,
+    #[description = "Item"]
+    #[autocomplete = "item_autocomplete_handler"]
+    item: String
+) -> Result<(), Error> {
+    let custom_data = ctx.data();
+    let db_client: &async_sqlite::Client = &custom_data.db_client;
+    let author_id: u64 = ctx.author().id.get();
+
+    let item: Result<Item, std::io::Error> = item_from_id(&item, &custom_data.config.get().economy.items);
+    if item.is_err() {
+        return send_unknown_item(ctx).await;
+    }
+    let item: Item = item.unwrap();
+
+    if item.kind == ItemKind::Collectible || item.effect.is_none() {
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description(format!("❌ **{}** can't be used, it's just nice to have!", item.display_name))
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let effect: &ItemEffect = item.effect.as_ref().unwrap();
+
+    // Items with slot are equipped (and consumed if they are consumables)
+    let slot: Option<&'static str> = item_slot(effect);
+    if slot.is_some() {
+        let consume: bool = item.kind == ItemKind::Consumable;
+
+        if !consume {
+            // Cosmetics stay in inventory, so just check that user has one
+            let items: Result<Vec<DataInventoryItem>, async_sqlite::Error> = get_user_items_in_inventory_db(db_client, author_id).await;
+            if items.is_err() {
+                error!("Failed to get {}'s items: {}", author_id, items.unwrap_err().to_string());
+                ctx.send(CreateReply::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description("❌ Failed to find your items! Please try again later, if the issue persists contact <@908779319084589067>")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .ephemeral(true)
+                ).await?;
+                return Ok(());
+            }
+
+            if !items.unwrap().iter().any(|data_item| data_item.item_id == item.id) {
+                ctx.send(CreateReply::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description(format!("❌ You don't have **{}**!", item.display_name))
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .ephemeral(true)
+                ).await?;
+                return Ok(());
+            }
+        }
+
+        let remaining_uses: Option<u64> = match effect {
+            ItemEffect::FishingBait { uses, .. } => Some(*uses),
+            _ => None
+        };
+
+        let equipped: Result<bool, async_sqlite::Error> = equip_item_in_inventory_db(db_client, author_id, slot.unwrap().to_string(), item.id.clone(), remaining_uses, consume).await;
+        if equipped.is_err() {
+            error!("Failed to equip {} for {}: {}", item.id, author_id, equipped.unwrap_err().to_string());
+            ctx.send(CreateReply::default()
+                .embed(
+                    CreateEmbed::default()
+                        .description("❌ Failed to use item! Please try again later, if the issue persists contact <@908779319084589067>")
+                        .color(Color::from_rgb(255, 100, 100))
+                )
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+
+        if !equipped.unwrap() {
+            ctx.send(CreateReply::default()
+                .embed(
+                    CreateEmbed::default()
+                        .description(format!("❌ You don't have **{}**!", item.display_name))
+                        .color(Color::from_rgb(255, 100, 100))
+                )
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+
+        let description: String = match effect {
+            ItemEffect::FishingBait { uses, .. } => format!("🎣 **{}** is on your hook for the next **{}** fishing trip(s)!", item.display_name, uses),
+            _ => format!("✅ Equipped **{}** as your {}!", item.display_name, slot.unwrap())
+        };
+
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description(description)
+                    .color(Color::from_rgb(100, 255, 100))
+            )
+        ).await?;
+        return Ok(());
+    }
+
+    if let ItemEffect::CooldownSkip { action } = effect {
+        // Don't waste user's item if there's nothing to skip
+        let cooldown_duration: Option<Duration> = match action.as_str() {
+            WORK_COOLDOWN_ACTION => Some(Duration::from_secs(custom_data.config.get().economy.work_cooldown*60)),
+            FISH_COOLDOWN_ACTION => Some(Duration::from_secs(custom_data.config.get().economy.fish_cooldown_mg*60)),
+            _ => None
+        };
+
+        if cooldown_duration.is_some() {
+            let remaining_cooldown: Result<Option<Duration>, async_sqlite::Error> = get_user_remaining_cooldown_in_cooldowns_db(db_client, author_id, action.clone(), cooldown_duration.unwrap()).await;
+            if remaining_cooldown.is_ok() && remaining_cooldown.unwrap().is_none() {
+                ctx.send(CreateReply::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description("❌ You have nothing to skip right now!")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .ephemeral(true)
+                ).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let taken: Result<bool, async_sqlite::Error> = take_item_from_user_in_inventory_db(db_client, author_id, item.id.clone(), 1).await;
+    if taken.is_err() {
+        error!("Failed to take {} from {}: {}", item.id, author_id, taken.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("❌ Failed to use item! Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if !taken.unwrap() {
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description(format!("❌ You don't have **{}**!", item.display_name))
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let (successfully_applied, description): (bool, String) = match effect {
+        ItemEffect::Exp { amount } => (
+            give_user_eco_exp(custom_data, ctx.author(), *amount).await,
+            format!("✨ **+{} EXP**", amount)
+        ),
+        ItemEffect::CooldownSkip { action } => {
+            let reset: Result<usize, async_sqlite::Error> = reset_user_cooldowns_in_cooldowns_db(db_client, author_id, Some(action.clone())).await;
+            if reset.is_err() {
+                error!("Failed to reset {}'s {} cooldown: {}", author_id, action, reset.as_ref().unwrap_err().to_string());
+            }
+            (reset.is_ok(), format!("⏩ Your `{}` cooldown was skipped!", action))
+        },
+        _ => (false, String::new())
+    };
+
+    if !successfully_applied {
+        // Give item back so user can try again
+        let refund: Result<usize, async_sqlite::Error> = give_item_to_user_in_inventory_db(db_client, author_id, item.id.clone(), 1).await;
+        if refund.is_err() {
+            error!("Failed to give {} back to {} after failed use: {} | Please give it back manually.", item.id, author_id, refund.unwrap_err().to_string());
+        }
+
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("❌ Failed to use item! Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title(format!("{} {}", item.emoji, item.display_name))
+                .description(description)
+                .color(Color::from_rgb(100, 255, 100))
+        )
+    ).await?;
+
+    Ok(())
+}
+
+/// Take off an equipped item
+#[poise::command(slash_command)]
+pub async fn unequip(
+    ctx: Context<'_>,
+    #[description = "Slot"]
+    #[choices("background", "badge")]
+    slot: &'static str
+) -> Result<(), Error> {
+    let author_id: u64 = ctx.author().id.get();
+    let unequipped: Result<usize, async_sqlite::Error> = unequip_item_in_inventory_db(&ctx.data().db_client, author_id, slot.to_string()).await;
+
+    if unequipped.is_err() {
+        error!("Failed to unequip {}'s {}: {}", author_id, slot, unequipped.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("❌ Failed to unequip item! Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if unequipped.unwrap() == 0 {
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description(format!("❌ You don't have anything equipped as your {}!", slot))
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .description(format!("✅ Unequipped your {}!", slot))
+                .color(Color::from_rgb(100, 255, 100))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// See items you can buy
+#[poise::command(slash_command)]
+pub async fn shop(
+    ctx: Context<'_>
+) -> Result<(), Error> {
+    let mut response: String = String::new();
+    for item in &ctx.data().config.get().economy.items {
+        if item.price.is_none() {
+            continue;
+        }
+
+        response.push_str(&format!("{} **{}** • *${}*\n*{}* • `{}`\n\n",
+            item.emoji,
+            item.display_name,
+            item.price.unwrap(),
+            item.description,
+            item.id
+        ));
+    }
+
+    if response.is_empty() {
+        response = "Nothing to buy right now, come back later!".to_string();
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title("Item Shop")
+                .description(format!("{}-# Buy items with `/inventory buy`", response))
+                .color(Color::from_rgb(255, 255, 255))
+        )
+    ).await?;
+
+    Ok(())
+}
+
+/// Buy an item from shop
+#[poise::command(slash_command)]
+pub async fn buy(
+    ctx: Context<'_>,
+    #[description = "Item"]
+    #[autocomplete = "buyable_item_autocomplete_handler"]
+    item: String,
+    #[description = "How many items to buy"]
+    #[min = 1]
+    #[max = 100]
+    quantity: Option<u64>
+) -> Result<(), Error> {
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let author_id: u64 = ctx.author().id.get();
+    let quantity: u64 = quantity.unwrap_or(1);
+
+    let item: Result<Item, std::io::Error> = item_from_id(&item, &ctx.data().config.get().economy.items);
+    if item.is_err() || item.as_ref().unwrap().price.is_none() {
+        return send_unknown_item(ctx).await;
+    }
+    let item: Item = item.unwrap();
+    let total_price: u64 = item.price.unwrap()*quantity;
+
+    let successfully_created: Result<usize, async_sqlite::Error> = create_user_in_eco_db(db_client, author_id).await;
+    if successfully_created.is_err() {
+        error!("Failed to create user ({}) in eco db: {}", author_id, successfully_created.unwrap_err().to_string());
+    }
+
+    let balance_after: Result<Option<u64>, async_sqlite::Error> = buy_item_in_inventory_db(db_client, author_id, item.id.clone(), total_price, quantity).await;
+    if balance_after.is_err() {
+        error!("Failed to buy {}x{} for {}: {}", item.id, quantity, author_id, balance_after.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("❌ Failed to buy item! Please try again later, if the issue persists contact <@908779319084589067>")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let balance_after: Option<u64> = balance_after.unwrap();
+    if balance_after.is_none() {
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("❌ Insufficient Funds!")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .description(format!("✅ Bought **{}x {} {}** for **${}**!\n-# Your balance: **${}**", quantity, item.emoji, item.display_name, total_price, balance_after.unwrap()))
+                .color(Color::from_rgb(100, 255, 100))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Give item to member (recorded in audit log)
+#[poise::command(slash_command, custom_data = "PermissionLevel::Owner")]
+pub async fn give(
+    ctx: Context<'_>,
+    #[description = "Member"] user: serenity::model::user::User,
+    #[description = "Item"]
+    #[autocomplete = "item_autocomplete_handler"]
+    item: String,
+    #[description = "How many items to give"]
+    #[min = 1]
+    quantity: Option<u64>
+) -> Result<(), Error> {
+    let quantity: u64 = quantity.unwrap_or(1);
+
+    let item: Result<Item, std::io::Error> = item_from_id(&item, &ctx.data().config.get().economy.items);
+    if item.is_err() {
+        return send_unknown_item(ctx).await;
+    }
+    let item: Item = item.unwrap();
+
+    let successfully_gave: Result<usize, async_sqlite::Error> = give_item_to_user_in_inventory_db(&ctx.data().db_client, user.id.get(), item.id.clone(), quantity).await;
+    if successfully_gave.is_err() {
+        error!("Failed to give {}x{} to {}: {}", item.id, quantity, user.id.get(), successfully_gave.unwrap_err().to_string());
+        ctx.send(CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("❌ Failed to give item!")
+                    .color(Color::from_rgb(255, 100, 100))
+            )
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.send(CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .description(format!("✅ Gave **{}x {} {}** to <@{}>", quantity, item.emoji, item.display_name, user.id.get()))
+                .color(Color::from_rgb(100, 255, 100))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
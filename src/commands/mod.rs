@@ -4,4 +4,7 @@ pub mod qa;
 pub mod eco;
 pub mod lastfm;
 pub mod fishing;
-pub mod adm;
\ No newline at end of file
+pub mod adm;
+pub mod inventory;
+pub mod blacklist;
+pub mod moderation;
\ No newline at end of file
@@ -0,0 +1,346 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serenity::all::{ChannelId, Color, CreateEmbed, CreateEmbedFooter, CreateMessage, GuildId, Member, Message, Timestamp, User};
+use tracing::{error, warn};
+
+use crate::{utils::{basic::{format_duration, parse_duration}, permissions::{user_permission_level, PermissionLevel}, database::moderation::{count_user_cases_in_moderation_db, create_case_in_moderation_db, get_user_cases_in_moderation_db, remove_case_in_moderation_db}}, Context, DataModCase, Error};
+
+const MAX_TIMEOUT: u64 = 28 * 86400; // Longest timeout Discord allows
+const SHOWN_CASES: u64 = 15;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ModAction {
+    Warn,
+    Timeout,
+    Kick,
+    Ban,
+    Unban
+}
+
+impl ModAction {
+    /// Stored in `mod_cases` table
+    pub fn id(&self) -> &'static str {
+        match self {
+            ModAction::Warn => "warn",
+            ModAction::Timeout => "timeout",
+            ModAction::Kick => "kick",
+            ModAction::Ban => "ban",
+            ModAction::Unban => "unban"
+        }
+    }
+
+    pub fn label_from_id(id: &str) -> &'static str {
+        match id {
+            "warn" => "Warn",
+            "timeout" => "Timeout",
+            "kick" => "Kick",
+            "ban" => "Ban",
+            "unban" => "Unban",
+            _ => "Unknown"
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        ModAction::label_from_id(self.id())
+    }
+
+    fn past_tense(&self) -> &'static str {
+        match self {
+            ModAction::Warn => "warned",
+            ModAction::Timeout => "timed out",
+            ModAction::Kick => "kicked",
+            ModAction::Ban => "banned",
+            ModAction::Unban => "unbanned"
+        }
+    }
+
+    fn preposition(&self) -> &'static str {
+        match self {
+            ModAction::Warn | ModAction::Timeout => "in",
+            _ => "from"
+        }
+    }
+
+    /// Member can't be messaged once they're kicked or banned (unless they're in another server with Loopchan)
+    fn notify_before(&self) -> bool {
+        matches!(self, ModAction::Kick | ModAction::Ban)
+    }
+}
+
+/// Moderation Commands
+#[poise::command(slash_command, rename = "mod", custom_data = "PermissionLevel::Staff", subcommands("warn", "timeout", "kick", "ban", "unban", "cases"), subcommand_required)]
+pub async fn moderation(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Returns reason why author can't moderate this user, if there is one
+async fn validate_target(ctx: Context<'_>, user: &User) -> Option<&'static str> {
+    if user.id == ctx.author().id {
+        return Some("You can't moderate yourself.");
+    }
+    if user.bot {
+        return Some("You can't moderate bots.");
+    }
+    // Only owner can moderate staff
+    if ctx.author().id.get() != ctx.data().config.get().owner && user_permission_level(ctx, user).await >= PermissionLevel::Staff {
+        return Some("You can't moderate other staff members.");
+    }
+    None
+}
+
+/// Discord won't let bot timeout/kick/ban members with same or higher role. `None` if it can't be checked from cache.
+fn bot_outranks(ctx: Context<'_>, member: &Member) -> Option<bool> {
+    let guild = ctx.serenity_context().cache.guild(member.guild_id)?;
+    if member.user.id == guild.owner_id {
+        return Some(false);
+    }
+    let bot_member: &Member = guild.members.get(&ctx.framework().bot_id)?;
+    let bot_position: u16 = guild.member_highest_role(bot_member).map(|role| role.position).unwrap_or(0);
+    let member_position: u16 = guild.member_highest_role(member).map(|role| role.position).unwrap_or(0);
+    Some(bot_position > member_position)
+}
+
+fn format_case_details(duration: Option<u64>, reason: &Option<String>) -> String {
+    let mut details: String = String::new();
+    if duration.is_some() {
+        details.push_str(&format!("\n**Duration:** {}", format_duration(duration.unwrap())));
+    }
+    details.push_str(&format!("\n**Reason:** {}", reason.as_deref().unwrap_or("No reason given")));
+    details
+}
+
+async fn notify_target(ctx: Context<'_>, user: &User, action: ModAction, case_id: i64, duration: Option<u64>, reason: &Option<String>) {
+    let sent_message: Result<Message, serenity::Error> = user.direct_message(ctx,
+        CreateMessage::default()
+            .embed(
+                CreateEmbed::default()
+                    .description(format!("You were {} {} **PARKOUR: The Loop**.{}", action.past_tense(), action.preposition(), format_case_details(duration, reason)))
+                    .footer(CreateEmbedFooter::new(format!("Case #{}", case_id)))
+                    .color(if action == ModAction::Unban { Color::from_rgb(100, 255, 100) } else { Color::from_rgb(255, 100, 100) })
+            )
+    ).await;
+    if sent_message.is_err() {
+        error!("Failed to send {} case #{} message to {}: {}", action.id(), case_id, user.id.get(), sent_message.unwrap_err().to_string());
+    }
+}
+
+async fn post_to_mod_log(ctx: Context<'_>, user: &User, action: ModAction, case_id: i64, duration: Option<u64>, reason: &Option<String>) {
+    let mod_log: Option<u64> = ctx.data().config.get().channels.mod_log;
+    if mod_log.is_none() {
+        return;
+    }
+
+    let posted: Result<Message, serenity::Error> = ChannelId::new(mod_log.unwrap()).send_message(ctx,
+        CreateMessage::default()
+            .embed(
+                CreateEmbed::default()
+                    .title(format!("Case #{} • {}", case_id, action.label()))
+                    .description(format!("**Member:** @{} (<@{}>, {})\n**Moderator:** <@{}>{}", user.name, user.id.get(), user.id.get(), ctx.author().id.get(), format_case_details(duration, reason)))
+                    .timestamp(Timestamp::now())
+                    .color(if action == ModAction::Unban { Color::from_rgb(100, 255, 100) } else { Color::from_rgb(255, 100, 100) })
+            )
+    ).await;
+    if posted.is_err() {
+        error!("Failed to post case #{} to mod log: {}", case_id, posted.unwrap_err().to_string());
+    }
+}
+
+/// Opens case, messages member and applies action. Case is removed again if action fails.
+async fn apply_action(
+    ctx: Context<'_>,
+    action: ModAction,
+    user: &User,
+    member: Option<Member>,
+    reason: Option<String>,
+    duration: Option<u64>
+) -> Result<(), Error> {
+    if action != ModAction::Unban {
+        let invalid_reason: Option<&str> = validate_target(ctx, user).await;
+        if invalid_reason.is_some() {
+            ctx.send(poise::CreateReply::default()
+                .content(invalid_reason.unwrap())
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    }
+
+    let outranks: Option<bool> = member.as_ref().and_then(|member| bot_outranks(ctx, member));
+    if action != ModAction::Warn && action != ModAction::Unban && outranks == Some(false) {
+        ctx.send(poise::CreateReply::default()
+            .content(format!("I can't {} <@{}>, their highest role isn't below mine.", action.id(), user.id.get()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    // DM is only sent before action if it's known to go through, otherwise member would be told about action that failed
+    let notify_before: bool = action.notify_before() && outranks == Some(true);
+
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let case_id: Result<i64, async_sqlite::Error> = create_case_in_moderation_db(db_client, user.id.get(), ctx.author().id.get(), action.id(), reason.clone(), duration).await;
+    if case_id.is_err() {
+        error!("Failed to open {} case for {}: {}", action.id(), user.id.get(), case_id.unwrap_err().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to open case. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    let case_id: i64 = case_id.unwrap();
+
+    if notify_before {
+        notify_target(ctx, user, action, case_id, duration, &reason).await;
+    }
+
+    let guild_id: GuildId = ctx.data().config.get().guild.into();
+    let audit_reason: String = format!("Case #{}: {}", case_id, reason.as_deref().unwrap_or("No reason given"));
+    let applied: Result<(), serenity::Error> = match action {
+        ModAction::Warn => Ok(()),
+        ModAction::Timeout => {
+            let until: i64 = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + duration.unwrap_or(0)) as i64;
+            match (member, Timestamp::from_unix_timestamp(until)) {
+                (Some(mut member), Ok(until)) => member.disable_communication_until_datetime(ctx, until).await,
+                _ => Err(serenity::Error::Other("Member or timeout end is missing"))
+            }
+        },
+        ModAction::Kick => match member {
+            Some(member) => member.kick_with_reason(ctx, &audit_reason).await,
+            None => Err(serenity::Error::Other("Member is missing"))
+        },
+        ModAction::Ban => guild_id.ban_with_reason(ctx, user.id, 0, &audit_reason).await,
+        ModAction::Unban => guild_id.unban(ctx, user.id).await
+    };
+
+    if applied.is_err() {
+        error!("Failed to apply {} case #{} to {}: {}", action.id(), case_id, user.id.get(), applied.unwrap_err().to_string());
+        let removed: Result<usize, async_sqlite::Error> = remove_case_in_moderation_db(db_client, case_id).await;
+        if removed.is_err() {
+            error!("Failed to remove case #{} after its action failed: {}", case_id, removed.unwrap_err().to_string());
+        }
+        ctx.send(poise::CreateReply::default()
+            .content(format!("Failed to {} <@{}>. Check terminal logs.", action.id(), user.id.get()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if !notify_before {
+        notify_target(ctx, user, action, case_id, duration, &reason).await;
+    }
+    post_to_mod_log(ctx, user, action, case_id, duration, &reason).await;
+
+    warn!("Case #{}: {} {} @{} ({}) ({})", case_id, ctx.author().name, action.id(), user.name, user.id.get(), reason.as_deref().unwrap_or("no reason"));
+    ctx.send(poise::CreateReply::default()
+        .content(format!("**Case #{}:** <@{}> was {}.{}", case_id, user.id.get(), action.past_tense(), format_case_details(duration, &reason)))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+/// Warn member
+#[poise::command(slash_command)]
+pub async fn warn(
+    ctx: Context<'_>,
+    #[description = "Member"] member: Member,
+    #[description = "Reason (shown to member)"] #[max_length = 500] reason: Option<String>,
+) -> Result<(), Error> {
+    let user: User = member.user.clone();
+    apply_action(ctx, ModAction::Warn, &user, Some(member), reason, None).await
+}
+
+/// Time out member (up to 28 days)
+#[poise::command(slash_command)]
+pub async fn timeout(
+    ctx: Context<'_>,
+    #[description = "Member"] member: Member,
+    #[description = "Duration (e.g. 30m, 1h 30m, 7d)"] duration: String,
+    #[description = "Reason (shown to member)"] #[max_length = 500] reason: Option<String>,
+) -> Result<(), Error> {
+    let seconds: Option<u64> = parse_duration(&duration).filter(|seconds| *seconds > 0 && *seconds <= MAX_TIMEOUT);
+    if seconds.is_none() {
+        ctx.send(poise::CreateReply::default()
+            .content("Duration must be like `30m`, `1h 30m` or `7d`, and at most 28 days.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let user: User = member.user.clone();
+    apply_action(ctx, ModAction::Timeout, &user, Some(member), reason, seconds).await
+}
+
+/// Kick member
+#[poise::command(slash_command)]
+pub async fn kick(
+    ctx: Context<'_>,
+    #[description = "Member"] member: Member,
+    #[description = "Reason (shown to member)"] #[max_length = 500] reason: Option<String>,
+) -> Result<(), Error> {
+    let user: User = member.user.clone();
+    apply_action(ctx, ModAction::Kick, &user, Some(member), reason, None).await
+}
+
+/// Ban user (they don't have to be in server)
+#[poise::command(slash_command)]
+pub async fn ban(
+    ctx: Context<'_>,
+    #[description = "User"] user: User,
+    #[description = "Reason (shown to user)"] #[max_length = 500] reason: Option<String>,
+) -> Result<(), Error> {
+    apply_action(ctx, ModAction::Ban, &user, None, reason, None).await
+}
+
+/// Unban user
+#[poise::command(slash_command)]
+pub async fn unban(
+    ctx: Context<'_>,
+    #[description = "User"] user: User,
+    #[description = "Reason"] #[max_length = 500] reason: Option<String>,
+) -> Result<(), Error> {
+    apply_action(ctx, ModAction::Unban, &user, None, reason, None).await
+}
+
+/// Show member's cases
+#[poise::command(slash_command)]
+pub async fn cases(
+    ctx: Context<'_>,
+    #[description = "User"] user: User,
+) -> Result<(), Error> {
+    let db_client: &async_sqlite::Client = &ctx.data().db_client;
+    let cases: Result<Vec<DataModCase>, async_sqlite::Error> = get_user_cases_in_moderation_db(db_client, user.id.get(), SHOWN_CASES).await;
+    let counts: Result<Vec<(String, u64)>, async_sqlite::Error> = count_user_cases_in_moderation_db(db_client, user.id.get()).await;
+    if cases.is_err() || counts.is_err() {
+        error!("Failed to get {}'s cases: {}", user.id.get(), cases.err().or(counts.err()).unwrap().to_string());
+        ctx.send(poise::CreateReply::default()
+            .content("Failed to get cases. Check terminal logs.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let cases: Vec<DataModCase> = cases.unwrap();
+    let response: String = if cases.is_empty() {
+        format!("<@{}> has no cases.", user.id.get())
+    } else {
+        cases.iter().map(|case| {
+            let duration: String = case.duration.map(|duration| format!(" ({})", format_duration(duration))).unwrap_or_default();
+            format!("**#{}** {}{} • <t:{}:R> by <@{}>\n-# {}", case.id, ModAction::label_from_id(&case.action), duration, case.created_at, case.moderator, case.reason.as_deref().unwrap_or("No reason given"))
+        }).collect::<Vec<String>>().join("\n")
+    };
+    let summary: String = counts.unwrap().iter()
+        .map(|(action, count)| format!("{} {}", count, ModAction::label_from_id(action)))
+        .collect::<Vec<String>>()
+        .join(" • ");
+
+    ctx.send(poise::CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title(format!("Cases of @{}", user.name))
+                .description(response)
+                .footer(CreateEmbedFooter::new(if summary.is_empty() { String::from("No cases") } else { summary }))
+                .color(Color::from_rgb(255, 255, 255))
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
@@ -2,10 +2,11 @@ use std::env;
 
 use serenity::all::{ButtonStyle, Color, CreateActionRow, CreateButton, CreateEmbed};
 
-use crate::{utils::basic::is_qa, Context, Error};
+use crate::{Context, Error};
+use crate::utils::permissions::{user_permission_level, PermissionLevel};
 
 /// QA Managing Commands
-#[poise::command(slash_command, subcommands("sendform"), subcommand_required)]
+#[poise::command(slash_command, custom_data = "PermissionLevel::Staff", subcommands("sendform"), subcommand_required)]
 pub async fn qa(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
 
 #[poise::command(slash_command)]
@@ -13,9 +14,9 @@ pub async fn sendform(
     ctx: Context<'_>,
     #[description = "Member"] user: serenity::model::user::User
 ) -> Result<(), Error> {
-    // Check if user is already QA (ignore owner for testing)
-    if &user.id.get() != &ctx.data().config.owner {
-        if is_qa(ctx, &user).await {
+    // Check if user already has QA permission level (ignore owner for testing)
+    if &user.id.get() != &ctx.data().config.get().owner {
+        if user_permission_level(ctx, &user).await >= PermissionLevel::Qa {
             ctx.send(poise::CreateReply::default()
                 .content("This user is already in QA program.")
                 .ephemeral(true)
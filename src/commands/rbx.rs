@@ -51,9 +51,9 @@ pub async fn verify(
             return Ok(());
         }
 
-        let member: serenity::all::Member = Guild::get(ctx, ctx_data.config.guild).await.unwrap().member(ctx, author_id).await.unwrap();
-        if !member.roles.contains(&RoleId::new(ctx_data.config.roles.member)) {
-            let successfully_gave_member_role: Result<(), serenity::Error> = member.add_role(ctx, RoleId::new(ctx_data.config.roles.member)).await;
+        let member: serenity::all::Member = Guild::get(ctx, ctx_data.config.get().guild).await.unwrap().member(ctx, author_id).await.unwrap();
+        if !member.roles.contains(&RoleId::new(ctx_data.config.get().roles.member)) {
+            let successfully_gave_member_role: Result<(), serenity::Error> = member.add_role(ctx, RoleId::new(ctx_data.config.get().roles.member)).await;
             if !successfully_gave_member_role.is_ok() {
                 error!("{}", &successfully_gave_member_role.err().unwrap().to_string());
                 ctx.send(poise::CreateReply::default()
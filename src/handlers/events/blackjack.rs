@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serenity::all::{ButtonStyle, Color, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage};
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+
+use crate::LoopchanConfig;
+use crate::utils::{database::gambling::pay_out_bet_in_gambling_db, gambling::{apply_house_edge, format_hand, hand_value, BlackjackGame, BlackjackOutcome}};
+
+pub const BLACKJACK_TIMEOUT: Duration = Duration::from_secs(10*60);
+
+pub fn blackjack_components(owner_id: u64) -> Vec<CreateActionRow> {
+    vec![
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("eco.blackjack.hit.{}", owner_id))
+                .label("Hit")
+                .style(ButtonStyle::Primary),
+            CreateButton::new(format!("eco.blackjack.stand.{}", owner_id))
+                .label("Stand")
+                .style(ButtonStyle::Secondary),
+        ])
+    ]
+}
+
+/// `result` is outcome and payout once game is finished
+pub fn blackjack_embed(game: &BlackjackGame, result: Option<(&BlackjackOutcome, u64)>) -> CreateEmbed {
+    let dealer_text: String = if result.is_some() {
+        format!("{} (**{}**)", format_hand(&game.dealer), hand_value(&game.dealer))
+    } else {
+        // Second dealer's card stays hidden until player stands
+        format!("{} `??`", game.dealer[0].display())
+    };
+
+    let mut embed: CreateEmbed = CreateEmbed::default()
+        .title("🃏 Blackjack")
+        .field("Dealer", dealer_text, false)
+        .field("You", format!("{} (**{}**)", format_hand(&game.player), hand_value(&game.player)), false);
+
+    match result {
+        None => {
+            embed = embed
+                .description(format!("Bet: **${}**", game.bet))
+                .color(Color::from_rgb(255, 255, 255))
+                .footer(CreateEmbedFooter::new("Unfinished games are stood automatically after 10 minutes"));
+        },
+        Some((outcome, payout)) => {
+            let (text, color): (String, Color) = match outcome {
+                BlackjackOutcome::Blackjack => (format!("🎉 Blackjack! You won **${}**", payout), Color::from_rgb(100, 255, 100)),
+                BlackjackOutcome::Win => (format!("✅ You won **${}**", payout), Color::from_rgb(100, 255, 100)),
+                BlackjackOutcome::Push => (format!("🤝 Push, your **${}** bet was returned", payout), Color::from_rgb(255, 255, 255)),
+                BlackjackOutcome::Lose => (format!("❌ You lost **${}**", game.bet), Color::from_rgb(255, 100, 100))
+            };
+            embed = embed.description(text).color(color);
+        }
+    }
+
+    embed
+}
+
+/// Closes bet of finished game and pays it out, returns payout after house edge (or `None` if payout failed)
+pub async fn finish_blackjack_game(
+    config: &LoopchanConfig,
+    db_client: &async_sqlite::Client,
+    owner_id: u64,
+    game: &BlackjackGame,
+    outcome: &BlackjackOutcome
+) -> Option<u64> {
+    let payout: u64 = apply_house_edge(game.bet, game.payout(outcome), config.economy.gamble_house_edge);
+
+    let paid_out: Result<bool, async_sqlite::Error> = pay_out_bet_in_gambling_db(db_client, owner_id, payout, "blackjack").await;
+    if paid_out.is_err() {
+        error!("Failed to pay out ${} blackjack win to {}: {} | Please pay out manually.", payout, owner_id, paid_out.unwrap_err().to_string());
+        return None;
+    }
+    if !paid_out.unwrap() {
+        error!("Blackjack bet of {} wasn't open anymore, ${} wasn't paid out | Please check manually.", owner_id, payout);
+        return None;
+    }
+
+    Some(payout)
+}
+
+/// Stands for players who left their game unfinished and pays it out.
+/// Bet isn't just refunded, since then anyone could wait out a bad hand.
+pub async fn finish_expired_blackjack_games(
+    config: &LoopchanConfig,
+    db_client: &async_sqlite::Client,
+    games: &Mutex<HashMap<u64, BlackjackGame>>
+) {
+    let expired: Vec<(u64, BlackjackGame)> = {
+        let mut games = games.lock().await;
+        let expired_ids: Vec<u64> = games.iter()
+            .filter(|(_, game)| game.bet_placed && game.started_at.elapsed() > BLACKJACK_TIMEOUT)
+            .map(|(owner_id, _)| *owner_id)
+            .collect();
+        expired_ids.into_iter().filter_map(|owner_id| games.remove(&owner_id).map(|game| (owner_id, game))).collect()
+    };
+
+    for (owner_id, mut game) in expired {
+        let outcome: BlackjackOutcome = game.stand();
+        let payout: Option<u64> = finish_blackjack_game(config, db_client, owner_id, &game, &outcome).await;
+        if payout.is_some() {
+            warn!("Finished {}'s unfinished blackjack game automatically, paid out ${}", owner_id, payout.unwrap());
+        }
+    }
+}
+
+pub async fn handle_interaction(
+    ctx: &serenity::prelude::Context,
+    interaction: ComponentInteraction,
+    data: &crate::Data
+) {
+    let interaction_id: &String = &interaction.data.custom_id;
+    let owner_id: Option<u64> = interaction_id.rsplit('.').next().and_then(|id| id.parse().ok());
+
+    if owner_id != Some(interaction.user.id.get()) {
+        interaction.create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description("❌ This is not your game! Start your own with `/eco blackjack`")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .ephemeral(true)
+            )
+        ).await.unwrap();
+        return;
+    }
+    let owner_id: u64 = owner_id.unwrap();
+
+    let mut games = data.blackjack_games.lock().await;
+    let game: Option<&mut BlackjackGame> = games.get_mut(&owner_id);
+
+    // Bet of reserved game is still being placed, buttons can only be from older game
+    if game.is_none() || !game.as_ref().unwrap().bet_placed {
+        drop(games);
+
+        interaction.create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description("❌ This game has already ended.")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .components(vec![])
+            )
+        ).await.unwrap();
+        return;
+    }
+    let game: &mut BlackjackGame = game.unwrap();
+    let expired: bool = game.started_at.elapsed() > BLACKJACK_TIMEOUT;
+
+    let outcome: Option<BlackjackOutcome> = if expired {
+        Some(game.stand())
+    } else if interaction_id.starts_with("eco.blackjack.hit.") {
+        game.hit();
+        match hand_value(&game.player) {
+            value if value > 21 => Some(BlackjackOutcome::Lose),
+            21 => Some(game.stand()),
+            _ => None
+        }
+    } else {
+        Some(game.stand())
+    };
+
+    if outcome.is_none() {
+        let embed: CreateEmbed = blackjack_embed(game, None);
+        drop(games);
+
+        interaction.create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::default()
+                    .embed(embed)
+                    .components(blackjack_components(owner_id))
+            )
+        ).await.unwrap();
+        return;
+    }
+    let outcome: BlackjackOutcome = outcome.unwrap();
+
+    // Game is removed before paying out so it can't be paid twice
+    let game: BlackjackGame = games.remove(&owner_id).unwrap();
+    drop(games);
+
+    let payout: Option<u64> = finish_blackjack_game(&data.config.get(), &data.db_client, owner_id, &game, &outcome).await;
+    if payout.is_none() {
+        interaction.create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::default()
+                    .embed(
+                        blackjack_embed(&game, Some((&outcome, 0)))
+                            .description("❌ Failed to pay out your winnings. Please contact <@908779319084589067> to receive them.")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .components(vec![])
+            )
+        ).await.unwrap();
+        return;
+    }
+
+    let mut embed: CreateEmbed = blackjack_embed(&game, Some((&outcome, payout.unwrap())));
+    if expired {
+        embed = embed.footer(CreateEmbedFooter::new("Game was left unfinished for 10 minutes, so you stood automatically"));
+    }
+    interaction.create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(embed)
+                .components(vec![])
+        )
+    ).await.unwrap();
+}
@@ -1,40 +1,468 @@
-use ::serenity::all::{Member, CreateMessage, CreateEmbed, Color, Message, Error};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ::serenity::all::{ButtonStyle, ChannelId, ComponentInteraction, CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, Member, CreateMessage, CreateEmbed, Color, Message, User, UserId};
 
 use poise::serenity_prelude as serenity;
-use tracing::error;
+use poise::Modal;
+use tracing::{error, warn};
+
+use crate::{DataBlacklistAppeal, DataBlacklistEntry, LoopchanConfig};
+use crate::utils::audit::record_audit_entry;
+use crate::utils::database::settings::get_setting_in_settings_db;
+use crate::utils::permissions::{member_permission_level, permission_level_of, PermissionLevel};
+use crate::utils::database::blacklist::{
+    create_appeal_in_blacklist_db,
+    decide_appeal_in_blacklist_db,
+    delete_appeal_in_blacklist_db,
+    get_all_users_in_blacklist_db,
+    get_appeal_in_blacklist_db,
+    get_last_appeal_in_blacklist_db,
+    get_user_in_blacklist_db,
+    take_expired_users_in_blacklist_db
+};
+
+const APPEAL_MODAL_TIMEOUT: Duration = Duration::from_secs(900);
+// Set while imported entries weren't reviewed yet, sweeps only report who they would ban until `/adm blacklist sweep confirm:True`
+pub const SWEEP_REVIEW_SETTING: &'static str = "blacklist.sweep_review";
+
+#[derive(Debug, poise::Modal)]
+#[name = "Blacklist Appeal"]
+struct BlacklistAppealModal {
+    #[name = "Why should your blacklist be lifted?"]
+    #[placeholder = "Explain what happened and why we should give you another chance."]
+    #[paragraph]
+    #[min_length = 20]
+    #[max_length = 1000]
+    appeal: String
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// Appeal button shown under blacklist notice, empty if appeals are disabled
+pub fn blacklist_components(config: &LoopchanConfig) -> Vec<CreateActionRow> {
+    if config.channels.blacklist_appeals.is_none() {
+        return vec![];
+    }
+    vec![
+        CreateActionRow::Buttons(vec![
+            CreateButton::new("blacklist.appeal")
+                .label("Appeal")
+                .style(ButtonStyle::Secondary)
+                .emoji('📝')
+        ])
+    ]
+}
+
+/// Blacklist notice sent to user, also shown by `/blacklist_check`
+pub fn blacklist_embed(entry: &DataBlacklistEntry, config: &LoopchanConfig) -> CreateEmbed {
+    let mut description: String = String::from("Hello! You have been blacklisted from **PARKOUR: The Loop**.\nThis means that you will not be able to access any of the content related to **PTL**, or it's community.");
+    match &entry.reason {
+        Some(reason) => description.push_str(&format!("\n\n**Reason:** {}", reason)),
+        None => description.push_str("\nReason of this blacklist wasn't given, if you were blacklisted this means that you should know what you did.")
+    }
+    match entry.expires_at {
+        Some(expires_at) => description.push_str(&format!("\n**Expires:** <t:{}:R>", expires_at)),
+        None => description.push_str("\n**Expires:** never")
+    }
+    if config.channels.blacklist_appeals.is_some() {
+        description.push_str("\n\nIf you think you deserve another chance, you can appeal with the button below.");
+    } else {
+        description.push_str("\n\nCurrently appealing a blacklist is not possible. However, we will let you know if that changes ever.");
+    }
+
+    CreateEmbed::default()
+        .description(description)
+        .color(Color::from_rgb(255, 0, 0))
+}
+
+/// DMs user about their blacklist, fails silently if their DMs are closed
+pub async fn send_blacklist_notice(ctx: &serenity::Context, config: &LoopchanConfig, user: &User, entry: &DataBlacklistEntry) {
+    let sent_message: Result<Message, serenity::Error> = user.direct_message(ctx,
+        CreateMessage::default()
+            .embed(blacklist_embed(entry, config))
+            .components(blacklist_components(config))
+    ).await;
+
+    if sent_message.is_err() {
+        error!("Failed to send blacklist message to {}: {}", user.id.get(), sent_message.unwrap_err().to_string());
+        return;
+    }
+    let replied: Result<Message, serenity::Error> = sent_message.unwrap().reply(ctx, "You can run command ```/blacklist_check``` in loopchan's DMs to check your blacklist status at any time.").await;
+    if replied.is_err() {
+        error!("Failed to send blacklist check hint to {}: {}", user.id.get(), replied.unwrap_err().to_string());
+    }
+}
 
 pub async fn blacklist_check(
     ctx: &serenity::Context,
     new_member: &Member,
     data: &crate::Data
-) -> Result<bool, Error> {
-    let blacklist: &Vec<u64> = &data.config.blacklist;
-    if blacklist.contains(&new_member.user.id.get()) {
-        let sent_message: Result<Message, Error> = new_member.user.direct_message(ctx,
-            CreateMessage::default()
+) -> Result<bool, crate::Error> {
+    let entry: Option<DataBlacklistEntry> = get_user_in_blacklist_db(&data.db_client, new_member.user.id.get()).await?;
+    if entry.is_none() {
+        return Ok(false);
+    }
+
+    send_blacklist_notice(ctx, &data.config.get(), &new_member.user, &entry.unwrap()).await;
+
+    let is_banned: Result<(), serenity::Error> = new_member.ban_with_reason(ctx, 7, "Blacklisted UserId").await;
+
+    if is_banned.is_err() {
+        let err_unwrapped = is_banned.unwrap_err();
+        error!("Failed to ban {}: {} (blacklist ban)", new_member.user.id.get(), err_unwrapped.to_string());
+        return Err(err_unwrapped.into());
+    }
+
+    Ok(true)
+}
+
+/// Blacklisted users found in PTL server by sweep
+#[derive(Default)]
+pub struct BlacklistSweep {
+    pub banned: Vec<u64>,
+    pub pending: Vec<u64>, // Would've been banned, but sweep was report-only or ban failed
+    pub conflicts: Vec<u64> // Owner and staff, sweep never bans them
+}
+
+/// Bans blacklisted users who are already in PTL server (e.g. added by ID or imported while they were in).
+/// Owner and staff are only reported as conflicts, `report_only` doesn't ban anyone.
+/// Uses cached members, so it only sees everyone once server was chunked.
+pub async fn sweep_blacklisted_members(
+    ctx: &serenity::Context,
+    config: &LoopchanConfig,
+    db_client: &async_sqlite::Client,
+    report_only: bool
+) -> BlacklistSweep {
+    let mut sweep: BlacklistSweep = BlacklistSweep::default();
+    let entries: Result<Vec<DataBlacklistEntry>, async_sqlite::Error> = get_all_users_in_blacklist_db(db_client).await;
+    if entries.is_err() {
+        error!("Failed to get blacklist for sweep: {}", entries.unwrap_err().to_string());
+        return sweep;
+    }
+
+    let present: Vec<(Member, DataBlacklistEntry)> = {
+        let guild = ctx.cache.guild(config.guild);
+        if guild.is_none() {
+            return sweep;
+        }
+        let guild = guild.unwrap();
+        entries.unwrap().into_iter()
+            .filter_map(|entry| guild.members.get(&entry.discord_id.into()).cloned().map(|member| (member, entry)))
+            .collect()
+    };
+
+    for (member, entry) in present {
+        if member_permission_level(config, &member) >= PermissionLevel::Staff {
+            sweep.conflicts.push(entry.discord_id);
+            continue;
+        }
+        if report_only {
+            sweep.pending.push(entry.discord_id);
+            continue;
+        }
+
+        send_blacklist_notice(ctx, config, &member.user, &entry).await;
+        let is_banned: Result<(), serenity::Error> = member.ban_with_reason(ctx, 0, "Blacklisted UserId (sweep)").await;
+        if is_banned.is_err() {
+            error!("Failed to ban {}: {} (blacklist sweep)", entry.discord_id, is_banned.unwrap_err().to_string());
+            sweep.pending.push(entry.discord_id);
+            continue;
+        }
+        sweep.banned.push(entry.discord_id);
+    }
+
+    if !sweep.conflicts.is_empty() {
+        warn!("Blacklist sweep skipped owner/staff members who are blacklisted: {:?}", sweep.conflicts);
+    }
+    sweep
+}
+
+/// Automatic sweep (after server was chunked and periodically), only reports while imported blacklist wasn't reviewed
+pub async fn run_blacklist_sweep(
+    ctx: &serenity::Context,
+    config: &LoopchanConfig,
+    db_client: &async_sqlite::Client
+) {
+    let review_pending: Result<Option<String>, async_sqlite::Error> = get_setting_in_settings_db(db_client, SWEEP_REVIEW_SETTING).await;
+    if review_pending.is_err() {
+        error!("Failed to check if blacklist sweep needs review: {}", review_pending.unwrap_err().to_string());
+        return;
+    }
+    let report_only: bool = review_pending.unwrap().is_some();
+
+    let sweep: BlacklistSweep = sweep_blacklisted_members(ctx, config, db_client, report_only).await;
+    if !sweep.banned.is_empty() {
+        warn!("Banned {} blacklisted members who were already in server", sweep.banned.len());
+    }
+    if report_only && !sweep.pending.is_empty() {
+        warn!("{} blacklisted members are in server but weren't banned since imported blacklist wasn't reviewed yet, use /adm blacklist sweep", sweep.pending.len());
+    }
+}
+
+/// Unbans users whose blacklist expired
+pub async fn lift_expired_blacklists(
+    ctx: &serenity::prelude::Context,
+    db_client: &async_sqlite::Client,
+    guild_id: u64
+) {
+    let expired: Result<Vec<DataBlacklistEntry>, async_sqlite::Error> = take_expired_users_in_blacklist_db(db_client).await;
+    if expired.is_err() {
+        error!("Failed to get expired blacklist entries: {}", expired.unwrap_err().to_string());
+        return;
+    }
+
+    for entry in expired.unwrap() {
+        let unbanned: Result<(), serenity::Error> = ctx.http.remove_ban(guild_id.into(), entry.discord_id.into(), Some("Blacklist expired")).await;
+        if unbanned.is_err() {
+            // User might've been unbanned manually already
+            warn!("Failed to unban {} after their blacklist expired: {}", entry.discord_id, unbanned.unwrap_err().to_string());
+        } else {
+            warn!("Blacklist of {} expired, they were unbanned", entry.discord_id);
+        }
+    }
+}
+
+/// Same as `poise::execute_modal_on_component_interaction`, which needs poise's context that event handlers don't have.
+/// `None` if user didn't submit in time.
+async fn show_appeal_modal(
+    ctx: &serenity::prelude::Context,
+    interaction: &ComponentInteraction
+) -> Result<Option<BlacklistAppealModal>, serenity::Error> {
+    let modal_id: String = format!("blacklist.appeal.modal.{}", interaction.id.get());
+    interaction.create_response(ctx, BlacklistAppealModal::create(None, modal_id.clone())).await?;
+
+    let submitted: Option<serenity::ModalInteraction> = serenity::ModalInteractionCollector::new(&ctx.shard)
+        .filter(move |submitted| submitted.data.custom_id == modal_id)
+        .timeout(APPEAL_MODAL_TIMEOUT)
+        .await;
+    if submitted.is_none() {
+        return Ok(None);
+    }
+    let submitted: serenity::ModalInteraction = submitted.unwrap();
+
+    // Closes the form
+    submitted.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+    Ok(Some(BlacklistAppealModal::parse(submitted.data.clone()).map_err(serenity::Error::Other)?))
+}
+
+async fn respond_with_error(ctx: &serenity::prelude::Context, interaction: &ComponentInteraction, description: &str) {
+    let responded: Result<(), serenity::Error> = interaction.create_response(
+        ctx,
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::default()
                 .embed(
                     CreateEmbed::default()
-                        .description("Hello! You have been blacklisted from **PARKOUR: The Loop**.\nThis means that you will not be able to access any of the content related to **PTL**, or it's community.\nReasons of blacklisting are not being disclosed, if you were blacklisted this means that you should know what you did.\nCurrently appealing a blacklist is not possible. However, we will let you know if that changes ever.")
-                        .color(Color::from_rgb(255, 0, 0))
+                        .description(description)
+                        .color(Color::from_rgb(255, 100, 100))
                 )
-        ).await;
+                .ephemeral(true)
+        )
+    ).await;
+    if responded.is_err() {
+        error!("Failed to respond to {}'s {} interaction: {}", interaction.user.id.get(), interaction.data.custom_id, responded.unwrap_err().to_string());
+    }
+}
 
-        if sent_message.is_err() {
-            error!("Failed to send blacklist message to {}: {}", new_member.user.id.get(), sent_message.unwrap_err().to_string());
-        } else {
-            sent_message.unwrap().reply(ctx, "You can run command ```/blacklist_check``` in loopchan's DMs to get your blacklist status and appeal server once it becomes available.").await?;
+/// Appeal button in DMs, opens appeal form and posts it to staff
+async fn handle_appeal_button(
+    ctx: &serenity::prelude::Context,
+    interaction: ComponentInteraction,
+    data: &crate::Data
+) {
+    let discord_id: u64 = interaction.user.id.get();
+    if data.config.get().channels.blacklist_appeals.is_none() {
+        return respond_with_error(ctx, &interaction, "Appealing a blacklist is currently not possible.").await;
+    }
+    let appeals_channel: ChannelId = data.config.get().channels.blacklist_appeals.unwrap().into();
+
+    let entry: Result<Option<DataBlacklistEntry>, async_sqlite::Error> = get_user_in_blacklist_db(&data.db_client, discord_id).await;
+    if entry.is_err() {
+        error!("Failed to get {}'s blacklist entry: {}", discord_id, entry.unwrap_err().to_string());
+        return respond_with_error(ctx, &interaction, "❌ Failed to check your blacklist status! Please try again later, if the issue persists contact <@908779319084589067>").await;
+    }
+    let entry: Option<DataBlacklistEntry> = entry.unwrap();
+    if entry.is_none() {
+        return respond_with_error(ctx, &interaction, "You are not blacklisted anymore.").await;
+    }
+    let entry: DataBlacklistEntry = entry.unwrap();
+
+    let last_appeal: Result<Option<DataBlacklistAppeal>, async_sqlite::Error> = get_last_appeal_in_blacklist_db(&data.db_client, discord_id).await;
+    if last_appeal.is_err() {
+        error!("Failed to get {}'s last blacklist appeal: {}", discord_id, last_appeal.unwrap_err().to_string());
+        return respond_with_error(ctx, &interaction, "❌ Failed to check your appeals! Please try again later, if the issue persists contact <@908779319084589067>").await;
+    }
+    if let Some(last_appeal) = last_appeal.unwrap() {
+        if last_appeal.accepted.is_none() {
+            return respond_with_error(ctx, &interaction, "Your appeal is still being reviewed, please be patient.").await;
+        }
+        // Accepted appeal lifts blacklist, so user was blacklisted again and can appeal right away
+        if last_appeal.accepted == Some(false) {
+            let can_appeal_at: u64 = last_appeal.decided_at.unwrap_or(0) + data.config.get().blacklist_appeal_cooldown.unwrap_or(0) * 86400;
+            if can_appeal_at > now_secs() {
+                return respond_with_error(ctx, &interaction, &format!("Your last appeal was denied. You can appeal again <t:{}:R>.", can_appeal_at)).await;
+            }
         }
+    }
 
-        let is_banned: Result<(), serenity::Error> = new_member.ban_with_reason(ctx, 7, "Blacklisted UserId").await;
+    let submitted: Result<Option<BlacklistAppealModal>, serenity::Error> = show_appeal_modal(ctx, &interaction).await;
+    if submitted.is_err() {
+        error!("Failed to get blacklist appeal form from {}: {}", discord_id, submitted.unwrap_err().to_string());
+        return;
+    }
+    let submitted: Option<BlacklistAppealModal> = submitted.unwrap();
+    if submitted.is_none() {
+        return;
+    }
+    let appeal: String = submitted.unwrap().appeal;
 
-        if is_banned.is_err() {
-            let err_unwrapped = is_banned.unwrap_err();
-            error!("Failed to ban {}: {} (blacklist ban)", new_member.user.id.get(), err_unwrapped.to_string());
-            return Err(err_unwrapped);
+    let appeal_id: Result<i64, async_sqlite::Error> = create_appeal_in_blacklist_db(&data.db_client, discord_id, appeal.clone()).await;
+    if appeal_id.is_err() {
+        error!("Failed to save {}'s blacklist appeal: {}", discord_id, appeal_id.unwrap_err().to_string());
+        let _ = interaction.channel_id.say(ctx, "❌ Failed to send your appeal! Please try again later, if the issue persists contact <@908779319084589067>").await;
+        return;
+    }
+    let appeal_id: i64 = appeal_id.unwrap();
+
+    let posted: Result<Message, serenity::Error> = appeals_channel.send_message(ctx,
+        CreateMessage::default()
+            .embed(
+                CreateEmbed::default()
+                    .title(format!("Blacklist Appeal #{}", appeal_id))
+                    .description(format!("@{} (<@{}>, {})\nBlacklisted <t:{}:R> • {}\n\n{}", interaction.user.name, discord_id, discord_id, entry.created_at, entry.reason.as_deref().unwrap_or("No reason"), appeal))
+                    .color(Color::from_rgb(255, 255, 255))
+            )
+            .components(vec![
+                CreateActionRow::Buttons(vec![
+                    CreateButton::new(format!("blacklist.appeal.accept.{}", appeal_id))
+                        .label("Accept")
+                        .style(ButtonStyle::Success),
+                    CreateButton::new(format!("blacklist.appeal.deny.{}", appeal_id))
+                        .label("Deny")
+                        .style(ButtonStyle::Danger)
+                ])
+            ])
+    ).await;
+    if posted.is_err() {
+        // Nobody would see the appeal, delete it so user isn't stuck with pending one
+        error!("Failed to post {}'s blacklist appeal #{} to appeals channel: {}", discord_id, appeal_id, posted.unwrap_err().to_string());
+        let deleted: Result<(), async_sqlite::Error> = delete_appeal_in_blacklist_db(&data.db_client, appeal_id).await;
+        if deleted.is_err() {
+            error!("Failed to delete unposted blacklist appeal #{}: {}", appeal_id, deleted.unwrap_err().to_string());
         }
+        let _ = interaction.channel_id.say(ctx, "❌ Failed to send your appeal! Please try again later, if the issue persists contact <@908779319084589067>").await;
+        return;
+    }
+
+    warn!("@{} ({}) appealed their blacklist (#{})", interaction.user.name, discord_id, appeal_id);
+    let confirmed: Result<Message, serenity::Error> = interaction.channel_id.send_message(ctx,
+        CreateMessage::default()
+            .embed(
+                CreateEmbed::default()
+                    .description("Your appeal was sent! We'll message you once it's reviewed.")
+                    .color(Color::from_rgb(100, 255, 100))
+            )
+    ).await;
+    if confirmed.is_err() {
+        error!("Failed to confirm blacklist appeal to {}: {}", discord_id, confirmed.unwrap_err().to_string());
+    }
+}
+
+/// Accept/Deny buttons under appeal in staff channel
+async fn handle_appeal_decision(
+    ctx: &serenity::prelude::Context,
+    interaction: ComponentInteraction,
+    data: &crate::Data
+) {
+    // blacklist.appeal.<accept|deny>.<appeal id>
+    let parts: Vec<&str> = interaction.data.custom_id.split('.').collect();
+    if parts.len() != 4 {
+        return;
+    }
+    let accepted: bool = parts[2] == "accept";
+    let appeal_id: Option<i64> = parts[3].parse().ok();
+    if appeal_id.is_none() {
+        return;
+    }
+    let appeal_id: i64 = appeal_id.unwrap();
+    let moderator_id: u64 = interaction.user.id.get();
+
+    // Anyone who can see appeals channel can press these buttons
+    let level: PermissionLevel = permission_level_of(ctx, data, interaction.user.id).await;
+    if level < PermissionLevel::Staff {
+        record_audit_entry(ctx, data, &interaction.user, format!("blacklist appeal {}", parts[2]), format!("appeal=#{}", appeal_id), None, format!("denied (needs {}, has {})", PermissionLevel::Staff.name(), level.name())).await;
+        return respond_with_error(ctx, &interaction, &format!("🔒 You need **{}** permission level to decide appeals.", PermissionLevel::Staff.name())).await;
+    }
 
-        return Ok(true);
+    let appeal: Result<Option<DataBlacklistAppeal>, async_sqlite::Error> = get_appeal_in_blacklist_db(&data.db_client, appeal_id).await;
+    if appeal.is_err() || appeal.as_ref().unwrap().is_none() {
+        error!("Failed to get blacklist appeal #{}: {}", appeal_id, appeal.map_or_else(|err| err.to_string(), |_| String::from("not found")));
+        return respond_with_error(ctx, &interaction, "Failed to get this appeal. Check terminal logs.").await;
     }
+    let appeal: DataBlacklistAppeal = appeal.unwrap().unwrap();
 
-    return Ok(false);
-}
\ No newline at end of file
+    let decided: Result<bool, async_sqlite::Error> = decide_appeal_in_blacklist_db(&data.db_client, appeal_id, accepted, moderator_id).await;
+    if decided.is_err() {
+        error!("Failed to decide blacklist appeal #{}: {}", appeal_id, decided.unwrap_err().to_string());
+        return respond_with_error(ctx, &interaction, "Failed to save decision. Check terminal logs.").await;
+    }
+    if !decided.unwrap() {
+        return respond_with_error(ctx, &interaction, "This appeal was already decided.").await;
+    }
+
+    let outcome: String = if accepted {
+        let unbanned: Result<(), serenity::Error> = ctx.http.remove_ban(data.config.get().guild.into(), appeal.discord_id.into(), Some("Blacklist appeal accepted")).await;
+        if unbanned.is_err() {
+            warn!("Failed to unban {} after accepting their appeal: {}", appeal.discord_id, unbanned.unwrap_err().to_string());
+        }
+        String::from("Your blacklist appeal was accepted! You are no longer blacklisted from **PARKOUR: The Loop** and can join the server again.")
+    } else {
+        let can_appeal_at: u64 = now_secs() + data.config.get().blacklist_appeal_cooldown.unwrap_or(0) * 86400;
+        format!("Your blacklist appeal was denied. You can appeal again <t:{}:R>.", can_appeal_at)
+    };
+
+    let notified: Result<Message, serenity::Error> = UserId::new(appeal.discord_id).direct_message(ctx,
+        CreateMessage::default()
+            .embed(
+                CreateEmbed::default()
+                    .description(outcome)
+                    .color(if accepted { Color::from_rgb(100, 255, 100) } else { Color::from_rgb(255, 100, 100) })
+            )
+    ).await;
+    if notified.is_err() {
+        error!("Failed to send blacklist appeal #{} outcome to {}: {}", appeal_id, appeal.discord_id, notified.unwrap_err().to_string());
+    }
+
+    record_audit_entry(ctx, data, &interaction.user, format!("blacklist appeal {}", parts[2]), format!("appeal=#{}", appeal_id), Some(appeal.discord_id), String::from("success")).await;
+
+    let mut embed: CreateEmbed = interaction.message.embeds.first().cloned().map(CreateEmbed::from).unwrap_or_default();
+    embed = embed
+        .field("Decision", format!("{} by <@{}> <t:{}:R>", if accepted { "Accepted" } else { "Denied" }, moderator_id, now_secs()), false)
+        .color(if accepted { Color::from_rgb(100, 255, 100) } else { Color::from_rgb(255, 100, 100) });
+    let updated: Result<(), serenity::Error> = interaction.create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(embed)
+                .components(vec![])
+        )
+    ).await;
+    if updated.is_err() {
+        error!("Failed to update blacklist appeal #{} message: {}", appeal_id, updated.unwrap_err().to_string());
+    }
+}
+
+pub async fn handle_interaction(
+    ctx: &serenity::prelude::Context,
+    interaction: ComponentInteraction,
+    data: &crate::Data
+) {
+    let interaction_id = &interaction.data.custom_id;
+    if interaction_id == "blacklist.appeal" {
+        return handle_appeal_button(ctx, interaction, data).await;
+    }
+    if interaction_id.starts_with("blacklist.appeal.accept.") || interaction_id.starts_with("blacklist.appeal.deny.") {
+        return handle_appeal_decision(ctx, interaction, data).await;
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ::serenity::all::{ChannelId, Color, CreateAllowedMentions, CreateAttachment, CreateEmbed, CreateMessage, Member, User};
+
+use poise::serenity_prelude as serenity;
+
+use tracing::{error, warn};
+
+use crate::Error;
+use crate::handlers::events::welcomecard::fetch_avatar;
+use crate::utils::basic::format_duration;
+use crate::utils::database::members::{count_quick_leaves_in_members_db, log_member_leave_in_members_db};
+use crate::utils::render::{load_layout, render_layout, Layout, LayoutValues};
+
+const GOODBYECARD_LAYOUT: &str = "goodbyecard.toml";
+const DEFAULT_GOODBYE_MESSAGE: &str = "Goodbye {mention}, you were with us for {duration}.";
+
+/// Forgets everything about member kept in memory
+async fn clear_member_state(data: &crate::Data, discord_id: u64) {
+    data.exp_cooldowns.lock().await.remove(&discord_id);
+    data.regenerations_cooldowns.lock().await.remove(&discord_id);
+    data.verifications.lock().await.remove(&discord_id);
+    data.roleshop_purchases.lock().await.remove(&discord_id);
+    data.profile_cards.lock().await.remove(&discord_id);
+    // Unfinished blackjack game is kept, it's finished automatically once it times out
+}
+
+async fn render_goodbyecard(user: &User, duration: &String) -> Result<Vec<u8>, Error> {
+    let layout: Layout = load_layout(GOODBYECARD_LAYOUT)?;
+    let values: LayoutValues = LayoutValues::default()
+        .text("username", user.name.clone())
+        .text("display_name", user.display_name())
+        .text("duration", duration.clone())
+        .image("avatar", fetch_avatar(user).await);
+    Ok(render_layout(&layout, &values)?)
+}
+
+pub async fn goodbyecard(
+    ctx: &serenity::Context,
+    user: &User,
+    member: Option<&Member>,
+    data: &crate::Data
+) -> Result<(), Error> {
+    let goodbye_config = &data.config.get().goodbyecard;
+    let discord_id: u64 = user.id.get();
+    let left_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    // Member is only known if they were cached, which they should be since whole server is chunked on startup
+    let joined_at: Option<u64> = member.and_then(|member| member.joined_at).map(|joined_at| joined_at.unix_timestamp().max(0) as u64);
+    let membership_duration: Option<u64> = joined_at.map(|joined_at| left_at.saturating_sub(joined_at));
+    let quick_leave: bool = goodbye_config.quick_leave.is_some_and(|quick_leave| membership_duration.is_some_and(|duration| duration < quick_leave * 60));
+
+    let logged: Result<usize, async_sqlite::Error> = log_member_leave_in_members_db(&data.db_client, discord_id, joined_at, left_at, quick_leave).await;
+    if logged.is_err() {
+        error!("Failed to log {}'s leave: {}", discord_id, logged.unwrap_err().to_string());
+    }
+
+    if quick_leave && goodbye_config.quick_leave_alert.is_some() {
+        let quick_leaves: Result<u64, async_sqlite::Error> = count_quick_leaves_in_members_db(&data.db_client, left_at - 3600).await;
+        if quick_leaves.is_err() {
+            error!("Failed to count quick leaves: {}", quick_leaves.unwrap_err().to_string());
+        } else if quick_leaves.as_ref().unwrap() >= &goodbye_config.quick_leave_alert.unwrap() {
+            warn!("{} members joined and left within {} minutes in the last hour (latest: @{} {}), possible raid!", quick_leaves.unwrap(), goodbye_config.quick_leave.unwrap(), user.name, discord_id);
+        }
+    }
+
+    if goodbye_config.clear_state.unwrap_or(false) {
+        clear_member_state(data, discord_id).await;
+    }
+
+    if !goodbye_config.enabled || goodbye_config.channel.is_none() {
+        return Ok(());
+    }
+    let goodbyes_channel: ChannelId = goodbye_config.channel.unwrap().into();
+
+    let duration: String = membership_duration.map(format_duration).unwrap_or(String::from("a while"));
+    let text: String = goodbye_config.message.clone().unwrap_or(DEFAULT_GOODBYE_MESSAGE.to_string())
+        .replace("{mention}", &format!("<@{}>", discord_id))
+        .replace("{username}", &user.name)
+        .replace("{duration}", &duration);
+
+    let mut embed: CreateEmbed = CreateEmbed::default()
+        .description(text)
+        .color(Color::from_rgb(255, 255, 255));
+    let mut message: CreateMessage = CreateMessage::default()
+        .allowed_mentions(CreateAllowedMentions::new().empty_users());
+
+    if goodbye_config.card.unwrap_or(false) {
+        let card: Result<Vec<u8>, Error> = render_goodbyecard(user, &duration).await;
+        if card.is_ok() {
+            let attachment: CreateAttachment = CreateAttachment::bytes(card.unwrap(), format!("goodbyecard{}.png", discord_id));
+            embed = embed.attachment(attachment.filename.clone());
+            message = message.add_file(attachment);
+        } else {
+            error!("Failed to render {}'s goodbye card: {}", discord_id, card.unwrap_err().to_string());
+        }
+    }
+
+    let goodbye_message: Result<serenity::model::prelude::Message, serenity::Error> = goodbyes_channel.send_message(ctx, message.embed(embed)).await;
+    if goodbye_message.is_err() {
+        error!("Failed to send {}'s goodbye message: {}", user.name, goodbye_message.unwrap_err().to_string());
+    }
+
+    Ok(())
+}
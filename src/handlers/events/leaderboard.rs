@@ -0,0 +1,201 @@
+use serenity::all::{ButtonStyle, Color, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage};
+use tracing::error;
+
+use crate::{commands::eco::{exp_needed_to_next_level, LeaderboardType}, utils::{basic::generate_emoji_progressbar, database::economy::{
+    build_balance_leaderboard_from_eco_db,
+    build_level_leaderboard_from_eco_db,
+    count_users_in_leaderboard_from_eco_db,
+    get_user_placement_in_balance_leaderboard,
+    get_user_placement_in_level_leaderboard
+}}};
+
+pub const LEADERBOARD_PAGE_SIZE: u64 = 5;
+
+fn leaderboard_type_id(lbtype: &LeaderboardType) -> &'static str {
+    match lbtype {
+        LeaderboardType::Level => "level",
+        LeaderboardType::Balance => "balance"
+    }
+}
+
+fn placement_emoji(placement: u64) -> &'static str {
+    match placement {
+        1 => "<a:WINNER:1367093328864346122> ",
+        2 => ":second_place: ",
+        3 => ":third_place: ",
+        _ => ""
+    }
+}
+
+/// JSON array of IDs of members currently in PTL server, or `None` if cache doesn't have all of them yet
+pub fn get_guild_member_ids(ctx: &serenity::prelude::Context, data: &crate::Data) -> Option<String> {
+    let guild = ctx.cache.guild(data.config.get().guild)?;
+    if (guild.members.len() as u64) < guild.member_count {
+        return None;
+    }
+    let member_ids: Vec<u64> = guild.members.keys().map(|id| id.get()).collect();
+    serde_json::to_string(&member_ids).ok()
+}
+
+/// `page` starts from 0, if it's `None` page with `owner_id`'s placement is shown
+pub async fn build_leaderboard_page(
+    ctx: &serenity::prelude::Context,
+    data: &crate::Data,
+    lbtype: &LeaderboardType,
+    page: Option<u64>,
+    owner_id: u64
+) -> Result<(CreateEmbed, Vec<CreateActionRow>), async_sqlite::Error> {
+    let db_client: &async_sqlite::Client = &data.db_client;
+    let member_ids: Option<String> = get_guild_member_ids(ctx, data);
+
+    let total: u64 = count_users_in_leaderboard_from_eco_db(db_client, member_ids.clone()).await?;
+    let last_page: u64 = total.saturating_sub(1) / LEADERBOARD_PAGE_SIZE;
+
+    let placement: Option<u64> = match lbtype {
+        LeaderboardType::Level => get_user_placement_in_level_leaderboard(db_client, owner_id, member_ids.clone()).await?,
+        LeaderboardType::Balance => get_user_placement_in_balance_leaderboard(db_client, owner_id, member_ids.clone()).await?
+    };
+
+    let page: u64 = match page {
+        Some(page) => page.min(last_page),
+        None => placement.map(|placement| (placement - 1) / LEADERBOARD_PAGE_SIZE).unwrap_or(0)
+    };
+    let offset: u64 = page * LEADERBOARD_PAGE_SIZE;
+
+    let mut response: String = String::new();
+    let title: &str;
+    match lbtype {
+        LeaderboardType::Level => {
+            title = "<a:qtstar:1367089440073318501> Level Leaderboard";
+            let lb: Vec<(u64, u64, u64)> = build_level_leaderboard_from_eco_db(db_client, member_ids, LEADERBOARD_PAGE_SIZE, offset).await?;
+            for (index, (discord_id, level, experience)) in lb.iter().enumerate() {
+                let position: u64 = offset + index as u64 + 1;
+                let experience_needed: u64 = exp_needed_to_next_level(*level);
+                let progressbar: String = generate_emoji_progressbar(*experience, experience_needed, data.config.get().leveling.progress_bar_in_leaderboard_size, &data.config.get().progressbar_emojis);
+
+                response.push_str(&format!("{}**{}.** <@{}> •\n<:LoopchanLevel:1368298876842279072> Level: {}\n<:LoopchanExp:1368298874803982479> Experience: {}/{}{}\n\n", placement_emoji(position), position, discord_id, level, experience, experience_needed, progressbar));
+            }
+        },
+        LeaderboardType::Balance => {
+            title = "<a:qtstar:1367089440073318501> Balance Leaderboard";
+            let lb: Vec<(u64, u64)> = build_balance_leaderboard_from_eco_db(db_client, member_ids, LEADERBOARD_PAGE_SIZE, offset).await?;
+            for (index, (discord_id, balance)) in lb.iter().enumerate() {
+                let position: u64 = offset + index as u64 + 1;
+                response.push_str(&format!("{}**{}.** <@{}> •\n<:LoopchanCoin:1368311103238570025> Balance: {}\n\n", placement_emoji(position), position, discord_id, balance));
+            }
+        }
+    }
+
+    if response.is_empty() {
+        response.push_str("Nobody is on this leaderboard yet.\n");
+    }
+
+    match placement {
+        Some(placement) => response.push_str(&format!("-# Your placement is #{}. <a:haphap:1367093618967318618>", placement)),
+        None => response.push_str("-# You are not on this leaderboard yet.")
+    }
+
+    let embed: CreateEmbed = CreateEmbed::default()
+        .title(title)
+        .description(response)
+        .footer(CreateEmbedFooter::new(format!("{} members", total)))
+        .color(Color::from_rgb(255, 255, 255));
+
+    // Custom IDs have to be unique within message (even for disabled buttons), and target pages can be the same,
+    // so every button has its own action in ID
+    let type_id: &str = leaderboard_type_id(lbtype);
+    let components: Vec<CreateActionRow> = vec![
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("eco.leaderboard.{}.first.0.{}", type_id, owner_id))
+                .label("⏮")
+                .style(ButtonStyle::Secondary)
+                .disabled(page == 0),
+            CreateButton::new(format!("eco.leaderboard.{}.prev.{}.{}", type_id, page.saturating_sub(1), owner_id))
+                .label("◀")
+                .style(ButtonStyle::Secondary)
+                .disabled(page == 0),
+            CreateButton::new(format!("eco.leaderboard.{}.page.{}", type_id, owner_id))
+                .label(format!("{}/{}", page + 1, last_page + 1))
+                .style(ButtonStyle::Secondary)
+                .disabled(true),
+            CreateButton::new(format!("eco.leaderboard.{}.next.{}.{}", type_id, (page + 1).min(last_page), owner_id))
+                .label("▶")
+                .style(ButtonStyle::Secondary)
+                .disabled(page >= last_page),
+            CreateButton::new(format!("eco.leaderboard.{}.last.{}.{}", type_id, last_page, owner_id))
+                .label("⏭")
+                .style(ButtonStyle::Secondary)
+                .disabled(page >= last_page),
+        ]),
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("eco.leaderboard.{}.me.{}", type_id, owner_id))
+                .label("📍 My rank")
+                .style(ButtonStyle::Primary)
+                .disabled(placement.is_none()),
+        ])
+    ];
+
+    Ok((embed, components))
+}
+
+pub async fn handle_interaction(
+    ctx: &serenity::prelude::Context,
+    interaction: ComponentInteraction,
+    data: &crate::Data
+) {
+    // eco.leaderboard.<type>.<first|prev|next|last>.<page>.<owner> or eco.leaderboard.<type>.me.<owner>
+    let interaction_id: &String = &interaction.data.custom_id;
+    let parts: Vec<&str> = interaction_id.split('.').collect();
+    let expected_parts: usize = if parts.get(3) == Some(&"me") { 5 } else { 6 };
+    if parts.len() != expected_parts {
+        return;
+    }
+
+    let lbtype: LeaderboardType = match parts[2] {
+        "level" => LeaderboardType::Level,
+        "balance" => LeaderboardType::Balance,
+        _ => return
+    };
+    let page: Option<u64> = match parts[3] {
+        "me" => None,
+        "first" | "prev" | "next" | "last" => match parts[4].parse() {
+            Ok(page) => Some(page),
+            Err(_) => return
+        },
+        _ => return
+    };
+    let owner_id: Option<u64> = parts[parts.len() - 1].parse().ok();
+    let user_id: u64 = interaction.user.id.get();
+
+    let leaderboard = build_leaderboard_page(ctx, data, &lbtype, page, user_id).await;
+    if leaderboard.is_err() {
+        error!("Failed to build {} leaderboard for {}: {}", parts[2], user_id, leaderboard.unwrap_err().to_string());
+
+        interaction.create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::default()
+                    .embed(
+                        CreateEmbed::default()
+                            .description("❌ Failed to load leaderboard! Please try again later, if the issue persists contact <@908779319084589067>")
+                            .color(Color::from_rgb(255, 100, 100))
+                    )
+                    .ephemeral(true)
+            )
+        ).await.unwrap();
+        return;
+    }
+    let (embed, components) = leaderboard.unwrap();
+
+    // Someone else's leaderboard is left as is, clicker gets their own copy instead
+    let message: CreateInteractionResponseMessage = CreateInteractionResponseMessage::default()
+        .embed(embed)
+        .components(components);
+    let response: CreateInteractionResponse = if owner_id == Some(user_id) {
+        CreateInteractionResponse::UpdateMessage(message)
+    } else {
+        CreateInteractionResponse::Message(message.ephemeral(true))
+    };
+
+    interaction.create_response(ctx, response).await.unwrap();
+}
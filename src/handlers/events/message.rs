@@ -1,39 +1,32 @@
 use std::time::Duration;
-use chrono::Datelike;
 
 use ::serenity::all::Message;
 
 use tokio::time::Instant;
 use tracing::info;
 
+use crate::utils::basic::weekday_exp_multiplier;
+use crate::utils::maintenance::maintenance_block_message;
+
 pub async fn give_exp_for_message(
+    ctx: &serenity::prelude::Context,
     message: &Message,
     data: &crate::Data
 ) {
     let userid: u64 = message.author.id.get();
-    let loopchans_config = &data.config;
 
-    if loopchans_config.maintenance && userid != loopchans_config.owner {
+    if message.author.bot { return; }
+    if maintenance_block_message(ctx, data, message.author.id).await.is_some() {
         return; // Loopchan is currently closed for maintenance. Please check back later, or wait for announcement.
     }
-
-    if message.author.bot { return; }
     if message.content.len() < 2 { return; }
     let cooldown_duration: Duration = Duration::from_secs(10);
     let mut cooldowns = data.exp_cooldowns.lock().await;
     let last_exp_time = cooldowns.entry(userid).or_insert((Instant::now() - cooldown_duration).into());
 
     if last_exp_time.elapsed() >= cooldown_duration {
-        let leveling_config = &data.config.leveling;
-        let mut weekday_multiplier: u64 = 1;
-        if leveling_config.double_multiplier_on_weekdays {
-            let weekday: chrono::Weekday = chrono::offset::Local::now().date_naive().weekday();
-            if weekday == chrono::Weekday::Sat {
-                weekday_multiplier = 2
-            } else if weekday == chrono::Weekday::Sun {
-                weekday_multiplier = 2
-            }
-        }
+        let leveling_config = &data.config.get().leveling;
+        let weekday_multiplier: u64 = weekday_exp_multiplier(leveling_config);
         let exp_amount: u64 = message.content.len().min(leveling_config.max_exp_per_message as usize) as u64*leveling_config.exp_multiplier*weekday_multiplier;
         crate::commands::eco::give_user_eco_exp(data, &message.author, exp_amount).await;
         *last_exp_time = Instant::now().into();
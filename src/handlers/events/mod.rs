@@ -4,4 +4,7 @@ pub mod verification;
 pub mod roleshop;
 pub mod inventory_paginator;
 pub mod fishing_minigame;
-pub mod blacklist;
\ No newline at end of file
+pub mod blacklist;
+pub mod blackjack;
+pub mod leaderboard;
+pub mod goodbyecard;
\ No newline at end of file
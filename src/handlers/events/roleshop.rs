@@ -1,7 +1,29 @@
-use serenity::all::{ButtonStyle, Color, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, Guild, RoleId};
+use serenity::all::{ButtonStyle, Color, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, Guild, GuildId, HttpError, Member, RoleId};
 use tracing::{error, warn};
 
-use crate::{utils::database::economy::{decrement_user_balance_in_eco_db, get_roleshopitem_by_id, get_user_balance_in_eco_db}, RoleShopItem};
+use crate::{utils::database::{economy::{get_user_balance_in_eco_db, get_user_level_in_eco_db}, ledger::LedgerReason, roleshop::{count_role_owners_in_roleshop_db, get_expired_role_purchases_in_roleshop_db, get_shop_item_in_roleshop_db, get_user_role_purchases_in_roleshop_db, purchase_role_in_roleshop_db, remove_role_purchase_in_roleshop_db, remove_role_purchase_with_refund_in_roleshop_db, RolePurchaseResult}}, DataRolePurchase, RoleShopItem};
+
+// Discord JSON error codes
+const UNKNOWN_MEMBER: isize = 10007;
+const UNKNOWN_ROLE: isize = 10011;
+
+pub fn format_roleshop_item_details(shop_item: &RoleShopItem) -> String {
+    let mut details: String = String::new();
+    if shop_item.duration.is_some() {
+        details.push_str(&format!("\nDuration: **{} hour(s)**", shop_item.duration.unwrap()));
+    }
+    if shop_item.min_level.is_some() {
+        details.push_str(&format!("\nRequired level: **{}**", shop_item.min_level.unwrap()));
+    }
+    if shop_item.sell_back.is_some() {
+        details.push_str(&format!("\nSells back for: **${}**", sell_back_price(shop_item)));
+    }
+    details
+}
+
+pub fn sell_back_price(shop_item: &RoleShopItem) -> u64 {
+    (shop_item.price as f64 * shop_item.sell_back.unwrap_or(0.0) as f64).floor() as u64
+}
 
 pub async fn handle_roleshop_selector(
     ctx: &serenity::prelude::Context,
@@ -21,13 +43,19 @@ pub async fn handle_roleshop_selector(
     let pressed_button_role_id_str: String = selector_option_id.clone().split_off(9);
     let pressed_button_role_id: u64 = pressed_button_role_id_str.parse().unwrap();
 
-    let shop_item: Result<&RoleShopItem, std::io::Error> = get_roleshopitem_by_id(pressed_button_role_id, &data.config.economy.shop_items).await;
+    let shop_item: Result<Option<RoleShopItem>, async_sqlite::Error> = get_shop_item_in_roleshop_db(&data.db_client, pressed_button_role_id).await;
 
     if shop_item.is_err() {
+        error!("Failed to get shop item with ID {}: {}", pressed_button_role_id, shop_item.unwrap_err().to_string());
+        return;
+    }
+    let shop_item: Option<RoleShopItem> = shop_item.unwrap();
+
+    if shop_item.is_none() {
         warn!("Not found shop item with ID: {}", selector_option_id);
         return;
     }
-    let shop_item: &RoleShopItem = shop_item.unwrap();
+    let shop_item: &RoleShopItem = &shop_item.unwrap();
 
     let author_id: u64 = interaction.user.id.get();
 
@@ -40,6 +68,16 @@ pub async fn handle_roleshop_selector(
                 .label("Buy")
                 .style(ButtonStyle::Success),
     ])];
+
+    let mut details: String = format_roleshop_item_details(shop_item);
+    if shop_item.stock.is_some() {
+        let owners: Result<u64, async_sqlite::Error> = count_role_owners_in_roleshop_db(db_client, shop_item.id).await;
+        if owners.is_ok() {
+            details.push_str(&format!("\nIn stock: **{}/{}**", shop_item.stock.unwrap().saturating_sub(owners.unwrap()), shop_item.stock.unwrap()));
+        } else {
+            error!("Failed to count owners of {} role: {}", shop_item.id, owners.unwrap_err().to_string());
+        }
+    }
     
     if !balance_check.is_ok() {
         error!("Failed to check {}'s balance: {}", author_id, balance_check.unwrap_err().to_string());
@@ -50,7 +88,7 @@ pub async fn handle_roleshop_selector(
                     .embed(
                         CreateEmbed::default()
                             .title(format!("Role :: <:{}:{}> {}", shop_item.icon_name, shop_item.icon_id, shop_item.display_name))
-                            .description(format!("*{}*\n\nActual Role: **<@&{}>**\nPrice: **${}**\n-# also failed to check your balance <:LoopchanOopsie:1376849367880826941>", shop_item.description, shop_item.id, shop_item.price))
+                            .description(format!("*{}*\n\nActual Role: **<@&{}>**\nPrice: **${}**{}\n-# also failed to check your balance <:LoopchanOopsie:1376849367880826941>", shop_item.description, shop_item.id, shop_item.price, details))
                             .color(Color::from_rgb(255, 255, 255))
                     )
                     .components(components)
@@ -70,7 +108,7 @@ pub async fn handle_roleshop_selector(
                 .embed(
                     CreateEmbed::default()
                         .title(format!("Role :: <:{}:{}> {}", shop_item.icon_name, shop_item.icon_id, shop_item.display_name))
-                        .description(format!("*{}*\n\nActual Role: **<@&{}>**\nPrice: **${}**\n\n*Your balance: **${}***", shop_item.description, shop_item.id, shop_item.price, balance))
+                        .description(format!("*{}*\n\nActual Role: **<@&{}>**\nPrice: **${}**{}\n\n*Your balance: **${}***", shop_item.description, shop_item.id, shop_item.price, details, balance))
                         .color(Color::from_rgb(255, 255, 255))
                 )
                 .components(components)
@@ -87,7 +125,7 @@ pub async fn handle_roleshop_buy(
     let role_id_str: String = interaction.data.custom_id.clone().split_off(13);
     let role_id: u64 = role_id_str.parse().unwrap();
 
-    let member = Guild::get(ctx, data.config.guild).await.unwrap().member(ctx, interaction.user.id).await.unwrap();
+    let member = Guild::get(ctx, data.config.get().guild).await.unwrap().member(ctx, interaction.user.id).await.unwrap();
     if member.roles.contains(&RoleId::new(role_id)) {
         interaction.create_response(
             ctx,
@@ -105,27 +143,31 @@ pub async fn handle_roleshop_buy(
         return;
     }
 
-    let shop_items: &Vec<RoleShopItem> = &data.config.economy.shop_items;
-    let shop_item: Result<&RoleShopItem, std::io::Error> = get_roleshopitem_by_id(role_id, shop_items).await;
+    let shop_item: Result<Option<RoleShopItem>, async_sqlite::Error> = get_shop_item_in_roleshop_db(&data.db_client, role_id).await;
 
     if shop_item.is_err() {
+        error!("Failed to get shop item with ID {}: {}", role_id, shop_item.unwrap_err().to_string());
+        return;
+    }
+    let shop_item: Option<RoleShopItem> = shop_item.unwrap();
+
+    if shop_item.is_none() {
         warn!("Not found shop item with ID: {}", role_id);
         return;
     }
-    let shop_item: &RoleShopItem = shop_item.unwrap();
+    let shop_item: &RoleShopItem = &shop_item.unwrap();
 
-    let db_client = &data.db_client;
     let author_id: u64 = interaction.user.id.get();
 
-    let balance_check: Result<u64, async_sqlite::Error> = get_user_balance_in_eco_db(db_client, author_id).await;
-    if !balance_check.is_ok() {
+    // Only one purchase per user at a time, so double clicking can't buy the same role twice
+    if !data.roleshop_purchases.lock().await.insert(author_id) {
         interaction.create_response(
             ctx,
             CreateInteractionResponse::UpdateMessage(
                 CreateInteractionResponseMessage::default()
                     .embed(
                         CreateEmbed::default()
-                            .description("❌ Failed to check your balance. Please try again later.")
+                            .description("❌ You're already buying something. Please wait.")
                             .color(Color::from_rgb(255, 100, 100))
                     )
                     .components(vec![])
@@ -135,9 +177,72 @@ pub async fn handle_roleshop_buy(
         return;
     }
 
-    let balance: u64 = balance_check.unwrap();
+    buy_roleshop_item(ctx, &interaction, data, &member, shop_item).await;
+
+    data.roleshop_purchases.lock().await.remove(&author_id);
+}
+
+async fn buy_roleshop_item(
+    ctx: &serenity::prelude::Context,
+    interaction: &ComponentInteraction,
+    data: &crate::Data,
+    member: &Member,
+    shop_item: &RoleShopItem
+) {
+    let db_client = &data.db_client;
+    let author_id: u64 = interaction.user.id.get();
+
+    if shop_item.min_level.is_some() {
+        let level: Result<u64, async_sqlite::Error> = get_user_level_in_eco_db(db_client, author_id).await;
+        if level.is_err() {
+            error!("Failed to check {}'s level: {}", author_id, level.unwrap_err().to_string());
+            interaction.create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::default()
+                        .embed(
+                            CreateEmbed::default()
+                                .description("❌ Failed to check your level. Please try again later.")
+                                .color(Color::from_rgb(255, 100, 100))
+                        )
+                        .components(vec![])
+                        .ephemeral(true)
+                )
+            ).await.unwrap();
+            return;
+        }
+
+        if level.unwrap() < shop_item.min_level.unwrap() {
+            interaction.create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::default()
+                        .embed(
+                            CreateEmbed::default()
+                                .description(format!("❌ You need to be at least level **{}** to buy this role.", shop_item.min_level.unwrap()))
+                                .color(Color::from_rgb(255, 100, 100))
+                        )
+                        .components(vec![])
+                        .ephemeral(true)
+                )
+            ).await.unwrap();
+            return;
+        }
+    }
+
+    // Stock, balance and purchase are checked and saved in one transaction, so concurrent purchases can't go below zero
+    let purchase: Result<RolePurchaseResult, async_sqlite::Error> = purchase_role_in_roleshop_db(
+        db_client,
+        author_id,
+        shop_item.id,
+        shop_item.price.into(),
+        shop_item.stock,
+        shop_item.duration.map(|hours| hours*3600)
+    ).await;
+
+    if purchase.is_err() {
+        error!("Failed to decrease {}'s balance: {}", author_id, purchase.unwrap_err().to_string());
 
-    if shop_item.price as u64 > balance {
         let components = vec![
             CreateActionRow::Buttons(vec![
                 CreateButton::new(format!("roleshop.buy.{}", shop_item.id))
@@ -151,27 +256,93 @@ pub async fn handle_roleshop_buy(
                 CreateInteractionResponseMessage::default()
                     .embed(
                         CreateEmbed::default()
-                            .description("❌ Insufficient Funds!")
+                            .description("❌ Failed to decrease your balance. Please try again later, if the issue persists contact <@908779319084589067>")
                             .color(Color::from_rgb(255, 100, 100))
                     )
                     .components(components)
                     .ephemeral(true)
             )
         ).await.unwrap();
+
         return;
     }
 
-    let successful_decrement = decrement_user_balance_in_eco_db(db_client, author_id, shop_item.price.into()).await;
+    let purchase: DataRolePurchase = match purchase.unwrap() {
+        RolePurchaseResult::Purchased(purchase) => purchase,
+        RolePurchaseResult::InsufficientFunds => {
+            let components = vec![
+                CreateActionRow::Buttons(vec![
+                    CreateButton::new(format!("roleshop.buy.{}", shop_item.id))
+                        .label("Retry")
+                        .style(ButtonStyle::Danger),
+            ])];
 
-    if successful_decrement.is_err() {
-        error!("Failed to decrease {}'s balance: {}", author_id, successful_decrement.unwrap_err().to_string());
+            interaction.create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::default()
+                        .embed(
+                            CreateEmbed::default()
+                                .description("❌ Insufficient Funds!")
+                                .color(Color::from_rgb(255, 100, 100))
+                        )
+                        .components(components)
+                        .ephemeral(true)
+                )
+            ).await.unwrap();
+            return;
+        }
+        RolePurchaseResult::OutOfStock => {
+            interaction.create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::default()
+                        .embed(
+                            CreateEmbed::default()
+                                .description("❌ This role is out of stock!")
+                                .color(Color::from_rgb(255, 100, 100))
+                        )
+                        .components(vec![])
+                        .ephemeral(true)
+                )
+            ).await.unwrap();
+            return;
+        }
+    };
 
-        let components = vec![
-            CreateActionRow::Buttons(vec![
-                CreateButton::new(format!("roleshop.buy.{}", shop_item.id))
-                    .label("Retry")
-                    .style(ButtonStyle::Danger),
-        ])];
+    let successfully_gave_shop_role: Result<(), serenity::Error> = member.add_role(ctx, RoleId::new(shop_item.id)).await;
+
+    if successfully_gave_shop_role.is_err() {
+        error!("Failed to give {} role to {}: {}", shop_item.id, author_id, successfully_gave_shop_role.unwrap_err().to_string());
+
+        // Compensate failed purchase
+        let refund: Result<Option<u64>, async_sqlite::Error> = remove_role_purchase_with_refund_in_roleshop_db(db_client, author_id, shop_item.id, purchase.price, LedgerReason::Shop, format!("refund:role:{}", shop_item.id)).await;
+
+        if refund.is_err() || refund.as_ref().unwrap().is_none() {
+            if refund.is_err() {
+                error!("Failed to refund ${} to {} after failed {} role purchase: {} | Please refund manually.", purchase.price, author_id, shop_item.id, refund.unwrap_err().to_string());
+            } else {
+                error!("Failed to refund ${} to {} after failed {} role purchase: purchase not found | Please refund manually.", purchase.price, author_id, shop_item.id);
+            }
+
+            interaction.create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::default()
+                        .embed(
+                            CreateEmbed::default()
+                                .description("❌ Failed to give you role and to refund your coins. Please contact <@908779319084589067> to receive your funds back.")
+                                .color(Color::from_rgb(255, 100, 100))
+                        )
+                        .components(vec![])
+                        .ephemeral(true)
+                )
+            ).await.unwrap();
+
+            return;
+        }
+
+        warn!("Refunded ${} to {} after failed {} role purchase", purchase.price, author_id, shop_item.id);
 
         interaction.create_response(
             ctx,
@@ -179,10 +350,10 @@ pub async fn handle_roleshop_buy(
                 CreateInteractionResponseMessage::default()
                     .embed(
                         CreateEmbed::default()
-                            .description("❌ Failed to decrease your balance. Please try again later, if the issue persists contact <@908779319084589067>")
+                            .description("❌ Failed to give you role. Your coins were refunded, please try again later.")
                             .color(Color::from_rgb(255, 100, 100))
                     )
-                    .components(components)
+                    .components(vec![])
                     .ephemeral(true)
             )
         ).await.unwrap();
@@ -190,43 +361,161 @@ pub async fn handle_roleshop_buy(
         return;
     }
 
-    let successfully_gave_shop_role: Result<(), serenity::Error> = member.add_role(ctx, RoleId::new(shop_item.id)).await;
+    let expiry_text: String = match purchase.expires_at {
+        Some(expires_at) => format!("\n-# Expires <t:{}:R>", expires_at),
+        None => String::new()
+    };
 
-    if successfully_gave_shop_role.is_err() {
-        error!("Failed to give {} role to {}: {}", shop_item.id, author_id, successful_decrement.unwrap_err().to_string());
-        
+    interaction.create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(
+                    CreateEmbed::default()
+                        .description(format!("✅ Successfully bought role!{}", expiry_text))
+                        .color(Color::from_rgb(100, 255, 100))
+                )
+                .components(vec![])
+                .ephemeral(true)
+        )
+    ).await.unwrap();
+}
+
+async fn respond_with_sell_error(ctx: &serenity::prelude::Context, interaction: &ComponentInteraction, description: &str) {
+    interaction.create_response(
+        ctx,
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::default()
+                .embed(
+                    CreateEmbed::default()
+                        .description(description)
+                        .color(Color::from_rgb(255, 100, 100))
+                )
+                .ephemeral(true)
+        )
+    ).await.unwrap();
+}
+
+pub async fn handle_roleshop_sell(
+    ctx: &serenity::prelude::Context,
+    interaction: ComponentInteraction,
+    data: &crate::Data
+) {
+    let role_id_str: String = interaction.data.custom_id.clone().split_off(14);
+    let role_id: u64 = role_id_str.parse().unwrap();
+    let author_id: u64 = interaction.user.id.get();
+
+    let shop_item: Result<Option<RoleShopItem>, async_sqlite::Error> = get_shop_item_in_roleshop_db(&data.db_client, role_id).await;
+    if shop_item.is_err() {
+        error!("Failed to get shop item with ID {}: {}", role_id, shop_item.as_ref().unwrap_err().to_string());
+    }
+    let shop_item: Option<RoleShopItem> = shop_item.unwrap_or(None);
+
+    if shop_item.is_none() || shop_item.as_ref().unwrap().sell_back.is_none() {
         interaction.create_response(
             ctx,
-            CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponse::Message(
                 CreateInteractionResponseMessage::default()
                     .embed(
                         CreateEmbed::default()
-                            .description("❌ Failed to give you role. Please contact <@908779319084589067> to receive your funds back.")
+                            .description("❌ This role can't be sold back.")
                             .color(Color::from_rgb(255, 100, 100))
                     )
-                    .components(vec![])
                     .ephemeral(true)
             )
         ).await.unwrap();
+        return;
+    }
+    let shop_item: &RoleShopItem = &shop_item.unwrap();
+    let sell_price: u64 = sell_back_price(shop_item);
+
+    let purchases: Result<Vec<DataRolePurchase>, async_sqlite::Error> = get_user_role_purchases_in_roleshop_db(&data.db_client, author_id).await;
+    if purchases.is_err() {
+        error!("Failed to get {}'s role purchases: {}", author_id, purchases.unwrap_err().to_string());
+        respond_with_sell_error(ctx, &interaction, "❌ Failed to sell role. Please try again later, if the issue persists contact <@908779319084589067>").await;
+        return;
+    }
+    if !purchases.unwrap().iter().any(|purchase| purchase.role_id == role_id) {
+        respond_with_sell_error(ctx, &interaction, "❌ You don't own this role.").await;
+        return;
+    }
+
+    // Role is taken first, so coins are only given back once member doesn't have it anymore
+    let guild_id: GuildId = data.config.get().guild.into();
+    let removed_role: Result<(), serenity::Error> = ctx.http.remove_member_role(guild_id, interaction.user.id, RoleId::new(role_id), Some("Sold back in role shop")).await;
+    if removed_role.is_err() {
+        error!("Failed to remove sold {} role from {}: {}", role_id, author_id, removed_role.unwrap_err().to_string());
+        respond_with_sell_error(ctx, &interaction, "❌ Failed to take role from you, it wasn't sold. Please try again later, if the issue persists contact <@908779319084589067>").await;
+        return;
+    }
+
+    let sold: Result<Option<u64>, async_sqlite::Error> = remove_role_purchase_with_refund_in_roleshop_db(&data.db_client, author_id, role_id, sell_price, LedgerReason::Sale, format!("role:{}", role_id)).await;
+
+    if sold.is_err() {
+        error!("Failed to sell {}'s {} role: {}", author_id, role_id, sold.unwrap_err().to_string());
+
+        // Compensate failed sale
+        let restored_role: Result<(), serenity::Error> = ctx.http.add_member_role(guild_id, interaction.user.id, RoleId::new(role_id), Some("Role shop sale failed")).await;
+        if restored_role.is_err() {
+            error!("Failed to give {} role back to {} after failed sale: {} | Please give it back manually.", role_id, author_id, restored_role.unwrap_err().to_string());
+        } else {
+            warn!("Gave {} role back to {} after failed sale", role_id, author_id);
+        }
 
+        respond_with_sell_error(ctx, &interaction, "❌ Failed to sell role. Please try again later, if the issue persists contact <@908779319084589067>").await;
+        return;
+    }
+
+    // Already sold by another click at the same time, which also gave coins back
+    if sold.unwrap().is_none() {
+        respond_with_sell_error(ctx, &interaction, "❌ You don't own this role.").await;
         return;
     }
 
     interaction.create_response(
         ctx,
-        CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponse::Message(
             CreateInteractionResponseMessage::default()
                 .embed(
                     CreateEmbed::default()
-                        .description("✅ Successfully bought role!")
+                        .description(format!("✅ Sold **{}** back for **${}**!", shop_item.display_name, sell_price))
                         .color(Color::from_rgb(100, 255, 100))
                 )
-                .components(vec![])
                 .ephemeral(true)
         )
     ).await.unwrap();
 }
 
+pub async fn remove_expired_roles(
+    ctx: &serenity::prelude::Context,
+    db_client: &async_sqlite::Client,
+    guild_id: u64
+) {
+    let expired: Result<Vec<DataRolePurchase>, async_sqlite::Error> = get_expired_role_purchases_in_roleshop_db(db_client).await;
+    if expired.is_err() {
+        error!("Failed to get expired role purchases: {}", expired.unwrap_err().to_string());
+        return;
+    }
+
+    for purchase in expired.unwrap() {
+        let removed_role: Result<(), serenity::Error> = ctx.http.remove_member_role(guild_id.into(), purchase.discord_id.into(), purchase.role_id.into(), Some("Rental role expired")).await;
+        if removed_role.is_err() {
+            let removed_role_err: serenity::Error = removed_role.unwrap_err();
+            // Member left the server or role was deleted, nothing left to remove. Anything else is retried on next check
+            let gone: bool = matches!(&removed_role_err, serenity::Error::Http(HttpError::UnsuccessfulRequest(response)) if response.error.code == UNKNOWN_MEMBER || response.error.code == UNKNOWN_ROLE);
+            warn!("Failed to remove expired {} role from {}: {}", purchase.role_id, purchase.discord_id, removed_role_err.to_string());
+            if !gone {
+                continue;
+            }
+        }
+
+        let removed_purchase: Result<usize, async_sqlite::Error> = remove_role_purchase_in_roleshop_db(db_client, purchase.discord_id, purchase.role_id).await;
+        if removed_purchase.is_err() {
+            error!("Failed to remove expired {} role purchase of {}: {}", purchase.role_id, purchase.discord_id, removed_purchase.unwrap_err().to_string());
+        }
+    }
+}
+
 pub async fn handle_interaction(
     ctx: &serenity::prelude::Context,
     interaction: ComponentInteraction,
@@ -239,4 +528,7 @@ pub async fn handle_interaction(
     if interaction_id.starts_with("roleshop.buy.") {
         return handle_roleshop_buy(ctx, interaction, data).await;
     }
+    if interaction_id.starts_with("roleshop.sell.") {
+        return handle_roleshop_sell(ctx, interaction, data).await;
+    }
 }
\ No newline at end of file
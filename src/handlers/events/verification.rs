@@ -203,8 +203,8 @@ pub async fn handle_interaction(
 
     // TODO: Also update roles depending on data in game
 
-    let member = Guild::get(ctx, data.config.guild).await.unwrap().member(ctx, interaction.user.id).await.unwrap();
-    let successfully_gave_member_role: Result<(), serenity::Error> = member.add_role(ctx, RoleId::new(data.config.roles.member)).await;
+    let member = Guild::get(ctx, data.config.get().guild).await.unwrap().member(ctx, interaction.user.id).await.unwrap();
+    let successfully_gave_member_role: Result<(), serenity::Error> = member.add_role(ctx, RoleId::new(data.config.get().roles.member)).await;
     if !successfully_gave_member_role.is_ok() {
         error!("{}", &successfully_gave_member_role.err().unwrap().to_string());
         interaction.edit_response(
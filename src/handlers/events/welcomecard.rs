@@ -1,29 +1,160 @@
-use tokio::io::AsyncWriteExt;
+use std::io::Cursor;
 
-use ab_glyph::{FontRef, PxScale, VariableFont};
-
-use ::serenity::all::{CreateAttachment, ChannelId, Color, CreateEmbed, CreateMessage, Member};
+use ::serenity::all::{CreateAttachment, ChannelId, Color, CreateEmbed, CreateMessage, Member, ReactionType, User};
 
 use poise::serenity_prelude as serenity;
 
 use tracing::error;
 
-use image::{DynamicImage, Rgb, Rgba};
-use image::imageops::{overlay, resize, FilterType};
-use imageproc::drawing::draw_text_mut;
-use std::path::Path;
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, Frame};
 
 use crate::Error;
+use crate::utils::database::settings::get_setting_in_settings_db;
+use crate::utils::render::{default_avatar, download_image, load_layout, render_layout, render_layout_animated, Layout, LayoutValues};
+
+const WELCOMECARD_LAYOUT: &str = "welcomecard.toml";
+const ANIMATED_SCALE: f32 = 0.5; // Animated cards are rendered at half size to stay under Discord's upload limit
+const ANIMATED_MAX_FRAMES: usize = 30; // Each frame adds ~150KB
+const DEFAULT_WELCOME_MESSAGE: &str = "Welcome {mention}! Hope you'll enjoy your stay.";
+pub const UPLOADED_TEMPLATE_PATH: &str = "welcomecardtemplate_uploaded.png";
+
+/// Welcome card settings after `/adm welcomecard set` overrides are applied over Config.toml
+pub struct WelcomecardSettings {
+    pub channel: Option<u64>,
+    pub reaction: Option<ReactionType>, // `None` if welcome message shouldn't be reacted to
+    pub message: String,
+    pub template: Option<String> // Uploaded template, layout's template is used if not set
+}
+
+async fn get_welcomecard_setting(data: &crate::Data, key: &str) -> Option<String> {
+    let setting: Result<Option<String>, async_sqlite::Error> = get_setting_in_settings_db(&data.db_client, &format!("welcomecard.{}", key)).await;
+    if setting.is_err() {
+        error!("Failed to get welcomecard.{} setting, using Config.toml instead: {}", key, setting.unwrap_err().to_string());
+        return None;
+    }
+    setting.unwrap()
+}
+
+pub async fn get_welcomecard_settings(data: &crate::Data) -> WelcomecardSettings {
+    let welcome_config = &data.config.get().welcomecard;
+
+    let channel: Option<u64> = match get_welcomecard_setting(data, "channel").await {
+        Some(channel) => channel.parse().ok(),
+        None => welcome_config.channel
+    };
+
+    // Stored as `<:name:id>` or unicode emoji, `none` disables reaction
+    let reaction: Option<ReactionType> = match get_welcomecard_setting(data, "reaction").await {
+        Some(reaction) if reaction == "none" => None,
+        Some(reaction) => ReactionType::try_from(reaction).ok(),
+        None if welcome_config.react.unwrap_or(false) && welcome_config.react_id.is_some() => Some(ReactionType::Custom {
+            animated: welcome_config.react_animated.unwrap_or(false),
+            id: welcome_config.react_id.unwrap().into(),
+            name: welcome_config.react_name.clone()
+        }),
+        None => None
+    };
+
+    let message: String = get_welcomecard_setting(data, "message").await
+        .or(welcome_config.message.clone())
+        .unwrap_or(DEFAULT_WELCOME_MESSAGE.to_string());
+
+    WelcomecardSettings {
+        channel,
+        reaction,
+        message,
+        template: get_welcomecard_setting(data, "template").await
+    }
+}
+
+pub fn format_welcome_message(message: &str, user: &User, member_count: u64) -> String {
+    message
+        .replace("{mention}", &format!("<@{}>", user.id.get()))
+        .replace("{username}", &user.name)
+        .replace("{member_count}", &member_count.to_string())
+}
+
+/// Decodes avatar straight from memory, falls back to default avatar if it can't be downloaded or decoded
+pub async fn fetch_avatar(user: &User) -> DynamicImage {
+    let avatar: Option<Vec<u8>> = download_image(&user.static_face()).await;
+    if avatar.is_none() {
+        return default_avatar();
+    }
+
+    let decoded: Result<DynamicImage, image::ImageError> = image::load_from_memory(&avatar.unwrap());
+    if decoded.is_err() {
+        error!("Failed to decode {}'s avatar: {}", user.name, decoded.unwrap_err().to_string());
+        return default_avatar();
+    }
+    decoded.unwrap()
+}
+
+/// Frames of member's animated avatar, `None` if it isn't animated or couldn't be decoded
+async fn fetch_animated_avatar(user: &User) -> Option<Vec<Frame>> {
+    if !user.avatar.is_some_and(|avatar| avatar.is_animated()) {
+        return None;
+    }
+
+    let avatar: Vec<u8> = download_image(&user.avatar_url()?).await?;
+    let frames: Result<Vec<Frame>, image::ImageError> = GifDecoder::new(Cursor::new(avatar)).and_then(|decoder| decoder.into_frames().collect_frames());
+    match frames {
+        Ok(frames) => Some(frames),
+        Err(err) => {
+            error!("Failed to decode {}'s animated avatar: {}", user.name, err.to_string());
+            None
+        }
+    }
+}
+
+/// Renders welcome card, returns image and its file extension (`gif` if card is animated).
+/// `template` replaces layout's template image.
+pub async fn render_welcomecard(user: &User, member_count: u64, animated: bool, template: Option<&String>) -> Result<(Vec<u8>, &'static str), Error> {
+    let layout: Layout = load_layout(WELCOMECARD_LAYOUT)?;
+    let mut values: LayoutValues = LayoutValues::default()
+        .text("username", user.name.clone())
+        .text("display_name", user.display_name())
+        .text("member_count", member_count.to_string());
+
+    if template.is_some() {
+        let template_image: Result<DynamicImage, image::ImageError> = image::open(template.unwrap());
+        if template_image.is_ok() {
+            values = values.background(template_image.unwrap());
+        } else {
+            error!("Failed to open uploaded welcome card template {}, using layout's template instead: {}", template.unwrap(), template_image.unwrap_err().to_string());
+        }
+    }
+
+    if animated {
+        let frames: Option<Vec<Frame>> = fetch_animated_avatar(user).await;
+        if frames.is_some() {
+            let frames: Vec<Frame> = frames.unwrap();
+            // Encoding GIF is slow, so it's kept off async runtime
+            let rendered: Result<Vec<u8>, image::ImageError> = tokio::task::spawn_blocking(move || {
+                render_layout_animated(&layout, &values, "avatar", frames, ANIMATED_SCALE, ANIMATED_MAX_FRAMES)
+            }).await?;
+            return Ok((rendered?, "gif"));
+        }
+    }
+
+    let values: LayoutValues = values.image("avatar", fetch_avatar(user).await);
+    Ok((render_layout(&layout, &values)?, "png"))
+}
 
 pub async fn welcomecard(
     ctx: &serenity::Context,
     new_member: &Member,
     data: &crate::Data
 ) -> Result<(), Error> {
-    let loopchans_config = &data.config;
+    let loopchans_config = data.config.get();
     if !loopchans_config.welcomecard.enabled { return Ok(()) }
+    let settings: WelcomecardSettings = get_welcomecard_settings(data).await;
+    if settings.channel.is_none() {
+        error!("Welcome card is enabled, but there's no welcomes channel set!");
+        return Ok(());
+    }
     let ptl_channels: std::collections::HashMap<ChannelId, serenity::model::prelude::GuildChannel> = ctx.cache.guild(loopchans_config.guild).unwrap().channels.clone();
-    let welcomes_channel = ptl_channels.get(&loopchans_config.welcomecard.channel.unwrap().into());
+    let welcomes_channel = ptl_channels.get(&settings.channel.unwrap().into());
     if welcomes_channel.is_none() {
         error!("Failed to find welcomes channel to welcome member in!");
         return Ok(());
@@ -31,61 +162,10 @@ pub async fn welcomecard(
 
     let member_count: u64 = ctx.cache.guild(loopchans_config.guild).unwrap().member_count;
 
-    let mut image: image::ImageBuffer<Rgb<u8>, Vec<u8>> = image::open(Path::new("welcomecardtemplate.png")).unwrap().into();
-
-    let mut font: FontRef<'_> = FontRef::try_from_slice(include_bytes!("../../../CascadiaCode.ttf")).unwrap();
-    font.set_variation(b"lght", 400.0);
-
-    let scale: PxScale = PxScale { x: 74.0, y: 74.0, };
-    let (member_username, member_userid) = (&new_member.user.name, &new_member.user.id.get());
-
-    draw_text_mut(&mut image, Rgb([255, 255, 255]), 79, 582, scale, &font, member_username);
-    draw_text_mut(&mut image, Rgb([255, 255, 255]), 79, 656, scale, &font, &format!("you’re member #{}!", member_count));
-    
-    // ^ If I make image initially Rgba draw_text_mut just breaks for some reason (even if I provide Rgba to color)
-    // So we convert it to Rgba buffer after adding text:
-    let mut image: image::ImageBuffer<Rgba<u8>, Vec<u8>> = DynamicImage::from(image).into();
-
-    let avatar_url = new_member.user.static_avatar_url();
-    if avatar_url.is_some() {
-        let avatar_url: String = avatar_url.unwrap();
-        let response: Result<roboat::reqwest::Response, roboat::reqwest::Error> = roboat::reqwest::get(&avatar_url).await;
-
-        if response.is_ok() { // nesting goesw hard
-            let response: roboat::reqwest::Response = response.unwrap();
-            let is_cool = response.status().is_success();
-            if is_cool {
-                // Download file into temp
-                // https://cdn.discordapp.com/avatars/exampleid/examplehash.png?size=1024 >>> examplehash.png?size=1024 >>> examplehash.png
-                let avatar_file_name = avatar_url.split("/").last().unwrap().split("?").next().unwrap();
-                let avatar_path = format!("temp/{}", avatar_file_name);
-                let mut file = tokio::fs::File::create(&avatar_path).await?;
-                file.write_all(&response.bytes().await.unwrap().to_vec().as_slice()).await?;
-
-                // Prepare and resize image
-                let pfp_image: image::ImageBuffer<Rgba<u8>, Vec<u8>> = image::open(avatar_path).unwrap().into();
-                let mut pfp_image: image::ImageBuffer<Rgba<u8>, Vec<u8>> = resize(&pfp_image, 256, 256, FilterType::Nearest);
-                
-                // Make pixels outside of circle radius (128) completely transparent :P
-                for x in 0..=255 {
-                    for y in 0..=255 {
-                        if f64::hypot((x as f64) - 128.0, (y as f64) - 128.0) > 129.0 {
-                            pfp_image.put_pixel(x, y, Rgba([0, 0, 0, 1]));
-                            pfp_image.put_pixel(y, x, Rgba([0, 0, 0, 1]));
-                        }
-                    }
-                }
-
-                // Overlay pfp ontop of welcome image
-                overlay(&mut image, &pfp_image, 1690, 95);
-            }
-        }
-    }
-
-    let mut bytes: Vec<u8> = Vec::new();
-    image.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+    let member_userid: u64 = new_member.user.id.get();
+    let (bytes, extension): (Vec<u8>, &str) = render_welcomecard(&new_member.user, member_count, loopchans_config.welcomecard.animated.unwrap_or(false), settings.template.as_ref()).await?;
 
-    let attachment: CreateAttachment = CreateAttachment::bytes(bytes, format!("welcomecard{}.gif", member_userid));
+    let attachment: CreateAttachment = CreateAttachment::bytes(bytes, format!("welcomecard{}.{}", member_userid, extension));
     let filename = attachment.filename.clone();
 
     let welcome_message: Result<serenity::model::prelude::Message, serenity::Error> = welcomes_channel.unwrap().send_message(ctx,
@@ -93,22 +173,17 @@ pub async fn welcomecard(
             .add_file(attachment)
             .embed(
                 CreateEmbed::default()
-                    .description(format!("Welcome <@{}>! Hope you'll enjoy your stay.", member_userid))
+                    .description(format_welcome_message(&settings.message, &new_member.user, member_count))
                     .attachment(filename)
                     .color(Color::from_rgb(255, 255, 255))
             )
     ).await;
 
     if welcome_message.is_ok() {
-        if !loopchans_config.welcomecard.react.unwrap() {
+        if settings.reaction.is_none() {
             return Ok(());
         }
-        let welcome_react: serenity::model::prelude::ReactionType = serenity::ReactionType::Custom {
-            animated: loopchans_config.welcomecard.react_animated.unwrap(),
-            id: loopchans_config.welcomecard.react_id.unwrap().into(),
-            name: loopchans_config.welcomecard.react_name.clone()
-        };
-        welcome_message.unwrap().react(ctx, welcome_react).await?;
+        welcome_message.unwrap().react(ctx, settings.reaction.unwrap()).await?;
     } else {
         error!("Failed to send {}'s welcome card: {}", &new_member.user.name, welcome_message.unwrap_err().to_string());
     }
@@ -13,15 +13,19 @@ use roboat;
 
 use ::serenity::prelude::TypeMapKey;
 
-use utils::database::{create_db, fishing::prepare_fishing_db, linking::prepare_users_db, economy::prepare_eco_db, lastfm::prepare_lastfm_db, linking::create_user_in_users_db, economy::create_user_in_eco_db};
+use utils::database::{create_db, cooldowns::{prepare_cooldowns_db, get_user_remaining_cooldown_in_cooldowns_db, start_user_cooldown_in_cooldowns_db, prune_expired_cooldowns_in_cooldowns_db}, fishing::{prepare_fishing_db, prepare_fish_catches_db}, linking::prepare_users_db, economy::{prepare_eco_db, prepare_transfers_db}, ledger::prepare_ledger_db, roleshop::{prepare_roleshop_db, prepare_shop_items_db, import_shop_items_in_roleshop_db}, inventory::{prepare_inventory_db, prepare_equipped_items_db}, gambling::{prepare_gambling_db, prepare_gambling_blocks_db, prepare_open_bets_db, refund_open_bets_in_gambling_db}, members::prepare_member_leaves_db, settings::{prepare_settings_db, set_setting_in_settings_db}, blacklist::{prepare_blacklist_db, prepare_blacklist_appeals_db, import_blacklist_in_blacklist_db}, moderation::prepare_mod_cases_db, audit::prepare_audit_log_db, maintenance::{prepare_maintenance_windows_db, prepare_maintenance_allowlist_db}, lastfm::prepare_lastfm_db, linking::create_user_in_users_db, economy::create_user_in_eco_db};
+
+use utils::gambling::BlackjackGame;
+use utils::config::{read_config, ConfigHandle};
+use utils::maintenance::{load_maintenance_state, maintenance_block_message, MaintenanceState};
+use utils::presence::PresenceState;
 
 use tokio::sync::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 
 // Config
 use serde::Deserialize;
-use toml;
 
 #[derive(Deserialize, Serialize)]
 pub struct LoopchanConfig {
@@ -31,18 +35,54 @@ pub struct LoopchanConfig {
     maintenance: bool,
     database_path: Option<String>,
     blacklist: Vec<u64>,
+    blacklist_appeal_cooldown: Option<u64>, // Days before user whose appeal was denied can appeal again
     welcomecard: WelcomecardConfig,
+    goodbyecard: GoodbyecardConfig,
     roles: LoopchansRoles,
     channels: LoopchansChannels,
     progressbar_emojis: ProgressBarEmojisTypes,
     leveling: LevelingConfig,
-    economy: EconomyConfig
+    economy: EconomyConfig,
+    presence: Option<PresenceConfig> // Default presence is shown if not set
 }
 
 impl TypeMapKey for LoopchanConfig {
     type Value = LoopchanConfig;
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct PresenceConfig {
+    interval: u64, // Seconds between rotation entries
+    status: PresenceStatus,
+    rotation: Vec<PresenceEntry>
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceStatus {
+    Online,
+    Idle,
+    Dnd,
+    Invisible
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceKind {
+    Playing,
+    Listening,
+    Watching,
+    Competing,
+    Custom // Only `name` is shown, as custom status
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct PresenceEntry {
+    kind: PresenceKind,
+    name: String, // {member_count}, {fish_caught} and {boost_event} are replaced, entries with {boost_event} are skipped while there's no event
+    state: Option<String> // Shown under `name`, e.g. "700 ELO | In Queue"
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct ProgressBarEmojisTypes {
     empty: ProgressBarEmojis,
@@ -65,7 +105,7 @@ pub struct LevelingConfig {
     progress_bar_in_leaderboard_size: u64
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct RoleShopItem {
     id: u64,
     display_name: String,
@@ -73,6 +113,61 @@ pub struct RoleShopItem {
     icon_name: String,
     description: String,
     price: u32,
+    duration: Option<u64>, // In hours, role is removed once it expires (permanent if not set)
+    min_level: Option<u64>,
+    stock: Option<u64>, // How many members can own this role at the same time
+    sell_back: Option<f32>, // Part of price returned when selling role back (can't be sold if not set)
+}
+
+#[derive(Debug)]
+pub struct DataRolePurchase {
+    discord_id: u64,
+    role_id: u64,
+    price: u64,
+    purchased_at: u64,
+    expires_at: Option<u64>
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Consumable, // Taken from inventory once used
+    Cosmetic, // Equipped into its slot and stays in inventory
+    Collectible // Can't be used
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ItemEffect {
+    Exp { amount: u64 },
+    CooldownSkip { action: String }, // `eco.work`, `fishing.fish`
+    FishingBait { fail_chance_multiplier: f32, uses: u64 },
+    Background { path: String },
+    Badge { emoji: String }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Item {
+    id: String, // Stored in database, don't change it once item was given to someone
+    display_name: String,
+    description: String,
+    emoji: String,
+    kind: ItemKind,
+    price: Option<u64>, // Can't be bought if not set
+    effect: Option<ItemEffect>
+}
+
+#[derive(Debug)]
+pub struct DataInventoryItem {
+    item_id: String,
+    quantity: u64
+}
+
+#[derive(Debug)]
+pub struct DataEquippedItem {
+    slot: String,
+    item_id: String,
+    remaining_uses: Option<u64> // Permanent if not set
 }
 
 #[derive(Debug)]
@@ -83,6 +178,75 @@ pub struct DataFish {
     size: f32
 }
 
+#[derive(Debug)]
+pub struct DataLedgerDailyTotal {
+    day: u64, // Days since unix epoch (UTC)
+    reason: String,
+    minted: u64,
+    burned: u64
+}
+
+#[derive(Debug)]
+pub struct DataLedgerEntry {
+    id: i64,
+    discord_id: u64,
+    amount: i64,
+    balance_after: u64,
+    reason: String,
+    reference: Option<String>,
+    created_at: u64
+}
+
+#[derive(Debug)]
+pub struct DataBlacklistEntry {
+    discord_id: u64,
+    reason: Option<String>,
+    moderator: Option<u64>, // `None` for entries imported from Config.toml
+    created_at: u64,
+    expires_at: Option<u64>
+}
+
+#[derive(Debug)]
+pub struct DataBlacklistAppeal {
+    id: i64,
+    discord_id: u64,
+    appeal: String,
+    created_at: u64,
+    accepted: Option<bool>, // `None` while it's pending
+    decided_at: Option<u64>,
+    moderator: Option<u64>
+}
+
+#[derive(Debug)]
+pub struct DataModCase {
+    id: i64,
+    moderator: u64,
+    action: String,
+    reason: Option<String>,
+    duration: Option<u64>, // Seconds
+    created_at: u64
+}
+
+#[derive(Debug)]
+pub struct DataAuditEntry {
+    id: i64,
+    actor: u64,
+    command: String,
+    arguments: String,
+    target: Option<u64>,
+    outcome: String,
+    created_at: u64
+}
+
+#[derive(Debug)]
+pub struct DataMaintenanceWindow {
+    id: i64,
+    starts_at: u64,
+    ends_at: u64,
+    message: Option<String>,
+    created_by: u64
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct FishModifier {
     name: String,
@@ -112,12 +276,20 @@ pub struct EconomyConfig {
     work_cooldown: u64,
     work_payment: Vec<u32>,
     shop_not_level_3_warn: bool,
-    shop_items: Vec<RoleShopItem>,
+    shop_items: Vec<RoleShopItem>, // Only imported into database on first start, use `/adm shop` afterwards
+    pay_min: u64,
+    pay_max: u64,
+    pay_fee: Option<f32>,
+    gamble_min_bet: u64,
+    gamble_max_bet: u64,
+    gamble_house_edge: f32, // Part of winnings kept by the house
+    gamble_daily_loss_cap: Option<u64>,
     fish_fail_chance: f32,
     fish_cooldown: u64,
     fish_cooldown_mg: u64,
     fishes: Vec<Fish>,
-    fishes_modifiers: Vec<FishModifier>
+    fishes_modifiers: Vec<FishModifier>,
+    items: Vec<Item>
 }
 
 #[derive(Deserialize, Serialize)]
@@ -128,19 +300,35 @@ pub struct WelcomecardConfig {
     react_id: Option<u64>,
     react_name: Option<String>,
     react_animated: Option<bool>,
+    animated: Option<bool>, // Send animated (GIF) card for members with animated avatars
+    message: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GoodbyecardConfig {
+    enabled: bool,
+    channel: Option<u64>,
+    card: Option<bool>,
+    message: Option<String>, // {mention}, {username} and {duration} are replaced
+    quick_leave: Option<u64>, // In minutes
+    quick_leave_alert: Option<u64>,
+    clear_state: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct LoopchansRoles {
     qa: u64,
-//    staff: u64, // commented out to avoid deadcode warning for now
+    staff: u64,
     member: u64,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct LoopchansChannels {
     qa_forms: u64,
-    unverified_chat: u64
+    unverified_chat: u64,
+    blacklist_appeals: Option<u64>, // Blacklist appeals are disabled if not set
+    mod_log: Option<u64>,
+    audit: Option<u64> // Privileged commands are still recorded in database if not set
 }
 
 // Logging
@@ -177,22 +365,18 @@ struct Data {
     exp_cooldowns: Mutex<HashMap<u64, Instant>>, // Used to cooldown economics exp add
     regenerations_cooldowns: Mutex<HashMap<u64, Instant>>, // Used to cooldown wordgen regeneration in `/rbx verify`
     verifications: Mutex<HashMap<u64, (String, u64)>>, // Used to transfer data (wordgen and roblox_id) from `/rbx verify` to `verification::handle_interaction(...)``
-    config: LoopchanConfig, // Used to read and index Config.toml
+    roleshop_purchases: Mutex<HashSet<u64>>, // Used to prevent user from buying multiple roles at the same time
+    blackjack_games: std::sync::Arc<Mutex<HashMap<u64, BlackjackGame>>>, // Used to keep unfinished `/eco blackjack` games between button presses (bets are also kept in database, see `open_bets`)
+    profile_cards: Mutex<HashMap<u64, (u64, Vec<u8>)>>, // Used to cache rendered `/eco profile` cards with fingerprint of data they were rendered from
+    config: ConfigHandle, // Used to read and index Config.toml (can be reloaded, see `utils::config`)
+    maintenance: std::sync::Arc<Mutex<MaintenanceState>>, // Used to check maintenance without database calls (see `utils::maintenance`)
+    presence: std::sync::Arc<Mutex<PresenceState>>, // Used to keep owner's custom status and rotation position (see `utils::presence`)
     log_file: String // Session .log file path
 }
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
-// Loopchan's Game Status
-use once_cell::sync::Lazy;
-static PTL_PAID_TESTING_PRESENCE: Lazy<serenity::ActivityData> = Lazy::new(|| serenity::ActivityData {
-    name: "PARKOUR: The Loop".to_string(),
-    kind: serenity::ActivityType::Playing,
-    state: Some("700 ELO | In Queue".to_string()),
-    url: None,
-});
-
 // Error Handler
 async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
     match error {
@@ -207,6 +391,7 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
         crate::FrameworkError::Command { ctx, error , .. } => {
             let error = error.to_string();
             error!("An error occured in a command: {}", error);
+            utils::audit::audit_command(ctx, format!("error: {}", error)).await;
 
             let mentions = CreateAllowedMentions::new()
                 .everyone(false)
@@ -235,6 +420,8 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
                 .await.expect("Failed to send error message");
         }
         crate::FrameworkError::CommandPanic { ctx, payload: _ , .. } => {
+            utils::audit::audit_command(ctx, String::from("panic")).await;
+
             // Not showing the payload to the user because it may contain sensitive info
             let embed = serenity::CreateEmbed::default()
                 .title("Internal error")
@@ -386,12 +573,13 @@ async fn handle_message_component_interaction(
     data: &Data,
     interaction: &ComponentInteraction
 ) -> Result<(), Error> {
-    let loopchans_config = &data.config;
+    let loopchans_config = data.config.get();
 
-    if loopchans_config.maintenance && interaction.user.id.get() != loopchans_config.owner {
+    let maintenance_message: Option<String> = maintenance_block_message(ctx, data, interaction.user.id).await;
+    if maintenance_message.is_some() {
         interaction.create_response(ctx, serenity::CreateInteractionResponse::Message(
             CreateInteractionResponseMessage::default()
-                .content("Loopchan is currently closed for maintenance. Please check back later, or wait for announcement.")
+                .content(maintenance_message.unwrap())
                 .ephemeral(true)
         )).await?;
         return Ok(());
@@ -462,6 +650,12 @@ async fn handle_message_component_interaction(
         crate::handlers::events::inventory_paginator::handle_interaction(ctx, interaction.clone(), data).await;
     } else if interaction.data.custom_id.starts_with("fishing.minigame.") {
         crate::handlers::events::fishing_minigame::handle_interaction(ctx, interaction.clone(), data).await;
+    } else if interaction.data.custom_id.starts_with("eco.blackjack.") {
+        crate::handlers::events::blackjack::handle_interaction(ctx, interaction.clone(), data).await;
+    } else if interaction.data.custom_id.starts_with("eco.leaderboard.") {
+        crate::handlers::events::leaderboard::handle_interaction(ctx, interaction.clone(), data).await;
+    } else if interaction.data.custom_id.starts_with("blacklist.appeal") {
+        crate::handlers::events::blacklist::handle_interaction(ctx, interaction.clone(), data).await;
     }
 
     Ok(())
@@ -476,6 +670,14 @@ async fn event_handler(
     match event {
         serenity::FullEvent::Ready { data_about_bot, .. } => { // Print bot's username on startup
             warn!("Logged in as {}", data_about_bot.user.name);
+            // Leaderboards need every member of PTL server in cache to filter out ones who left, blacklist sweep runs once it's done
+            ctx.shard.chunk_guild(data.config.get().guild.into(), None, false, serenity::ChunkGuildFilter::None, None);
+        }
+        serenity::FullEvent::GuildMembersChunk { chunk } => { // BLACKLIST SWEEP once every member is cached
+            if chunk.guild_id.get() != data.config.get().guild || chunk.chunk_index + 1 != chunk.chunk_count {
+                return Ok(());
+            }
+            handlers::events::blacklist::run_blacklist_sweep(ctx, &data.config.get(), &data.db_client).await;
         }
         serenity::FullEvent::InteractionCreate { interaction } => { // Different interactions handling
             // Message Component Interaction
@@ -483,7 +685,7 @@ async fn event_handler(
             if !is_component.is_none() { return handle_message_component_interaction(ctx, event, framework, data, &is_component.unwrap()).await; }
         }
         serenity::FullEvent::Message { new_message } => {
-            handlers::events::message::give_exp_for_message(new_message, data).await;
+            handlers::events::message::give_exp_for_message(ctx, new_message, data).await;
         }
         serenity::FullEvent::GuildMemberAddition { new_member } => { // WELCOMECARD // WELCOME MESSAGE
             let is_blacklisted_check = handlers::events::blacklist::blacklist_check(ctx, new_member, data).await;
@@ -497,6 +699,12 @@ async fn event_handler(
             }
             handlers::events::welcomecard::welcomecard(ctx, new_member, data).await?;
         }
+        serenity::FullEvent::GuildMemberRemoval { guild_id, user, member_data_if_available } => { // GOODBYECARD // LEAVE LOGGING
+            if guild_id.get() != data.config.get().guild {
+                return Ok(());
+            }
+            handlers::events::goodbyecard::goodbyecard(ctx, user, member_data_if_available.as_ref(), data).await?;
+        }
         _ => {}
     }
     Ok(())
@@ -533,19 +741,55 @@ async fn main() {
         .with(terminal_layer)
         .init();
 
-    let toml_string = tokio::fs::read_to_string("Config.toml").await;
-    if toml_string.is_err() {
-        error!("Failed to read your config.toml file: {}", toml_string.unwrap_err().to_string());
-        return;
-    }
-    let loopchans_config: LoopchanConfig = toml::from_str(&toml_string.unwrap()).unwrap();
+    let loopchans_config: ConfigHandle = match read_config().await {
+        Ok(loopchans_config) => ConfigHandle::new(loopchans_config),
+        Err(error) => {
+            error!("{}", error);
+            return;
+        }
+    };
+    let startup_config: std::sync::Arc<LoopchanConfig> = loopchans_config.get();
 
     // Loopchan's Database
-    let sqlite_client: async_sqlite::Client = create_db(loopchans_config.database_path).await.expect("Failed connecting to users database");
+    let sqlite_client: async_sqlite::Client = create_db(startup_config.database_path.clone()).await.expect("Failed connecting to users database");
     prepare_users_db(&sqlite_client).await;
     prepare_eco_db(&sqlite_client).await;
+    prepare_transfers_db(&sqlite_client).await;
+    prepare_ledger_db(&sqlite_client).await;
+    prepare_roleshop_db(&sqlite_client).await;
+    prepare_shop_items_db(&sqlite_client).await;
+    let imported_shop_items: usize = import_shop_items_in_roleshop_db(&sqlite_client, startup_config.economy.shop_items.clone()).await.expect("Failed to import shop items into Loopchan's Database");
+    if imported_shop_items > 0 {
+        warn!("Imported {} shop items from Config.toml, use /adm shop to manage them from now on", imported_shop_items);
+    }
+    prepare_inventory_db(&sqlite_client).await;
+    prepare_equipped_items_db(&sqlite_client).await;
+    prepare_gambling_db(&sqlite_client).await;
+    prepare_gambling_blocks_db(&sqlite_client).await;
+    prepare_open_bets_db(&sqlite_client).await;
+    // Unfinished games were kept in memory, so they can't be finished anymore
+    let refunded_bets: Vec<(u64, String, u64)> = refund_open_bets_in_gambling_db(&sqlite_client).await.expect("Failed to refund open bets in Loopchan's Database");
+    for (discord_id, game, bet) in &refunded_bets {
+        warn!("Refunded ${} {} bet of {}, game was unfinished when Loopchan stopped", bet, game, discord_id);
+    }
+    prepare_member_leaves_db(&sqlite_client).await;
+    prepare_settings_db(&sqlite_client).await;
+    prepare_blacklist_db(&sqlite_client).await;
+    prepare_blacklist_appeals_db(&sqlite_client).await;
+    prepare_mod_cases_db(&sqlite_client).await;
+    prepare_audit_log_db(&sqlite_client).await;
+    prepare_maintenance_windows_db(&sqlite_client).await;
+    prepare_maintenance_allowlist_db(&sqlite_client).await;
+    let imported_blacklist: usize = import_blacklist_in_blacklist_db(&sqlite_client, startup_config.blacklist.clone()).await.expect("Failed to import blacklist into Loopchan's Database");
+    if imported_blacklist > 0 {
+        warn!("Imported {} blacklisted users from Config.toml, use /adm blacklist to manage them from now on", imported_blacklist);
+        // Members from it are only banned once someone reviews them with /adm blacklist sweep
+        set_setting_in_settings_db(&sqlite_client, handlers::events::blacklist::SWEEP_REVIEW_SETTING, String::from("Config.toml")).await.expect("Failed to mark imported blacklist for review in Loopchan's Database");
+    }
     prepare_fishing_db(&sqlite_client).await;
+    prepare_fish_catches_db(&sqlite_client).await;
     prepare_lastfm_db(&sqlite_client).await;
+    prepare_cooldowns_db(&sqlite_client).await;
 
     // Loopchan's Poise Framework
     let framework = poise::Framework::builder()
@@ -558,10 +802,31 @@ async fn main() {
                 commands::eco::eco(),
                 commands::lastfm::lastfm(),
                 commands::fishing::fishing(),
+                commands::inventory::inventory(),
                 commands::adm::adm(),
+                commands::blacklist::blacklist_check(),
+                commands::moderation::moderation(),
             ],
             command_check: Some(|ctx| {
                 Box::pin(async move {
+                    if !utils::permissions::check_permission_level(ctx).await? {
+                        return Ok(false);
+                    }
+
+                    // `/adm maintenance` always works, so staff can end maintenance without being on allowlist
+                    let maintenance_message: Option<String> = if ctx.command().qualified_name.starts_with("adm maintenance") {
+                        None
+                    } else {
+                        maintenance_block_message(ctx.serenity_context(), ctx.data(), ctx.author().id).await
+                    };
+                    if maintenance_message.is_some() {
+                        ctx.send(poise::CreateReply::default()
+                            .content(maintenance_message.unwrap())
+                            .ephemeral(true)
+                        ).await?;
+                        return Ok(false);
+                    }
+
                     // Ion know how to make it look better :pray:
                     match &ctx.command().qualified_name {
                         val if val == &"eco work".to_owned() => return Ok(true),
@@ -569,31 +834,20 @@ async fn main() {
                         _ => {}
                     }
 
-                    let loopchans_config = &ctx.data().config;
+                    let loopchans_config = ctx.data().config.get();
 
                     let author_id = ctx.author().id;
 
-                    if loopchans_config.maintenance && author_id.get() != loopchans_config.owner {
-                        return Err("Loopchan is currently closed for maintenance. Please check back later, or wait for announcement.".into());
-                    }
-
-                    let mut cooldown_durations = poise::CooldownConfig::default();
                     if loopchans_config.global_cooldown == 0 {
-                        cooldown_durations.user = None;
-                    } else {
-                        cooldown_durations.user = Some(std::time::Duration::from_secs(loopchans_config.global_cooldown));
+                        return Ok(true);
                     }
 
-                    let cc: poise::CooldownContext = poise::CooldownContext {
-                        user_id: author_id,
-                        channel_id: ctx.channel_id(),
-                        guild_id: ctx.guild_id()
-                    };
-
-                    let remaining_cooldown = {
-                        let cooldown_tracker = ctx.command().cooldowns.lock().unwrap();
-                        cooldown_tracker.remaining_cooldown(cc, &cooldown_durations)
-                    };
+                    let remaining_cooldown = get_user_remaining_cooldown_in_cooldowns_db(
+                        &ctx.data().db_client,
+                        author_id.get(),
+                        format!("command.{}", ctx.command().qualified_name),
+                        std::time::Duration::from_secs(loopchans_config.global_cooldown)
+                    ).await?;
 
                     match remaining_cooldown {
                         Some(remaining) => {
@@ -606,12 +860,7 @@ async fn main() {
                             ).await?;
                             Err(format!("Cooldown {} seconds", remaining_precise).into())
                         }
-                        None => {
-                            // Moved to post_command hook
-                            //let mut cooldown_tracker = ctx.command().cooldowns.lock().unwrap();
-                            //cooldown_tracker.start_cooldown(cc);
-                            Ok(true)
-                        },
+                        None => Ok(true), // Cooldown is started in post_command hook
                     }
                 })
             }),
@@ -635,6 +884,7 @@ async fn main() {
                 Box::pin(async move {
                     let author = ctx.author();
                     info!("@{} ({}) executed command: \"{}\"", author.name, author.id, ctx.command().name);
+                    utils::audit::audit_command(ctx, String::from("success")).await;
 
                     // Ion know how to make it look better :pray:
                     match &ctx.command().qualified_name {
@@ -644,17 +894,14 @@ async fn main() {
                     }
 
                     let skip_cooldown = ctx.invocation_data::<bool>().await;
-                    if skip_cooldown.is_some() {
+                    if skip_cooldown.is_some() || ctx.data().config.get().global_cooldown == 0 {
                         return;
                     }
 
-                    let cc: poise::CooldownContext = poise::CooldownContext {
-                        user_id: author.id,
-                        channel_id: ctx.channel_id(),
-                        guild_id: ctx.guild_id()
-                    };
-                    let mut cooldown_tracker = ctx.command().cooldowns.lock().unwrap();
-                    cooldown_tracker.start_cooldown(cc);
+                    let started_cooldown = start_user_cooldown_in_cooldowns_db(&ctx.data().db_client, author.id.get(), format!("command.{}", ctx.command().qualified_name)).await;
+                    if started_cooldown.is_err() {
+                        error!("Failed to start {}'s cooldown for \"{}\": {}", author.id, ctx.command().qualified_name, started_cooldown.unwrap_err().to_string());
+                    }
                 })
             },
             manual_cooldowns: true,
@@ -662,22 +909,103 @@ async fn main() {
         })
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
-                let toml_string = tokio::fs::read_to_string("Config.toml").await;
-                if toml_string.is_err() {
-                    let error_str = format!("Failed to read your config.toml file: {}", toml_string.unwrap_err().to_string());
-                    error!(error_str);
-                    return Err(error_str.into());
-                }
-
-                let loopchans_config: LoopchanConfig = toml::from_str(&toml_string.unwrap()).unwrap();
-
-                ctx.set_activity(Some(PTL_PAID_TESTING_PRESENCE.clone()));
-                ctx.idle();
+                // Reload config once Config.toml is saved
+                tokio::spawn(utils::config::watch_config(loopchans_config.clone()));
+
+                // Keep maintenance and presence up to date (presence is set on first maintenance tick, then rotated)
+                let maintenance_state: std::sync::Arc<Mutex<MaintenanceState>> = std::sync::Arc::new(Mutex::new(load_maintenance_state(&sqlite_client).await?));
+                let presence_state: std::sync::Arc<Mutex<PresenceState>> = std::sync::Arc::new(Mutex::new(PresenceState::default()));
+                tokio::spawn(utils::maintenance::watch_maintenance(ctx.clone(), loopchans_config.clone(), sqlite_client.clone(), maintenance_state.clone(), presence_state.clone()));
+                tokio::spawn(utils::presence::rotate_presence(ctx.clone(), loopchans_config.clone(), sqlite_client.clone(), maintenance_state.clone(), presence_state.clone()));
+
+                // Stand for players in blackjack games left unfinished every minute
+                let blackjack_games: std::sync::Arc<Mutex<HashMap<u64, BlackjackGame>>> = std::sync::Arc::new(Mutex::new(HashMap::new()));
+                let blackjack_config: ConfigHandle = loopchans_config.clone();
+                let blackjack_db_client: async_sqlite::Client = sqlite_client.clone();
+                let expired_blackjack_games: std::sync::Arc<Mutex<HashMap<u64, BlackjackGame>>> = blackjack_games.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                    loop {
+                        interval.tick().await;
+                        handlers::events::blackjack::finish_expired_blackjack_games(&blackjack_config.get(), &blackjack_db_client, &expired_blackjack_games).await;
+                    }
+                });
+
+                // Prune cooldowns that can't be active anymore every hour
+                let pruning_config: ConfigHandle = loopchans_config.clone();
+                let pruning_db_client: async_sqlite::Client = sqlite_client.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+                    loop {
+                        interval.tick().await;
+                        let config: std::sync::Arc<LoopchanConfig> = pruning_config.get();
+                        let longest_cooldown: std::time::Duration = std::time::Duration::from_secs(
+                            config.global_cooldown
+                                .max(config.economy.work_cooldown*60)
+                                .max(config.economy.fish_cooldown*60)
+                                .max(config.economy.fish_cooldown_mg*60)
+                        );
+                        let pruned = prune_expired_cooldowns_in_cooldowns_db(&pruning_db_client, longest_cooldown).await;
+                        if pruned.is_err() {
+                            error!("Failed to prune expired cooldowns: {}", pruned.unwrap_err().to_string());
+                        }
+                    }
+                });
+
+                // Remove expired rental roles every minute
+                let expiry_ctx: serenity::Context = ctx.clone();
+                let expiry_db_client: async_sqlite::Client = sqlite_client.clone();
+                let expiry_guild_id: u64 = startup_config.guild;
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                    loop {
+                        interval.tick().await;
+                        handlers::events::roleshop::remove_expired_roles(&expiry_ctx, &expiry_db_client, expiry_guild_id).await;
+                    }
+                });
+
+                // Lift expired blacklists every minute
+                let blacklist_ctx: serenity::Context = ctx.clone();
+                let blacklist_db_client: async_sqlite::Client = sqlite_client.clone();
+                let blacklist_guild_id: u64 = startup_config.guild;
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                    loop {
+                        interval.tick().await;
+                        handlers::events::blacklist::lift_expired_blacklists(&blacklist_ctx, &blacklist_db_client, blacklist_guild_id).await;
+                    }
+                });
+
+                // Ban blacklisted members who are in server every 10 minutes (report only while imported blacklist wasn't reviewed)
+                let sweep_ctx: serenity::Context = ctx.clone();
+                let sweep_config: ConfigHandle = loopchans_config.clone();
+                let sweep_db_client: async_sqlite::Client = sqlite_client.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+                    loop {
+                        interval.tick().await;
+                        handlers::events::blacklist::run_blacklist_sweep(&sweep_ctx, &sweep_config.get(), &sweep_db_client).await;
+                    }
+                });
 
-                let ptl_guild_id: serenity::model::prelude::GuildId = loopchans_config.guild.into();
-                // Register commands
+                let ptl_guild_id: serenity::model::prelude::GuildId = startup_config.guild.into();
+                // Register commands, DM only ones (like /blacklist_check) have to be global since guild commands aren't shown in DMs
                 //poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                poise::builtins::register_in_guild(&ctx.http, &framework.options().commands, ptl_guild_id).await?;
+                let mut guild_commands: Vec<serenity::CreateCommand> = vec![];
+                let mut global_commands: Vec<serenity::CreateCommand> = vec![];
+                for command in &framework.options().commands {
+                    let slash_command: Option<serenity::CreateCommand> = command.create_as_slash_command();
+                    if slash_command.is_none() {
+                        continue;
+                    }
+                    if command.dm_only {
+                        global_commands.push(slash_command.unwrap());
+                    } else {
+                        guild_commands.push(slash_command.unwrap());
+                    }
+                }
+                ptl_guild_id.set_commands(&ctx.http, guild_commands).await?;
+                serenity::Command::set_global_commands(&ctx.http, global_commands).await?;
                 info!("Ready!");
                 // Create global data for commands and hooks
                 Ok(Data {
@@ -690,7 +1018,12 @@ async fn main() {
                     exp_cooldowns: Mutex::new(HashMap::new()),
                     regenerations_cooldowns: Mutex::new(HashMap::new()),
                     verifications: Mutex::new(HashMap::new()),
+                    roleshop_purchases: Mutex::new(HashSet::new()),
+                    blackjack_games,
+                    profile_cards: Mutex::new(HashMap::new()),
                     config: loopchans_config,
+                    maintenance: maintenance_state,
+                    presence: presence_state,
                     log_file
                 })
             })
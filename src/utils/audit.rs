@@ -0,0 +1,109 @@
+// Audit log of privileged actions (commands above Member permission level and staff buttons).
+// Entries are saved into database, logged as warnings (file log only keeps those) and mirrored to `audit` channel if it's set.
+
+use poise::serenity_prelude as serenity;
+use serenity::all::{ChannelId, Color, CreateAllowedMentions, CreateEmbed, CreateMessage, Message, ResolvedValue, Timestamp, User};
+use tracing::{error, warn};
+
+use crate::{Context, Data};
+use crate::utils::database::audit::log_entry_in_audit_db;
+use crate::utils::permissions::{required_permission_level, PermissionLevel};
+
+const MAX_ARGUMENT_LENGTH: usize = 200;
+
+fn format_argument(value: &ResolvedValue) -> String {
+    match value {
+        ResolvedValue::Boolean(value) => value.to_string(),
+        ResolvedValue::Integer(value) => value.to_string(),
+        ResolvedValue::Number(value) => value.to_string(),
+        ResolvedValue::String(value) => {
+            let mut value: String = value.replace('\n', " ");
+            if value.chars().count() > MAX_ARGUMENT_LENGTH {
+                value = format!("{}…", value.chars().take(MAX_ARGUMENT_LENGTH).collect::<String>());
+            }
+            format!("\"{}\"", value)
+        },
+        ResolvedValue::User(user, _) => format!("@{} ({})", user.name, user.id.get()),
+        ResolvedValue::Role(role) => format!("@{} ({})", role.name, role.id.get()),
+        ResolvedValue::Channel(channel) => format!("#{} ({})", channel.name.as_deref().unwrap_or("unknown"), channel.id.get()),
+        ResolvedValue::Attachment(attachment) => attachment.filename.clone(),
+        _ => String::from("?")
+    }
+}
+
+/// Arguments as `name=value` and first user argument as target
+fn command_arguments(ctx: Context<'_>) -> (String, Option<u64>) {
+    match ctx {
+        poise::Context::Application(app_ctx) => {
+            let target: Option<u64> = app_ctx.args.iter().find_map(|option| match option.value {
+                ResolvedValue::User(user, _) => Some(user.id.get()),
+                _ => None
+            });
+            let arguments: String = app_ctx.args.iter()
+                .map(|option| format!("{}={}", option.name, format_argument(&option.value)))
+                .collect::<Vec<String>>()
+                .join(" ");
+            (arguments, target)
+        },
+        poise::Context::Prefix(prefix_ctx) => (prefix_ctx.args.to_string(), None)
+    }
+}
+
+/// Records invoked command if it needs more than Member permission level, `outcome` is e.g. `success`, `denied` or `error: ...`
+pub async fn audit_command(ctx: Context<'_>, outcome: String) {
+    if required_permission_level(ctx) <= PermissionLevel::Member {
+        return;
+    }
+
+    let (arguments, target) = command_arguments(ctx);
+    record_audit_entry(ctx.serenity_context(), ctx.data(), ctx.author(), format!("/{}", ctx.command().qualified_name), arguments, target, outcome).await;
+}
+
+/// Saves entry into audit log and mirrors it to audit channel
+pub async fn record_audit_entry(
+    ctx: &serenity::Context,
+    data: &Data,
+    actor: &User,
+    command: String,
+    arguments: String,
+    target: Option<u64>,
+    outcome: String
+) {
+    warn!("Audit: @{} ({}) {} {} -> {}", actor.name, actor.id.get(), command, arguments, outcome);
+
+    let entry_id: Result<i64, async_sqlite::Error> = log_entry_in_audit_db(&data.db_client, actor.id.get(), command.clone(), arguments.clone(), target, outcome.clone()).await;
+    if entry_id.is_err() {
+        error!("Failed to save audit log entry for {} by {}: {}", command, actor.id.get(), entry_id.unwrap_err().to_string());
+        return;
+    }
+    let entry_id: i64 = entry_id.unwrap();
+
+    let audit_channel: Option<u64> = data.config.get().channels.audit;
+    if audit_channel.is_none() {
+        return;
+    }
+
+    let mut description: String = format!("**Actor:** @{} (<@{}>, {})", actor.name, actor.id.get(), actor.id.get());
+    if let Some(target) = target {
+        description.push_str(&format!("\n**Target:** <@{}> ({})", target, target));
+    }
+    if !arguments.is_empty() {
+        description.push_str(&format!("\n**Arguments:** `{}`", arguments.replace('`', "'")));
+    }
+    description.push_str(&format!("\n**Outcome:** {}", outcome));
+
+    let posted: Result<Message, serenity::Error> = ChannelId::new(audit_channel.unwrap()).send_message(ctx,
+        CreateMessage::default()
+            .embed(
+                CreateEmbed::default()
+                    .title(format!("Audit #{} • {}", entry_id, command))
+                    .description(description)
+                    .timestamp(Timestamp::now())
+                    .color(if outcome == "success" { Color::from_rgb(255, 255, 255) } else { Color::from_rgb(255, 100, 100) })
+            )
+            .allowed_mentions(CreateAllowedMentions::new())
+    ).await;
+    if posted.is_err() {
+        error!("Failed to post audit log entry #{} to audit channel: {}", entry_id, posted.unwrap_err().to_string());
+    }
+}
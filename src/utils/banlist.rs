@@ -0,0 +1,276 @@
+// Ban-list files shared with partner servers, either JSON or CSV:
+//   [{ "id": "908779319084589067", "reason": "Scamming", "created_at": 1747000000, "expires_at": null }]
+//   id,reason,created_at,expires_at
+//   908779319084589067,Scamming,1747000000,
+// IDs are written as strings since they don't fit into JavaScript numbers, but both are accepted.
+// Plain JSON array of IDs and CSV with only `id` column work too.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::DataBlacklistEntry;
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BanListEntry {
+    #[serde(serialize_with = "id_to_string", deserialize_with = "id_from_string_or_number")]
+    pub id: u64,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    #[serde(default)]
+    pub expires_at: Option<u64>
+}
+
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq)]
+pub enum BanListFormat {
+    #[name = "JSON"]
+    Json,
+    #[name = "CSV"]
+    Csv
+}
+
+impl BanListFormat {
+    /// Guessed from file name, JSON if it's not `.csv`
+    pub fn from_filename(filename: &str) -> BanListFormat {
+        if filename.to_lowercase().ends_with(".csv") {
+            BanListFormat::Csv
+        } else {
+            BanListFormat::Json
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BanListFormat::Json => "json",
+            BanListFormat::Csv => "csv"
+        }
+    }
+}
+
+fn id_to_string<S: Serializer>(id: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&id.to_string())
+}
+
+fn id_from_string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        String(String)
+    }
+    match Id::deserialize(deserializer)? {
+        Id::Number(id) => Ok(id),
+        Id::String(id) => id.trim().parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<&DataBlacklistEntry> for BanListEntry {
+    fn from(entry: &DataBlacklistEntry) -> Self {
+        BanListEntry {
+            id: entry.discord_id,
+            reason: entry.reason.clone(),
+            created_at: Some(entry.created_at),
+            expires_at: entry.expires_at
+        }
+    }
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits CSV into rows of fields, quoted fields may contain commas, quotes (`""`) and new lines
+fn split_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut field: String = String::new();
+    let mut quoted: bool = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {},
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            },
+            _ => field.push(character)
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.into_iter().filter(|row| !(row.len() == 1 && row[0].trim().is_empty())).collect()
+}
+
+pub fn write_ban_list(entries: &[BanListEntry], format: BanListFormat) -> String {
+    match format {
+        BanListFormat::Json => serde_json::to_string_pretty(entries).unwrap_or(String::from("[]")),
+        BanListFormat::Csv => {
+            let mut csv: String = String::from("id,reason,created_at,expires_at\n");
+            for entry in entries {
+                csv.push_str(&format!(
+                    "{},{},{},{}\n",
+                    entry.id,
+                    escape_csv_field(entry.reason.as_deref().unwrap_or("")),
+                    entry.created_at.map(|created_at| created_at.to_string()).unwrap_or_default(),
+                    entry.expires_at.map(|expires_at| expires_at.to_string()).unwrap_or_default()
+                ));
+            }
+            csv
+        }
+    }
+}
+
+/// Returns reason why file couldn't be read as error
+pub fn read_ban_list(text: &str, format: BanListFormat) -> Result<Vec<BanListEntry>, String> {
+    match format {
+        BanListFormat::Json => {
+            let entries: Result<Vec<BanListEntry>, serde_json::Error> = serde_json::from_str(text);
+            if entries.is_ok() {
+                return entries.map_err(|err| err.to_string());
+            }
+            // Plain array of IDs
+            #[derive(Deserialize)]
+            struct Id(#[serde(deserialize_with = "id_from_string_or_number")] u64);
+            let ids: Result<Vec<Id>, serde_json::Error> = serde_json::from_str(text);
+            if ids.is_err() {
+                return Err(entries.err().unwrap().to_string());
+            }
+            Ok(ids.unwrap().into_iter().map(|id| BanListEntry { id: id.0, reason: None, created_at: None, expires_at: None }).collect())
+        },
+        BanListFormat::Csv => {
+            let rows: Vec<Vec<String>> = split_csv(text);
+            if rows.is_empty() {
+                return Ok(vec![]);
+            }
+
+            // Header is optional, without it columns are in the same order as export
+            let header: Vec<String> = rows[0].iter().map(|column| column.trim().to_lowercase()).collect();
+            let has_header: bool = header.iter().any(|column| column == "id");
+            let column = |name: &str, fallback: usize| -> Option<usize> {
+                if has_header { header.iter().position(|column| column == name) } else { Some(fallback) }
+            };
+            let id_column: usize = column("id", 0).unwrap();
+            let reason_column: Option<usize> = column("reason", 1);
+            let created_at_column: Option<usize> = column("created_at", 2);
+            let expires_at_column: Option<usize> = column("expires_at", 3);
+
+            let mut entries: Vec<BanListEntry> = vec![];
+            for (index, row) in rows.iter().enumerate().skip(if has_header { 1 } else { 0 }) {
+                let field = |column: Option<usize>| -> Option<&str> {
+                    column.and_then(|column| row.get(column)).map(|field| field.trim()).filter(|field| !field.is_empty())
+                };
+                let id: Option<u64> = field(Some(id_column)).and_then(|id| id.parse().ok());
+                if id.is_none() {
+                    return Err(format!("Row {} doesn't have valid user ID", index + 1));
+                }
+                entries.push(BanListEntry {
+                    id: id.unwrap(),
+                    reason: field(reason_column).map(|reason| reason.to_string()),
+                    created_at: field(created_at_column).and_then(|created_at| created_at.parse().ok()),
+                    expires_at: field(expires_at_column).and_then(|expires_at| expires_at.parse().ok())
+                });
+            }
+            Ok(entries)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_csv_handles_quoted_commas_and_quotes() {
+        let rows: Vec<Vec<String>> = split_csv("id,reason\r\n1,\"Scam, \"\"free nitro\"\"\"\r\n2,\"Two\nlines\"\n\n");
+        assert_eq!(rows, vec![
+            vec!["id", "reason"],
+            vec!["1", "Scam, \"free nitro\""],
+            vec!["2", "Two\nlines"]
+        ]);
+    }
+
+    #[test]
+    fn csv_without_header_uses_export_column_order() {
+        let entries: Vec<BanListEntry> = read_ban_list("908779319084589067,Scamming,1747000000,1748000000\n1,,,\n", BanListFormat::Csv).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 908779319084589067);
+        assert_eq!(entries[0].reason.as_deref(), Some("Scamming"));
+        assert_eq!(entries[0].created_at, Some(1747000000));
+        assert_eq!(entries[0].expires_at, Some(1748000000));
+        assert_eq!(entries[1].id, 1);
+        assert_eq!(entries[1].reason, None);
+    }
+
+    #[test]
+    fn csv_with_header_uses_named_columns() {
+        let entries: Vec<BanListEntry> = read_ban_list("reason,ID\nScamming,908779319084589067\n", BanListFormat::Csv).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, 908779319084589067);
+        assert_eq!(entries[0].reason.as_deref(), Some("Scamming"));
+        assert_eq!(entries[0].created_at, None);
+    }
+
+    #[test]
+    fn csv_with_invalid_id_is_rejected() {
+        assert!(read_ban_list("id\n908779319084589067\nnot an id\n", BanListFormat::Csv).is_err());
+    }
+
+    #[test]
+    fn json_accepts_plain_id_array() {
+        let entries: Vec<BanListEntry> = read_ban_list("[\"908779319084589067\", 1]", BanListFormat::Json).unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.id).collect::<Vec<u64>>(), vec![908779319084589067, 1]);
+        assert!(entries.iter().all(|entry| entry.reason.is_none() && entry.created_at.is_none()));
+    }
+
+    #[test]
+    fn json_accepts_string_and_number_ids() {
+        let entries: Vec<BanListEntry> = read_ban_list(
+            "[{ \"id\": \"908779319084589067\", \"reason\": \"Scamming\" }, { \"id\": 1, \"expires_at\": 1748000000 }]",
+            BanListFormat::Json
+        ).unwrap();
+        assert_eq!(entries[0].id, 908779319084589067);
+        assert_eq!(entries[0].reason.as_deref(), Some("Scamming"));
+        assert_eq!(entries[1].id, 1);
+        assert_eq!(entries[1].expires_at, Some(1748000000));
+
+        // IDs are written as strings
+        assert!(write_ban_list(&entries, BanListFormat::Json).contains("\"908779319084589067\""));
+    }
+
+    #[test]
+    fn json_with_invalid_id_is_rejected() {
+        assert!(read_ban_list("[{ \"id\": \"not an id\" }]", BanListFormat::Json).is_err());
+    }
+
+    #[test]
+    fn export_then_import_keeps_entries() {
+        let entries: Vec<BanListEntry> = vec![
+            BanListEntry { id: 908779319084589067, reason: Some(String::from("Scam, \"free nitro\"\nand alts")), created_at: Some(1747000000), expires_at: None },
+            BanListEntry { id: 1, reason: None, created_at: Some(1747000001), expires_at: Some(1748000000) }
+        ];
+        for format in [BanListFormat::Json, BanListFormat::Csv] {
+            let imported: Vec<BanListEntry> = read_ban_list(&write_ban_list(&entries, format), format).unwrap();
+            assert_eq!(imported.len(), entries.len());
+            for (imported, entry) in imported.iter().zip(entries.iter()) {
+                assert_eq!(imported.id, entry.id);
+                assert_eq!(imported.reason, entry.reason);
+                assert_eq!(imported.created_at, entry.created_at);
+                assert_eq!(imported.expires_at, entry.expires_at);
+            }
+        }
+    }
+}
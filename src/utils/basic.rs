@@ -1,26 +1,15 @@
 use std::io::Error;
 
+use chrono::Datelike;
+
 use serenity::json;
 
-use crate::{Context, Fish, FishModifier};
+use crate::{DataFish, Fish, FishModifier, Item, LevelingConfig};
 
 pub fn remove_whitespace(s: &str) -> String {
     s.chars().filter(|c: &char| !c.is_whitespace()).collect()
 }
 
-pub async fn _is_staff(ctx: Context<'_>, user: &serenity::model::user::User) -> bool { // Currently not useful, access to commands will be configured within server settings.
-    let config = &ctx.data().config;
-    if user.id == config.owner { return true; }
-    return false;
-    // user.has_role(ctx, config.guild, config.roles.staff).await.unwrap_or(false)
-}
-
-pub async fn is_qa(ctx: Context<'_>, user: &serenity::model::user::User) -> bool {
-    let config = &ctx.data().config;
-    if user.id == config.owner { return true; }
-    user.has_role(ctx, config.guild, config.roles.qa).await.unwrap_or(false)
-}
-
 pub fn generate_emoji_progressbar(current: u64, max: u64, progressbar_size: u64, progressbar_emojis: &crate::ProgressBarEmojisTypes) -> String {
     let fillstart = &progressbar_emojis.filled.start;
     let fillmid = &progressbar_emojis.filled.mid;
@@ -122,4 +111,125 @@ pub fn fishmodifiers_from_datafishmodifiers(
         modifiers.push(found_modifier.unwrap());
     }
     Ok(modifiers)
+}
+
+pub fn item_from_id(
+    id: &String,
+    items: &Vec<Item>
+) -> Result<Item, Error> {
+    for item in items {
+        if item.id != *id {
+            continue;
+        }
+        return Ok(item.clone());
+    }
+    return Err(Error::new(std::io::ErrorKind::Other, format!("Not found Item by provided ID: {}", id)));
+}
+
+/// Final size (in cm) and value of caught fish, same as shown in `/fishing inventory`
+pub fn fish_size_and_value(
+    fish: &DataFish,
+    fishes: &Vec<Fish>,
+    fishmodifiers: &Vec<FishModifier>
+) -> Result<(f32, u64), Error> {
+    let actual_fish: Fish = fish_from_name(&fish.r#type, fishes)?;
+    let modifiers: Vec<FishModifier> = fishmodifiers_from_datafishmodifiers(&fish.modifiers, fishmodifiers.clone())?;
+
+    let mut final_size: f32 = fish.size;
+    let mut final_value: f64 = actual_fish.base_value as f64;
+    for modifier in modifiers {
+        if modifier.size_multiplier.is_some() {
+            final_size *= modifier.size_multiplier.unwrap()
+        }
+        if modifier.value_multiplier.is_some() {
+            final_value *= modifier.value_multiplier.unwrap() as f64
+        }
+    }
+
+    final_size = (final_size*100.0).floor()/100.0;
+    Ok((final_size, (final_value*final_size as f64).floor() as u64))
+}
+
+/// EXP multiplier from `double_multiplier_on_weekdays` (2 on Saturday and Sunday, 1 otherwise)
+pub fn weekday_exp_multiplier(leveling_config: &LevelingConfig) -> u64 {
+    if !leveling_config.double_multiplier_on_weekdays {
+        return 1;
+    }
+    match chrono::offset::Local::now().date_naive().weekday() {
+        chrono::Weekday::Sat | chrono::Weekday::Sun => 2,
+        _ => 1
+    }
+}
+
+/// Formats seconds as e.g. `2d 5h`, `3h 10m` or `45s`
+pub fn format_duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+    if days > 0 {
+        return format!("{}d {}h", days, hours);
+    }
+    if hours > 0 {
+        return format!("{}h {}m", hours, minutes);
+    }
+    if minutes > 0 {
+        return format!("{}m", minutes);
+    }
+    format!("{}s", seconds)
+}
+
+/// Parses durations like `30m`, `1h 30m` or `7d` into seconds, plain number is minutes
+pub fn parse_duration(duration: &str) -> Option<u64> {
+    let duration: String = remove_whitespace(duration).to_lowercase();
+    if duration.is_empty() {
+        return None;
+    }
+    if let Ok(minutes) = duration.parse::<u64>() {
+        return minutes.checked_mul(60);
+    }
+
+    let mut seconds: u64 = 0;
+    let mut number: String = String::new();
+    for character in duration.chars() {
+        if character.is_ascii_digit() {
+            number.push(character);
+            continue;
+        }
+        let multiplier: u64 = match character {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 604800,
+            _ => return None
+        };
+        seconds = seconds.checked_add(number.parse::<u64>().ok()?.checked_mul(multiplier)?)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(seconds)
+}
+
+/// `values` must be sorted from lowest to highest
+pub fn median(values: &Vec<u64>) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    let middle: usize = values.len() / 2;
+    if values.len() % 2 == 0 {
+        return (values[middle-1] + values[middle]) / 2;
+    }
+    values[middle]
+}
+
+/// 0 means everyone has the same amount, 1 means one member has everything
+/// `values` must be sorted from lowest to highest
+pub fn gini_coefficient(values: &Vec<u64>) -> f64 {
+    let total: f64 = values.iter().map(|value| *value as f64).sum();
+    if values.is_empty() || total == 0.0 {
+        return 0.0;
+    }
+    let count: f64 = values.len() as f64;
+    let weighted_sum: f64 = values.iter().enumerate().map(|(index, value)| (index as f64 + 1.0) * *value as f64).sum();
+    (2.0 * weighted_sum) / (count * total) - (count + 1.0) / count
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+use ab_glyph::{FontRef, PxScale};
+
+use image::{ImageBuffer, Rgb};
+use imageproc::drawing::{draw_filled_rect_mut, draw_line_segment_mut, draw_text_mut, text_size};
+use imageproc::rect::Rect;
+
+const CHART_WIDTH: u32 = 1200;
+const CHART_HEIGHT: u32 = 500;
+const CHART_MARGIN_LEFT: u32 = 110;
+const CHART_MARGIN_RIGHT: u32 = 30;
+const CHART_MARGIN_TOP: u32 = 60;
+const CHART_MARGIN_BOTTOM: u32 = 50;
+
+const BACKGROUND_COLOR: Rgb<u8> = Rgb([30, 31, 34]);
+const AXIS_COLOR: Rgb<u8> = Rgb([90, 92, 98]);
+const TEXT_COLOR: Rgb<u8> = Rgb([255, 255, 255]);
+const MINTED_COLOR: Rgb<u8> = Rgb([100, 255, 100]);
+const BURNED_COLOR: Rgb<u8> = Rgb([255, 100, 100]);
+const NET_COLOR: Rgb<u8> = Rgb([255, 210, 90]);
+
+/// Renders bar chart of coins minted (up) and burned (down) per day, with net change line on top.
+/// `days` is `(day since unix epoch, minted, burned)`, oldest first.
+pub fn render_minted_burned_chart(days: &Vec<(u64, u64, u64)>) -> Result<Vec<u8>, image::ImageError> {
+    let mut image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(CHART_WIDTH, CHART_HEIGHT, BACKGROUND_COLOR);
+    let font: FontRef<'_> = FontRef::try_from_slice(include_bytes!("../../CascadiaCode.ttf")).unwrap();
+    let title_scale: PxScale = PxScale { x: 30.0, y: 30.0 };
+    let label_scale: PxScale = PxScale { x: 18.0, y: 18.0 };
+
+    draw_text_mut(&mut image, TEXT_COLOR, CHART_MARGIN_LEFT as i32, 15, title_scale, &font, "Coins minted / burned per day");
+
+    let plot_width: u32 = CHART_WIDTH - CHART_MARGIN_LEFT - CHART_MARGIN_RIGHT;
+    let plot_height: u32 = CHART_HEIGHT - CHART_MARGIN_TOP - CHART_MARGIN_BOTTOM;
+    let zero_y: f32 = (CHART_MARGIN_TOP + plot_height/2) as f32;
+
+    let max_value: u64 = days.iter().map(|(_, minted, burned)| (*minted).max(*burned)).max().unwrap_or(0).max(1);
+    let value_to_height = |value: u64| -> f32 { value as f32 / max_value as f32 * (plot_height/2) as f32 };
+
+    // Axis and value labels
+    draw_line_segment_mut(&mut image, (CHART_MARGIN_LEFT as f32, zero_y), ((CHART_WIDTH - CHART_MARGIN_RIGHT) as f32, zero_y), AXIS_COLOR);
+    draw_line_segment_mut(&mut image, (CHART_MARGIN_LEFT as f32, CHART_MARGIN_TOP as f32), (CHART_MARGIN_LEFT as f32, (CHART_MARGIN_TOP + plot_height) as f32), AXIS_COLOR);
+    for (label, y) in [(format!("+{}", max_value), CHART_MARGIN_TOP as f32), ("0".to_string(), zero_y), (format!("-{}", max_value), (CHART_MARGIN_TOP + plot_height) as f32)] {
+        let (label_width, label_height) = text_size(label_scale, &font, &label);
+        draw_text_mut(&mut image, TEXT_COLOR, (CHART_MARGIN_LEFT - label_width - 10) as i32, (y - label_height as f32/2.0) as i32, label_scale, &font, &label);
+    }
+
+    if days.is_empty() {
+        let mut bytes: Vec<u8> = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+        return Ok(bytes);
+    }
+
+    let slot_width: f32 = plot_width as f32 / days.len() as f32;
+    let bar_width: u32 = ((slot_width * 0.6) as u32).max(1);
+    let mut previous_net_point: Option<(f32, f32)> = None;
+
+    for (index, (day, minted, burned)) in days.iter().enumerate() {
+        let slot_center: f32 = CHART_MARGIN_LEFT as f32 + slot_width * (index as f32 + 0.5);
+        let bar_x: i32 = (slot_center - bar_width as f32/2.0) as i32;
+
+        let minted_height: u32 = value_to_height(*minted) as u32;
+        if minted_height > 0 {
+            draw_filled_rect_mut(&mut image, Rect::at(bar_x, zero_y as i32 - minted_height as i32).of_size(bar_width, minted_height), MINTED_COLOR);
+        }
+
+        let burned_height: u32 = value_to_height(*burned) as u32;
+        if burned_height > 0 {
+            draw_filled_rect_mut(&mut image, Rect::at(bar_x, zero_y as i32 + 1).of_size(bar_width, burned_height), BURNED_COLOR);
+        }
+
+        let net: f32 = *minted as f32 - *burned as f32;
+        let net_point: (f32, f32) = (slot_center, zero_y - net / max_value as f32 * (plot_height/2) as f32);
+        if previous_net_point.is_some() {
+            draw_line_segment_mut(&mut image, previous_net_point.unwrap(), net_point, NET_COLOR);
+        }
+        previous_net_point = Some(net_point);
+
+        // Date label every 5 days (and always for the last one)
+        if index % 5 == 0 || index == days.len() - 1 {
+            let date: String = chrono::DateTime::from_timestamp((*day * 86400) as i64, 0)
+                .map(|date| date.format("%m-%d").to_string())
+                .unwrap_or_default();
+            let (label_width, _) = text_size(label_scale, &font, &date);
+            draw_text_mut(&mut image, TEXT_COLOR, (slot_center - label_width as f32/2.0) as i32, (CHART_MARGIN_TOP + plot_height + 15) as i32, label_scale, &font, &date);
+        }
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+    Ok(bytes)
+}
@@ -0,0 +1,192 @@
+// Config.toml is read once at startup and can be reloaded later with `/adm reload` or by saving the file (it's checked every few seconds).
+// Commands get current config with `data.config.get()`, which is cheap Arc clone, so don't keep it for longer than one command or event.
+
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use tracing::{error, warn};
+
+use crate::LoopchanConfig;
+
+pub const CONFIG_PATH: &'static str = "Config.toml";
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+pub struct ConfigHandle {
+    current: Arc<RwLock<Arc<LoopchanConfig>>>
+}
+
+impl ConfigHandle {
+    pub fn new(config: LoopchanConfig) -> Self {
+        ConfigHandle { current: Arc::new(RwLock::new(Arc::new(config))) }
+    }
+
+    pub fn get(&self) -> Arc<LoopchanConfig> {
+        self.current.read().unwrap().clone()
+    }
+
+    fn swap(&self, config: LoopchanConfig) -> Arc<LoopchanConfig> {
+        std::mem::replace(&mut *self.current.write().unwrap(), Arc::new(config))
+    }
+}
+
+/// Reads and validates Config.toml
+pub async fn read_config() -> Result<LoopchanConfig, String> {
+    let toml_string: String = tokio::fs::read_to_string(CONFIG_PATH).await
+        .map_err(|err| format!("Failed to read {}: {}", CONFIG_PATH, err))?;
+    let config: LoopchanConfig = toml::from_str(&toml_string)
+        .map_err(|err| format!("Failed to parse {}: {}", CONFIG_PATH, err))?;
+    validate_config(&config)?;
+    Ok(config)
+}
+
+fn validate_chance(name: &str, chance: f32) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&chance) {
+        return Err(format!("`{}` must be between 0 and 1", name));
+    }
+    Ok(())
+}
+
+fn validate_config(config: &LoopchanConfig) -> Result<(), String> {
+    let economy = &config.economy;
+
+    if economy.work_payment.len() != 2 || economy.work_payment[0] >= economy.work_payment[1] {
+        return Err(String::from("`economy.work_payment` must be [min, max] with min lower than max"));
+    }
+    if economy.pay_min > economy.pay_max {
+        return Err(String::from("`economy.pay_min` can't be higher than `economy.pay_max`"));
+    }
+    if economy.gamble_min_bet > economy.gamble_max_bet {
+        return Err(String::from("`economy.gamble_min_bet` can't be higher than `economy.gamble_max_bet`"));
+    }
+    validate_chance("economy.work_fail_chance", economy.work_fail_chance)?;
+    validate_chance("economy.fish_fail_chance", economy.fish_fail_chance)?;
+    validate_chance("economy.gamble_house_edge", economy.gamble_house_edge)?;
+    if let Some(pay_fee) = economy.pay_fee {
+        validate_chance("economy.pay_fee", pay_fee)?;
+    }
+    if economy.fishes.is_empty() {
+        return Err(String::from("`economy.fishes` can't be empty"));
+    }
+
+    let modifier_names: HashSet<&String> = economy.fishes_modifiers.iter().map(|modifier| &modifier.name).collect();
+    for fish in &economy.fishes {
+        if fish.chance == 0 {
+            return Err(format!("Chance of fish \"{}\" can't be 0", fish.name));
+        }
+        if fish.possible_size.len() != 2 || fish.possible_size[0] > fish.possible_size[1] {
+            return Err(format!("`possible_size` of fish \"{}\" must be [min, max]", fish.name));
+        }
+        let unknown_modifier: Option<&String> = fish.possible_modifiers.iter().find(|modifier| !modifier_names.contains(modifier));
+        if let Some(unknown_modifier) = unknown_modifier {
+            return Err(format!("Fish \"{}\" has unknown modifier \"{}\"", fish.name, unknown_modifier));
+        }
+    }
+
+    if let Some(presence) = &config.presence {
+        // Discord doesn't like presence changing too often
+        if presence.interval < 15 {
+            return Err(String::from("`presence.interval` must be at least 15 seconds"));
+        }
+        if presence.rotation.is_empty() {
+            return Err(String::from("`presence.rotation` can't be empty"));
+        }
+    }
+
+    let mut item_ids: HashSet<&String> = HashSet::new();
+    for item in &economy.items {
+        if !item_ids.insert(&item.id) {
+            return Err(format!("Item ID \"{}\" is used more than once", item.id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that can only be done against running config
+fn validate_reload(current: &LoopchanConfig, new: &LoopchanConfig) -> Result<(), String> {
+    if current.guild != new.guild || current.database_path != new.database_path {
+        return Err(String::from("`guild` and `database_path` can't be changed without restart"));
+    }
+
+    // Fishes, modifiers and items are referenced by name/ID from database
+    let fish_names: HashSet<&String> = new.economy.fishes.iter().map(|fish| &fish.name).collect();
+    if let Some(fish) = current.economy.fishes.iter().find(|fish| !fish_names.contains(&fish.name)) {
+        return Err(format!("Fish \"{}\" can't be removed, members may have already caught it", fish.name));
+    }
+    let modifier_names: HashSet<&String> = new.economy.fishes_modifiers.iter().map(|modifier| &modifier.name).collect();
+    if let Some(modifier) = current.economy.fishes_modifiers.iter().find(|modifier| !modifier_names.contains(&modifier.name)) {
+        return Err(format!("Fish modifier \"{}\" can't be removed, members may have already caught fish with it", modifier.name));
+    }
+    let item_ids: HashSet<&String> = new.economy.items.iter().map(|item| &item.id).collect();
+    if let Some(item) = current.economy.items.iter().find(|item| !item_ids.contains(&item.id)) {
+        return Err(format!("Item \"{}\" can't be removed, members may already have it", item.id));
+    }
+
+    Ok(())
+}
+
+fn collect_changed_keys(prefix: &str, old: Option<&toml::Value>, new: Option<&toml::Value>, changed: &mut Vec<String>) {
+    match (old, new) {
+        (Some(toml::Value::Table(old)), Some(toml::Value::Table(new))) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path: String = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                collect_changed_keys(&path, old.get(key), new.get(key), changed);
+            }
+        },
+        (old, new) if old != new => changed.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+/// Dotted paths of keys which differ, arrays (like `economy.fishes`) are compared as whole
+fn changed_keys(old: &LoopchanConfig, new: &LoopchanConfig) -> Vec<String> {
+    let old: Option<toml::Value> = toml::Value::try_from(old).ok();
+    let new: Option<toml::Value> = toml::Value::try_from(new).ok();
+    let mut changed: Vec<String> = vec![];
+    collect_changed_keys("", old.as_ref(), new.as_ref(), &mut changed);
+    changed
+}
+
+/// Reads Config.toml again and swaps it in if it's valid, returns changed keys.
+/// Values only used at startup (shop items and blacklist) are still managed with `/adm shop` and `/adm blacklist`.
+pub async fn reload_config(handle: &ConfigHandle) -> Result<Vec<String>, String> {
+    let new: LoopchanConfig = read_config().await?;
+    validate_reload(&handle.get(), &new)?;
+
+    let changed: Vec<String> = changed_keys(&handle.get(), &new);
+    if changed.is_empty() {
+        return Ok(changed);
+    }
+
+    handle.swap(new);
+    warn!("Reloaded {}, changed keys: {}", CONFIG_PATH, changed.join(", "));
+    Ok(changed)
+}
+
+fn config_modified_at() -> Option<SystemTime> {
+    std::fs::metadata(CONFIG_PATH).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Reloads config whenever Config.toml is saved, invalid configs are skipped until file is saved again
+pub async fn watch_config(handle: ConfigHandle) {
+    let mut modified_at: Option<SystemTime> = config_modified_at();
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let current_modified_at: Option<SystemTime> = config_modified_at();
+        if current_modified_at == modified_at {
+            continue;
+        }
+        modified_at = current_modified_at;
+
+        let reloaded: Result<Vec<String>, String> = reload_config(&handle).await;
+        if reloaded.is_err() {
+            error!("{} was changed but not reloaded: {}", CONFIG_PATH, reloaded.unwrap_err());
+        }
+    }
+}
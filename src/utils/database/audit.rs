@@ -0,0 +1,70 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::DataAuditEntry;
+
+pub async fn prepare_audit_log_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                actor INTEGER,
+                command TEXT,
+                arguments TEXT,
+                target INTEGER,
+                outcome TEXT,
+                created_at INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create audit_log table in Loopchan's Database");
+}
+
+/// Returns entry ID
+pub async fn log_entry_in_audit_db(
+    db_client: &async_sqlite::Client,
+    actor: u64,
+    command: String,
+    arguments: String,
+    target: Option<u64>,
+    outcome: String
+) -> Result<i64, async_sqlite::Error> {
+    let created_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO audit_log (actor, command, arguments, target, outcome, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (actor, command, arguments, target, outcome, created_at)
+        )?;
+        Ok(conn.last_insert_rowid())
+    }).await
+}
+
+/// Newest entries first, filters that are `None` are ignored
+pub async fn query_entries_in_audit_db(
+    db_client: &async_sqlite::Client,
+    actor: Option<u64>,
+    target: Option<u64>,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: u64
+) -> Result<Vec<DataAuditEntry>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare(
+            "SELECT id, actor, command, arguments, target, outcome, created_at FROM audit_log WHERE (?1 IS NULL OR actor=?1) AND (?2 IS NULL OR target=?2) AND (?3 IS NULL OR created_at>=?3) AND (?4 IS NULL OR created_at<=?4) ORDER BY id DESC LIMIT ?5"
+        )?;
+        let mut rows = stmt.query((actor, target, since, until, limit))?;
+
+        let mut entries: Vec<DataAuditEntry> = vec![];
+        while let Some(row) = rows.next()? {
+            entries.push(DataAuditEntry {
+                id: row.get(0)?,
+                actor: row.get(1)?,
+                command: row.get(2)?,
+                arguments: row.get(3)?,
+                target: row.get(4)?,
+                outcome: row.get(5)?,
+                created_at: row.get(6)?
+            });
+        }
+        Ok(entries)
+    }).await
+}
@@ -0,0 +1,317 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{DataBlacklistAppeal, DataBlacklistEntry};
+
+pub async fn prepare_blacklist_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS blacklist (
+                discord_id INTEGER PRIMARY KEY,
+                reason TEXT,
+                moderator INTEGER,
+                created_at INTEGER,
+                expires_at INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create blacklist table in Loopchan's Database");
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+const BLACKLIST_COLUMNS: &str = "discord_id, reason, moderator, created_at, expires_at";
+
+fn row_to_blacklist_entry(row: &async_sqlite::rusqlite::Row) -> Result<DataBlacklistEntry, async_sqlite::rusqlite::Error> {
+    Ok(DataBlacklistEntry {
+        discord_id: row.get(0)?,
+        reason: row.get(1)?,
+        moderator: row.get(2)?,
+        created_at: row.get(3)?,
+        expires_at: row.get(4)?
+    })
+}
+
+/// Copies IDs from Config.toml `blacklist` into database once, marked in settings table so removed entries don't come back
+pub async fn import_blacklist_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    discord_ids: Vec<u64>
+) -> Result<usize, async_sqlite::Error> {
+    let created_at: u64 = now_secs();
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let imported: u64 = transaction.query_row("SELECT COUNT(*) FROM settings WHERE key = 'blacklist.imported'", [], |row| row.get(0))?;
+        if imported > 0 {
+            return Ok(0);
+        }
+
+        let mut count: usize = 0;
+        for discord_id in &discord_ids {
+            count += transaction.execute(
+                "INSERT OR IGNORE INTO blacklist (discord_id, reason, moderator, created_at, expires_at) VALUES (?1, NULL, NULL, ?2, NULL)",
+                (discord_id, created_at)
+            )?;
+        }
+        transaction.execute("INSERT INTO settings (key, value) VALUES ('blacklist.imported', ?)", [created_at.to_string()])?;
+
+        transaction.commit()?;
+        Ok(count)
+    }).await
+}
+
+/// Adds user or replaces their existing entry
+pub async fn add_user_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    reason: Option<String>,
+    moderator: Option<u64>,
+    expires_at: Option<u64>
+) -> Result<usize, async_sqlite::Error> {
+    let created_at: u64 = now_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT OR REPLACE INTO blacklist (discord_id, reason, moderator, created_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            (discord_id, reason, moderator, created_at, expires_at)
+        )
+    }).await
+}
+
+pub async fn remove_user_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM blacklist WHERE discord_id=?",
+            [discord_id]
+        )
+    }).await
+}
+
+/// Expired entries are ignored, they're removed by `take_expired_users_in_blacklist_db`
+pub async fn get_user_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<Option<DataBlacklistEntry>, async_sqlite::Error> {
+    let now: u64 = now_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query: String = format!("SELECT {} FROM blacklist WHERE discord_id=?1 AND (expires_at IS NULL OR expires_at>?2)", BLACKLIST_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query((discord_id, now))?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row_to_blacklist_entry(row)?)),
+            None => Ok(None)
+        }
+    }).await
+}
+
+/// Adds users who aren't blacklisted yet, existing entries are kept as they are. Returns how many were added.
+pub async fn import_users_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    entries: Vec<DataBlacklistEntry>
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let mut count: usize = 0;
+        for entry in &entries {
+            count += transaction.execute(
+                "INSERT OR IGNORE INTO blacklist (discord_id, reason, moderator, created_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                (entry.discord_id, &entry.reason, entry.moderator, entry.created_at, entry.expires_at)
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(count)
+    }).await
+}
+
+/// Every entry that hasn't expired, oldest first
+pub async fn get_all_users_in_blacklist_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<DataBlacklistEntry>, async_sqlite::Error> {
+    let now: u64 = now_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query: String = format!("SELECT {} FROM blacklist WHERE expires_at IS NULL OR expires_at>? ORDER BY created_at, discord_id", BLACKLIST_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query([now])?;
+
+        let mut entries: Vec<DataBlacklistEntry> = vec![];
+        while let Some(row) = rows.next()? {
+            entries.push(row_to_blacklist_entry(row)?);
+        }
+        Ok(entries)
+    }).await
+}
+
+/// Newest entries first
+pub async fn list_users_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    limit: u64,
+    offset: u64
+) -> Result<Vec<DataBlacklistEntry>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query: String = format!("SELECT {} FROM blacklist ORDER BY created_at DESC, discord_id LIMIT ?1 OFFSET ?2", BLACKLIST_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query((limit, offset))?;
+
+        let mut entries: Vec<DataBlacklistEntry> = vec![];
+        while let Some(row) = rows.next()? {
+            entries.push(row_to_blacklist_entry(row)?);
+        }
+        Ok(entries)
+    }).await
+}
+
+pub async fn count_users_in_blacklist_db(
+    db_client: &async_sqlite::Client
+) -> Result<u64, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.query_row("SELECT COUNT(*) FROM blacklist", [], |row| row.get(0))
+    }).await
+}
+
+/// Removes expired entries and returns them, so their bans can be lifted
+pub async fn take_expired_users_in_blacklist_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<DataBlacklistEntry>, async_sqlite::Error> {
+    let now: u64 = now_secs();
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let mut entries: Vec<DataBlacklistEntry> = vec![];
+        {
+            let query: String = format!("SELECT {} FROM blacklist WHERE expires_at IS NOT NULL AND expires_at<=?", BLACKLIST_COLUMNS);
+            let mut stmt = transaction.prepare(&query)?;
+            let mut rows = stmt.query([now])?;
+            while let Some(row) = rows.next()? {
+                entries.push(row_to_blacklist_entry(row)?);
+            }
+        }
+        transaction.execute("DELETE FROM blacklist WHERE expires_at IS NOT NULL AND expires_at<=?", [now])?;
+
+        transaction.commit()?;
+        Ok(entries)
+    }).await
+}
+
+// Appeals are kept after decision, last one decides when user can appeal again
+pub async fn prepare_blacklist_appeals_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS blacklist_appeals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                discord_id INTEGER,
+                appeal TEXT,
+                created_at INTEGER,
+                accepted INTEGER,
+                decided_at INTEGER,
+                moderator INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create blacklist_appeals table in Loopchan's Database");
+}
+
+const APPEAL_COLUMNS: &str = "id, discord_id, appeal, created_at, accepted, decided_at, moderator";
+
+fn row_to_appeal(row: &async_sqlite::rusqlite::Row) -> Result<DataBlacklistAppeal, async_sqlite::rusqlite::Error> {
+    Ok(DataBlacklistAppeal {
+        id: row.get(0)?,
+        discord_id: row.get(1)?,
+        appeal: row.get(2)?,
+        created_at: row.get(3)?,
+        accepted: row.get(4)?,
+        decided_at: row.get(5)?,
+        moderator: row.get(6)?
+    })
+}
+
+/// Returns ID of new appeal
+pub async fn create_appeal_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    appeal: String
+) -> Result<i64, async_sqlite::Error> {
+    let created_at: u64 = now_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO blacklist_appeals (discord_id, appeal, created_at) VALUES (?1, ?2, ?3)",
+            (discord_id, appeal, created_at)
+        )?;
+        Ok(conn.last_insert_rowid())
+    }).await
+}
+
+/// Used when appeal couldn't be posted to staff, so user can send it again
+pub async fn delete_appeal_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    id: i64
+) -> Result<(), async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute("DELETE FROM blacklist_appeals WHERE id=? AND accepted IS NULL", [id])?;
+        Ok(())
+    }).await
+}
+
+pub async fn get_appeal_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    id: i64
+) -> Result<Option<DataBlacklistAppeal>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query: String = format!("SELECT {} FROM blacklist_appeals WHERE id=?", APPEAL_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query([id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row_to_appeal(row)?)),
+            None => Ok(None)
+        }
+    }).await
+}
+
+pub async fn get_last_appeal_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<Option<DataBlacklistAppeal>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query: String = format!("SELECT {} FROM blacklist_appeals WHERE discord_id=? ORDER BY id DESC LIMIT 1", APPEAL_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query([discord_id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row_to_appeal(row)?)),
+            None => Ok(None)
+        }
+    }).await
+}
+
+/// Accepting also removes user from blacklist. Returns `false` if appeal was already decided.
+pub async fn decide_appeal_in_blacklist_db(
+    db_client: &async_sqlite::Client,
+    id: i64,
+    accepted: bool,
+    moderator: u64
+) -> Result<bool, async_sqlite::Error> {
+    let decided_at: u64 = now_secs();
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let updated: usize = transaction.execute(
+            "UPDATE blacklist_appeals SET accepted=?1, decided_at=?2, moderator=?3 WHERE id=?4 AND accepted IS NULL",
+            (accepted, decided_at, moderator, id)
+        )?;
+        if updated == 0 {
+            return Ok(false);
+        }
+        if accepted {
+            transaction.execute(
+                "DELETE FROM blacklist WHERE discord_id=(SELECT discord_id FROM blacklist_appeals WHERE id=?)",
+                [id]
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(true)
+    }).await
+}
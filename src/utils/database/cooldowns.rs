@@ -0,0 +1,126 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Cooldowns are stored as the moment they were started (in milliseconds), not as expiry time,
+// because some actions share one cooldown with different durations (`/fishing fish` with and without minigame).
+
+pub async fn prepare_cooldowns_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS cooldowns (
+                discord_id INTEGER,
+                action TEXT,
+                started_at INTEGER,
+                PRIMARY KEY (discord_id, action)
+            )",
+            []
+        )
+    }).await.expect("Failed to create cooldowns table in Loopchan's Database");
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+pub async fn start_user_cooldown_in_cooldowns_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    action: String
+) -> Result<usize, async_sqlite::Error> {
+    let started_at: u64 = now_millis();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO cooldowns (discord_id, action, started_at) VALUES (?1, ?2, ?3) ON CONFLICT DO UPDATE SET started_at = EXCLUDED.started_at",
+            (discord_id, action, started_at)
+        )
+    }).await
+}
+
+/// Returns remaining cooldown for `action` if it was started less than `duration` ago
+pub async fn get_user_remaining_cooldown_in_cooldowns_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    action: String,
+    duration: Duration
+) -> Result<Option<Duration>, async_sqlite::Error> {
+    let started_at: Option<u64> = db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT started_at FROM cooldowns WHERE discord_id=?1 AND action=?2")?;
+        let mut rows = stmt.query((discord_id, action))?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None)
+        }
+    }).await?;
+
+    if started_at.is_none() {
+        return Ok(None);
+    }
+
+    let expires_at: u64 = started_at.unwrap() + duration.as_millis() as u64;
+    let now: u64 = now_millis();
+    if expires_at <= now {
+        return Ok(None);
+    }
+
+    Ok(Some(Duration::from_millis(expires_at - now)))
+}
+
+/// Checks and starts cooldown in one statement, so two invocations at once can't both get past it.
+/// Returns remaining cooldown if it's still active (and wasn't restarted), `None` if it was started.
+pub async fn try_start_user_cooldown_in_cooldowns_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    action: String,
+    duration: Duration
+) -> Result<Option<Duration>, async_sqlite::Error> {
+    let now: u64 = now_millis();
+    let duration: u64 = duration.as_millis() as u64;
+    let started_at: Option<u64> = db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let started: usize = conn.execute(
+            "INSERT INTO cooldowns (discord_id, action, started_at) VALUES (?1, ?2, ?3) ON CONFLICT DO UPDATE SET started_at = ?3 WHERE started_at <= ?3 - ?4",
+            (discord_id, &action, now, duration)
+        )?;
+        if started > 0 {
+            return Ok(None);
+        }
+        conn.query_row("SELECT started_at FROM cooldowns WHERE discord_id=?1 AND action=?2", (discord_id, &action), |row| row.get(0)).map(Some)
+    }).await?;
+
+    Ok(started_at.map(|started_at| Duration::from_millis((started_at + duration).saturating_sub(now))))
+}
+
+/// Resets all user's cooldowns, or only one if `action` is provided
+pub async fn reset_user_cooldowns_in_cooldowns_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    action: Option<String>
+) -> Result<usize, async_sqlite::Error> {
+    if action.is_some() {
+        return db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+            conn.execute(
+                "DELETE FROM cooldowns WHERE discord_id=?1 AND action=?2",
+                (discord_id, action.unwrap())
+            )
+        }).await;
+    }
+
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM cooldowns WHERE discord_id=?1",
+            [discord_id]
+        )
+    }).await
+}
+
+/// Deletes cooldowns that were started more than `max_duration` ago (so they can't be active anymore)
+pub async fn prune_expired_cooldowns_in_cooldowns_db(
+    db_client: &async_sqlite::Client,
+    max_duration: Duration
+) -> Result<usize, async_sqlite::Error> {
+    let oldest_possible: u64 = now_millis().saturating_sub(max_duration.as_millis() as u64);
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM cooldowns WHERE started_at < ?1",
+            [oldest_possible]
+        )
+    }).await
+}
@@ -1,8 +1,8 @@
-use std::io::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use tracing::error;
 
-use crate::RoleShopItem;
+use super::ledger::{apply_balance_change, LedgerReason};
 
 pub async fn prepare_eco_db(db_client: &async_sqlite::Client) {
     db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
@@ -18,6 +18,23 @@ pub async fn prepare_eco_db(db_client: &async_sqlite::Client) {
     }).await.expect("Failed to create economics table in Loopchan's Database");
 }
 
+pub async fn prepare_transfers_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS transfers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                sender_id INTEGER,
+                receiver_id INTEGER,
+                amount INTEGER,
+                fee INTEGER,
+                note TEXT,
+                created_at INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create transfers table in Loopchan's Database");
+}
+
 pub async fn create_user_in_eco_db(
     db_client: &async_sqlite::Client,
     discord_id: u64
@@ -69,17 +86,27 @@ pub async fn get_user_level_and_experience_in_eco_db(
     }).await
 }
 
-pub async fn build_level_leaderboard_from_eco_db(db_client: &async_sqlite::Client) -> Result<Vec<(u64, u64, u64)>, async_sqlite::Error> {
+// Leaderboard functions take `member_ids`, JSON array of IDs of members who are still in PTL server,
+// so members who left aren't shown or counted in placements. `None` includes everyone.
+const LEADERBOARD_MEMBERS_FILTER: &str = "(?1 IS NULL OR discord_id IN (SELECT value FROM json_each(?1)))";
+
+pub async fn build_level_leaderboard_from_eco_db(
+    db_client: &async_sqlite::Client,
+    member_ids: Option<String>,
+    limit: u64,
+    offset: u64
+) -> Result<Vec<(u64, u64, u64)>, async_sqlite::Error> {
     db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
         let query = format!(
-            "SELECT discord_id, level, experience FROM economics ORDER BY level DESC, experience DESC LIMIT 5",
+            "SELECT discord_id, level, experience FROM economics WHERE {} ORDER BY level DESC, experience DESC, discord_id LIMIT ?2 OFFSET ?3",
+            LEADERBOARD_MEMBERS_FILTER
         );
     
-        let mut binding = conn.prepare(&query).unwrap();
-        let mut rows = binding.query([]).unwrap();
+        let mut binding = conn.prepare(&query)?;
+        let mut rows = binding.query((member_ids, limit, offset))?;
         let mut leaderboard = Vec::new();
     
-        while let Ok(Some(row)) = rows.next() {
+        while let Some(row) = rows.next()? {
             let discord_id: u64 = row.get(0)?;
             let level: u64 = row.get(1)?;
             let experience: u64 = row.get(2)?;
@@ -90,30 +117,44 @@ pub async fn build_level_leaderboard_from_eco_db(db_client: &async_sqlite::Clien
     }).await
 }
 
+/// Returns `None` if user isn't on leaderboard
 pub async fn get_user_placement_in_level_leaderboard(
     db_client: &async_sqlite::Client,
-    discord_id: u64
-)-> Result<u8, async_sqlite::Error> {
+    discord_id: u64,
+    member_ids: Option<String>
+)-> Result<Option<u64>, async_sqlite::Error> {
     db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
-        conn.query_row(
-            "SELECT * FROM ( SELECT ROW_NUMBER() OVER (ORDER BY level DESC, experience DESC) AS RowNum, * FROM economics ) AS RowResults WHERE discord_id=?",
-            [discord_id],
-            |row| row.get(0),
-        )
+        let query = format!(
+            "SELECT RowNum FROM ( SELECT ROW_NUMBER() OVER (ORDER BY level DESC, experience DESC, discord_id) AS RowNum, discord_id FROM economics WHERE {} ) AS RowResults WHERE discord_id=?2",
+            LEADERBOARD_MEMBERS_FILTER
+        );
+
+        let mut binding = conn.prepare(&query)?;
+        let mut rows = binding.query((member_ids, discord_id))?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None)
+        }
     }).await
 }
 
-pub async fn build_balance_leaderboard_from_eco_db(db_client: &async_sqlite::Client) -> Result<Vec<(u64, u64)>, async_sqlite::Error> {
+pub async fn build_balance_leaderboard_from_eco_db(
+    db_client: &async_sqlite::Client,
+    member_ids: Option<String>,
+    limit: u64,
+    offset: u64
+) -> Result<Vec<(u64, u64)>, async_sqlite::Error> {
     db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
         let query = format!(
-            "SELECT discord_id, balance FROM economics ORDER BY balance DESC LIMIT 5",
+            "SELECT discord_id, balance FROM economics WHERE {} ORDER BY balance DESC, discord_id LIMIT ?2 OFFSET ?3",
+            LEADERBOARD_MEMBERS_FILTER
         );
     
-        let mut binding = conn.prepare(&query).unwrap();
-        let mut rows = binding.query([]).unwrap();
+        let mut binding = conn.prepare(&query)?;
+        let mut rows = binding.query((member_ids, limit, offset))?;
         let mut leaderboard = Vec::new();
     
-        while let Ok(Some(row)) = rows.next() {
+        while let Some(row) = rows.next()? {
             let discord_id: u64 = row.get(0)?;
             let balance: u64 = row.get(1)?;
             leaderboard.push((discord_id, balance));
@@ -123,16 +164,49 @@ pub async fn build_balance_leaderboard_from_eco_db(db_client: &async_sqlite::Cli
     }).await
 }
 
+/// Returns `None` if user isn't on leaderboard
 pub async fn get_user_placement_in_balance_leaderboard(
     db_client: &async_sqlite::Client,
-    discord_id: u64
-)-> Result<u8, async_sqlite::Error> {
+    discord_id: u64,
+    member_ids: Option<String>
+)-> Result<Option<u64>, async_sqlite::Error> {
     db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
-        conn.query_row(
-            "SELECT * FROM ( SELECT ROW_NUMBER() OVER (ORDER BY balance DESC) AS RowNum, * FROM economics ) AS RowResults WHERE discord_id=?",
-            [discord_id],
-            |row| row.get(0),
-        )
+        let query = format!(
+            "SELECT RowNum FROM ( SELECT ROW_NUMBER() OVER (ORDER BY balance DESC, discord_id) AS RowNum, discord_id FROM economics WHERE {} ) AS RowResults WHERE discord_id=?2",
+            LEADERBOARD_MEMBERS_FILTER
+        );
+
+        let mut binding = conn.prepare(&query)?;
+        let mut rows = binding.query((member_ids, discord_id))?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None)
+        }
+    }).await
+}
+
+pub async fn count_users_in_leaderboard_from_eco_db(
+    db_client: &async_sqlite::Client,
+    member_ids: Option<String>
+)-> Result<u64, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query = format!("SELECT COUNT(*) FROM economics WHERE {}", LEADERBOARD_MEMBERS_FILTER);
+        conn.query_row(&query, [member_ids], |row| row.get(0))
+    }).await
+}
+
+/// All balances sorted from lowest to highest, used for economy statistics
+pub async fn get_all_balances_in_eco_db(db_client: &async_sqlite::Client) -> Result<Vec<u64>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT balance FROM economics ORDER BY balance ASC")?;
+        let mut rows = stmt.query([])?;
+        let mut balances = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            balances.push(row.get(0)?);
+        }
+
+        Ok(balances)
     }).await
 }
 
@@ -176,53 +250,39 @@ pub async fn update_user_level_and_experience_in_eco_db(
     }).await;
 }
 
-pub async fn _update_user_balance_in_eco_db(
+/// Moves `amount` coins from sender to receiver and burns `fee` coins from sender in one transaction.
+/// Returns ID of transfer in `transfers` table, or `None` if sender has insufficient funds.
+pub async fn transfer_user_balance_in_eco_db(
     db_client: &async_sqlite::Client,
-    discord_id: u64,
-    balance: u64
-) -> Result<usize, async_sqlite::Error> {
-    return db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
-        conn.execute(
-            "UPDATE economics SET balance=(?2) WHERE discord_id=(?1)",
-            (discord_id, balance)
-        )
-    }).await;
-}
+    sender_id: u64,
+    receiver_id: u64,
+    amount: u64,
+    fee: u64,
+    note: Option<String>
+) -> Result<Option<i64>, async_sqlite::Error> {
+    let created_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
 
-pub async fn increment_user_balance_in_eco_db(
-    db_client: &async_sqlite::Client,
-    discord_id: u64,
-    increment: u64
-) -> Result<usize, async_sqlite::Error> {
-    return db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
-        conn.execute(
-            "UPDATE economics SET balance=balance+(?2) WHERE discord_id=(?1)",
-            (discord_id, increment)
-        )
-    }).await;
-}
+        transaction.execute(
+            "INSERT INTO transfers (sender_id, receiver_id, amount, fee, note, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (sender_id, receiver_id, amount, fee, note, created_at)
+        )?;
+        let transfer_id: i64 = transaction.last_insert_rowid();
+        let reference: String = format!("transfer:{}", transfer_id);
 
-pub async fn decrement_user_balance_in_eco_db(
-    db_client: &async_sqlite::Client,
-    discord_id: u64,
-    decrement: u64
-) -> Result<usize, async_sqlite::Error> {
-    return db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
-        conn.execute(
-            "UPDATE economics SET balance=balance-(?2) WHERE discord_id=(?1)",
-            (discord_id, decrement)
-        )
-    }).await;
-}
-
-pub async fn get_roleshopitem_by_id(
-    id: u64, shop_items: &Vec<RoleShopItem>
-) -> Result<&RoleShopItem, Error> {
-    for item in shop_items {
-        if item.id == id {
-            return Ok(item);
+        let debited: Option<u64> = apply_balance_change(&transaction, sender_id, -((amount+fee) as i64), LedgerReason::Transfer, Some(reference.clone()))?;
+        if debited.is_none() {
+            return Ok(None); // Transaction is rolled back on drop
         }
-    }
 
-    return Err(Error::new(std::io::ErrorKind::Other, format!("Not found RoleShopItem by provided ID: {}", id))); // How to make error properly??
+        transaction.execute(
+            "INSERT INTO economics (discord_id, balance, level, experience) VALUES (?1, ?2, ?3, ?4) ON CONFLICT DO NOTHING",
+            (receiver_id, 0, 1, 0)
+        )?;
+        apply_balance_change(&transaction, receiver_id, amount as i64, LedgerReason::Transfer, Some(reference))?;
+
+        transaction.commit()?;
+        Ok(Some(transfer_id))
+    }).await
 }
\ No newline at end of file
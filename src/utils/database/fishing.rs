@@ -1,3 +1,5 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::DataFish;
 
 pub async fn prepare_fishing_db(db_client: &async_sqlite::Client) {
@@ -15,6 +17,51 @@ pub async fn prepare_fishing_db(db_client: &async_sqlite::Client) {
     }).await.expect("Failed to create fishing table in Loopchan's Database");
 }
 
+// Every fish caught with `/fishing fish` (given fishes aren't catches), fishes table only has what members own
+pub async fn prepare_fish_catches_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        let existed: bool = conn.query_row("SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type='table' AND name='fish_catches')", [], |row| row.get(0))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fish_catches (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                discord_id INTEGER,
+                type TEXT,
+                size REAL,
+                caught_at INTEGER
+            )",
+            []
+        )?;
+        if !existed {
+            // Fishes caught before catch log existed, given ones can't be told apart from them
+            conn.execute("INSERT INTO fish_catches (discord_id, type, size, caught_at) SELECT discord_id, type, size, NULL FROM fishes", [])?;
+        }
+        Ok(())
+    }).await.expect("Failed to create fish_catches table in Loopchan's Database");
+}
+
+/// Gives caught fish and records it in catch log
+pub async fn catch_fish_in_fishing_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    fish: DataFish
+) -> Result<usize, async_sqlite::Error> {
+    let caught_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+        let given: usize = transaction.execute(
+            "INSERT INTO fishes (discord_id, uuid, type, modifiers, size) VALUES (?, ?, ?, ?, ?) ON CONFLICT DO NOTHING",
+            (discord_id, &fish.uuid, &fish.r#type, &fish.modifiers, fish.size)
+        )?;
+        transaction.execute(
+            "INSERT INTO fish_catches (discord_id, type, size, caught_at) VALUES (?, ?, ?, ?)",
+            (discord_id, &fish.r#type, fish.size, caught_at)
+        )?;
+        transaction.commit()?;
+        Ok(given)
+    }).await
+}
+
+/// Gives fish without recording it as catch (`/fishing give_fish`)
 pub async fn give_fish_to_user_in_fishing_db(
     db_client: &async_sqlite::Client,
     discord_id: u64,
@@ -52,4 +99,30 @@ pub async fn get_user_fishes_in_fishing_db(
     
         Ok(inventory)
     }).await
+}
+
+/// Amount of fishes ever caught
+pub async fn count_catches_in_fishing_db(
+    db_client: &async_sqlite::Client
+) -> Result<u64, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.query_row("SELECT COUNT(*) FROM fish_catches", [], |row| row.get(0))
+    }).await
+}
+
+/// Amount of fishes of every type ever caught
+pub async fn count_catches_by_type_in_fishing_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<(String, u64)>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT type, COUNT(*) AS amount FROM fish_catches GROUP BY type ORDER BY amount DESC")?;
+        let mut rows = stmt.query([])?;
+        let mut counts = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            counts.push((row.get(0)?, row.get(1)?));
+        }
+
+        Ok(counts)
+    }).await
 }
\ No newline at end of file
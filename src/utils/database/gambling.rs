@@ -0,0 +1,257 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::ledger::{apply_balance_change, LedgerReason};
+
+#[derive(Debug)]
+pub enum BetResult {
+    Placed(u64), // Balance after bet (and payout, if bet was settled right away)
+    InsufficientFunds,
+    LossCapReached
+}
+
+pub async fn prepare_gambling_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS gambling_stats (
+                discord_id INTEGER,
+                day INTEGER,
+                wagered INTEGER,
+                won INTEGER,
+                PRIMARY KEY (discord_id, day)
+            )",
+            []
+        )
+    }).await.expect("Failed to create gambling_stats table in Loopchan's Database");
+}
+
+// Bets of games that are decided later (blackjack), so they can be refunded if game is lost on restart
+pub async fn prepare_open_bets_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS open_bets (
+                discord_id INTEGER,
+                game TEXT,
+                bet INTEGER,
+                created_at INTEGER,
+                PRIMARY KEY (discord_id, game)
+            )",
+            []
+        )
+    }).await.expect("Failed to create open_bets table in Loopchan's Database");
+}
+
+// Members who opted out of gambling themselves, or were banned from it by staff
+pub async fn prepare_gambling_blocks_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS gambling_blocks (
+                discord_id INTEGER PRIMARY KEY,
+                banned INTEGER,
+                reason TEXT,
+                created_at INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create gambling_blocks table in Loopchan's Database");
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn today() -> u64 {
+    now_secs() / 86400
+}
+
+fn place_bet(
+    conn: &async_sqlite::rusqlite::Connection,
+    discord_id: u64,
+    bet: u64,
+    game: &str,
+    loss_cap: Option<u64>
+) -> Result<BetResult, async_sqlite::rusqlite::Error> {
+    let day: u64 = today();
+
+    if loss_cap.is_some() {
+        let lost_today: i64 = conn.query_row(
+            "SELECT COALESCE(SUM(wagered - won), 0) FROM gambling_stats WHERE discord_id=?1 AND day=?2",
+            (discord_id, day),
+            |row| row.get(0)
+        )?;
+        // Worst case user loses the whole bet
+        if lost_today.max(0) as u64 + bet > loss_cap.unwrap() {
+            return Ok(BetResult::LossCapReached);
+        }
+    }
+
+    let balance_after: Option<u64> = apply_balance_change(conn, discord_id, -(bet as i64), LedgerReason::Gamble, Some(format!("{}:bet", game)))?;
+    if balance_after.is_none() {
+        return Ok(BetResult::InsufficientFunds);
+    }
+
+    conn.execute(
+        "INSERT INTO gambling_stats (discord_id, day, wagered, won) VALUES (?1, ?2, ?3, 0) ON CONFLICT DO UPDATE SET wagered = wagered + EXCLUDED.wagered",
+        (discord_id, day, bet)
+    )?;
+
+    Ok(BetResult::Placed(balance_after.unwrap()))
+}
+
+fn pay_out(
+    conn: &async_sqlite::rusqlite::Connection,
+    discord_id: u64,
+    payout: u64,
+    game: &str
+) -> Result<Option<u64>, async_sqlite::rusqlite::Error> {
+    let balance_after: Option<u64> = apply_balance_change(conn, discord_id, payout as i64, LedgerReason::Gamble, Some(format!("{}:payout", game)))?;
+
+    conn.execute(
+        "INSERT INTO gambling_stats (discord_id, day, wagered, won) VALUES (?1, ?2, 0, ?3) ON CONFLICT DO UPDATE SET won = won + EXCLUDED.won",
+        (discord_id, today(), payout)
+    )?;
+
+    Ok(balance_after)
+}
+
+/// Takes bet for games that are decided later (blackjack) and keeps it open, see `pay_out_bet_in_gambling_db`
+pub async fn place_bet_in_gambling_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    bet: u64,
+    game: &'static str,
+    loss_cap: Option<u64>
+) -> Result<BetResult, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+        let result: BetResult = place_bet(&transaction, discord_id, bet, game, loss_cap)?;
+        if matches!(result, BetResult::Placed(_)) {
+            transaction.execute(
+                "INSERT INTO open_bets (discord_id, game, bet, created_at) VALUES (?1, ?2, ?3, ?4)",
+                (discord_id, game, bet, now_secs())
+            )?;
+            transaction.commit()?;
+        }
+        Ok(result)
+    }).await
+}
+
+/// Closes open bet and pays out `payout` (can be 0 if it was lost), `false` if bet wasn't open (already paid out or refunded)
+pub async fn pay_out_bet_in_gambling_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    payout: u64,
+    game: &'static str
+) -> Result<bool, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+        let closed: usize = transaction.execute("DELETE FROM open_bets WHERE discord_id=?1 AND game=?2", (discord_id, game))?;
+        if closed == 0 {
+            return Ok(false);
+        }
+        if payout > 0 {
+            pay_out(&transaction, discord_id, payout, game)?;
+        }
+        transaction.commit()?;
+        Ok(true)
+    }).await
+}
+
+/// Refunds every open bet and returns them as `(discord_id, game, bet)`, used on startup since games kept in memory don't survive restart.
+/// Refunded bets don't count towards daily loss cap.
+pub async fn refund_open_bets_in_gambling_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<(u64, String, u64)>, async_sqlite::Error> {
+    db_client.conn_mut(|conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+        let open_bets: Vec<(u64, String, u64, u64)> = {
+            let mut stmt = transaction.prepare("SELECT discord_id, game, bet, created_at FROM open_bets")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+            rows.collect::<Result<Vec<(u64, String, u64, u64)>, async_sqlite::rusqlite::Error>>()?
+        };
+
+        for (discord_id, game, bet, created_at) in &open_bets {
+            apply_balance_change(&transaction, *discord_id, *bet as i64, LedgerReason::Gamble, Some(format!("{}:refund", game)))?;
+            transaction.execute(
+                "UPDATE gambling_stats SET wagered = MAX(wagered - ?3, 0) WHERE discord_id=?1 AND day=?2",
+                (discord_id, created_at / 86400, bet)
+            )?;
+        }
+        transaction.execute("DELETE FROM open_bets", [])?;
+        transaction.commit()?;
+
+        Ok(open_bets.into_iter().map(|(discord_id, game, bet, _)| (discord_id, game, bet)).collect())
+    }).await
+}
+
+/// Takes bet and pays out winnings in one transaction, for games that are decided instantly
+pub async fn settle_bet_in_gambling_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    bet: u64,
+    payout: u64,
+    game: &'static str,
+    loss_cap: Option<u64>
+) -> Result<BetResult, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let result: BetResult = place_bet(&transaction, discord_id, bet, game, loss_cap)?;
+        if !matches!(result, BetResult::Placed(_)) {
+            return Ok(result);
+        }
+
+        let mut balance_after: BetResult = result;
+        if payout > 0 {
+            let paid_out: Option<u64> = pay_out(&transaction, discord_id, payout, game)?;
+            balance_after = BetResult::Placed(paid_out.unwrap_or(0));
+        }
+
+        transaction.commit()?;
+        Ok(balance_after)
+    }).await
+}
+
+/// Returns `(banned, reason, created_at)` if user can't gamble
+pub async fn get_user_gambling_block_in_gambling_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<Option<(bool, Option<String>, u64)>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT banned, reason, created_at FROM gambling_blocks WHERE discord_id=?")?;
+        let mut rows = stmt.query([discord_id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some((row.get(0)?, row.get(1)?, row.get(2)?))),
+            None => Ok(None)
+        }
+    }).await
+}
+
+/// Staff ban always replaces self opt-out, but self opt-out never replaces staff ban
+pub async fn block_user_in_gambling_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    banned: bool,
+    reason: Option<String>
+) -> Result<usize, async_sqlite::Error> {
+    let created_at: u64 = now_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO gambling_blocks (discord_id, banned, reason, created_at) VALUES (?1, ?2, ?3, ?4) ON CONFLICT DO UPDATE SET banned = EXCLUDED.banned, reason = EXCLUDED.reason, created_at = EXCLUDED.created_at WHERE EXCLUDED.banned OR NOT banned",
+            (discord_id, banned, reason, created_at)
+        )
+    }).await
+}
+
+/// Removes only self opt-out, unless `banned` is set
+pub async fn unblock_user_in_gambling_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    banned: bool
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM gambling_blocks WHERE discord_id=?1 AND (?2 OR NOT banned)",
+            (discord_id, banned)
+        )
+    }).await
+}
@@ -0,0 +1,233 @@
+use crate::{DataEquippedItem, DataInventoryItem};
+
+use super::ledger::{apply_balance_change, LedgerReason};
+
+pub async fn prepare_inventory_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS inventory (
+                discord_id INTEGER,
+                item_id TEXT,
+                quantity INTEGER,
+                PRIMARY KEY (discord_id, item_id)
+            )",
+            []
+        )
+    }).await.expect("Failed to create inventory table in Loopchan's Database");
+}
+
+// Equipped cosmetics and active consumables (like fishing bait), one item per slot
+pub async fn prepare_equipped_items_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS equipped_items (
+                discord_id INTEGER,
+                slot TEXT,
+                item_id TEXT,
+                remaining_uses INTEGER,
+                PRIMARY KEY (discord_id, slot)
+            )",
+            []
+        )
+    }).await.expect("Failed to create equipped_items table in Loopchan's Database");
+}
+
+pub async fn give_item_to_user_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    item_id: String,
+    quantity: u64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO inventory (discord_id, item_id, quantity) VALUES (?1, ?2, ?3) ON CONFLICT DO UPDATE SET quantity = quantity + EXCLUDED.quantity",
+            (discord_id, item_id, quantity)
+        )
+    }).await
+}
+
+fn take_item(
+    conn: &async_sqlite::rusqlite::Connection,
+    discord_id: u64,
+    item_id: &String,
+    quantity: u64
+) -> Result<bool, async_sqlite::rusqlite::Error> {
+    let changed: usize = conn.execute(
+        "UPDATE inventory SET quantity = quantity - ?3 WHERE discord_id=?1 AND item_id=?2 AND quantity>=?3",
+        (discord_id, item_id, quantity)
+    )?;
+    if changed == 0 {
+        return Ok(false);
+    }
+
+    conn.execute(
+        "DELETE FROM inventory WHERE discord_id=?1 AND item_id=?2 AND quantity<=0",
+        (discord_id, item_id)
+    )?;
+    Ok(true)
+}
+
+/// Returns `false` if user doesn't have enough of this item
+pub async fn take_item_from_user_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    item_id: String,
+    quantity: u64
+) -> Result<bool, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+        let taken: bool = take_item(&transaction, discord_id, &item_id, quantity)?;
+        if taken {
+            transaction.commit()?;
+        }
+        Ok(taken)
+    }).await
+}
+
+/// Takes `price` coins and gives `quantity` items in one transaction.
+/// Returns new balance, or `None` if user has insufficient funds.
+pub async fn buy_item_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    item_id: String,
+    price: u64,
+    quantity: u64
+) -> Result<Option<u64>, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let balance_after: Option<u64> = apply_balance_change(&transaction, discord_id, -(price as i64), LedgerReason::Shop, Some(format!("item:{}x{}", item_id, quantity)))?;
+        if balance_after.is_none() {
+            return Ok(None);
+        }
+
+        transaction.execute(
+            "INSERT INTO inventory (discord_id, item_id, quantity) VALUES (?1, ?2, ?3) ON CONFLICT DO UPDATE SET quantity = quantity + EXCLUDED.quantity",
+            (discord_id, item_id, quantity)
+        )?;
+
+        transaction.commit()?;
+        Ok(balance_after)
+    }).await
+}
+
+pub async fn get_user_items_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<Vec<DataInventoryItem>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT item_id, quantity FROM inventory WHERE discord_id=? ORDER BY item_id")?;
+        let mut rows = stmt.query([discord_id])?;
+        let mut items = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            items.push(
+                DataInventoryItem {
+                    item_id: row.get(0)?,
+                    quantity: row.get(1)?,
+                }
+            );
+        }
+
+        Ok(items)
+    }).await
+}
+
+/// Puts item into `slot`, replacing whatever was there.
+/// If `consume` is set, one item is taken from inventory in the same transaction (returns `false` if user has none).
+pub async fn equip_item_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    slot: String,
+    item_id: String,
+    remaining_uses: Option<u64>,
+    consume: bool
+) -> Result<bool, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        if consume && !take_item(&transaction, discord_id, &item_id, 1)? {
+            return Ok(false);
+        }
+
+        transaction.execute(
+            "INSERT INTO equipped_items (discord_id, slot, item_id, remaining_uses) VALUES (?1, ?2, ?3, ?4) ON CONFLICT DO UPDATE SET item_id = EXCLUDED.item_id, remaining_uses = EXCLUDED.remaining_uses",
+            (discord_id, slot, item_id, remaining_uses)
+        )?;
+
+        transaction.commit()?;
+        Ok(true)
+    }).await
+}
+
+pub async fn unequip_item_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    slot: String
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM equipped_items WHERE discord_id=?1 AND slot=?2",
+            (discord_id, slot)
+        )
+    }).await
+}
+
+pub async fn get_user_equipped_items_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<Vec<DataEquippedItem>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT slot, item_id, remaining_uses FROM equipped_items WHERE discord_id=?")?;
+        let mut rows = stmt.query([discord_id])?;
+        let mut items = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            items.push(
+                DataEquippedItem {
+                    slot: row.get(0)?,
+                    item_id: row.get(1)?,
+                    remaining_uses: row.get(2)?,
+                }
+            );
+        }
+
+        Ok(items)
+    }).await
+}
+
+/// Uses up one charge of item in `slot`, removing it once there are no charges left.
+/// Returns ID of used item, or `None` if slot is empty.
+pub async fn use_equipped_item_in_inventory_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    slot: String
+) -> Result<Option<String>, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let item_id: Option<String> = {
+            let mut stmt = transaction.prepare("SELECT item_id FROM equipped_items WHERE discord_id=?1 AND slot=?2")?;
+            let mut rows = stmt.query((discord_id, &slot))?;
+            match rows.next()? {
+                Some(row) => Some(row.get(0)?),
+                None => None
+            }
+        };
+        if item_id.is_none() {
+            return Ok(None);
+        }
+
+        transaction.execute(
+            "UPDATE equipped_items SET remaining_uses = remaining_uses - 1 WHERE discord_id=?1 AND slot=?2 AND remaining_uses IS NOT NULL",
+            (discord_id, &slot)
+        )?;
+        transaction.execute(
+            "DELETE FROM equipped_items WHERE discord_id=?1 AND slot=?2 AND remaining_uses<=0",
+            (discord_id, &slot)
+        )?;
+
+        transaction.commit()?;
+        Ok(item_id)
+    }).await
+}
@@ -0,0 +1,205 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{DataLedgerDailyTotal, DataLedgerEntry};
+
+// Every balance change goes through `apply_balance_change` so there is always a trail of why it happened.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedgerReason {
+    Work,
+    Shop,
+    Sale,
+    Transfer,
+    Gamble,
+    Admin,
+}
+
+impl LedgerReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerReason::Work => "work",
+            LedgerReason::Shop => "shop",
+            LedgerReason::Sale => "sale",
+            LedgerReason::Transfer => "transfer",
+            LedgerReason::Gamble => "gamble",
+            LedgerReason::Admin => "admin",
+        }
+    }
+}
+
+pub async fn prepare_ledger_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS ledger (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                discord_id INTEGER,
+                amount INTEGER,
+                balance_after INTEGER,
+                reason TEXT,
+                reference TEXT,
+                created_at INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create ledger table in Loopchan's Database");
+}
+
+/// Changes user's balance by `amount` and records it in ledger.
+/// Negative amounts are only applied if user has enough coins, otherwise returns `None`.
+/// Meant to be called inside of a transaction (see `change_user_balance_in_eco_db`).
+pub fn apply_balance_change(
+    conn: &async_sqlite::rusqlite::Connection,
+    discord_id: u64,
+    amount: i64,
+    reason: LedgerReason,
+    reference: Option<String>
+) -> Result<Option<u64>, async_sqlite::rusqlite::Error> {
+    let changed: usize = if amount < 0 {
+        conn.execute(
+            "UPDATE economics SET balance=balance+(?2) WHERE discord_id=(?1) AND balance>=(?3)",
+            (discord_id, amount, amount.unsigned_abs())
+        )?
+    } else {
+        conn.execute(
+            "UPDATE economics SET balance=balance+(?2) WHERE discord_id=(?1)",
+            (discord_id, amount)
+        )?
+    };
+
+    if changed == 0 {
+        return Ok(None);
+    }
+
+    let balance_after: u64 = conn.query_row(
+        "SELECT balance FROM economics WHERE discord_id=?",
+        [discord_id],
+        |row| row.get(0)
+    )?;
+
+    let created_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    conn.execute(
+        "INSERT INTO ledger (discord_id, amount, balance_after, reason, reference, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (discord_id, amount, balance_after, reason.as_str(), reference, created_at)
+    )?;
+
+    Ok(Some(balance_after))
+}
+
+/// Changes user's balance and records it in ledger.
+/// Returns new balance, or `None` if user has insufficient funds (or doesn't exist in economics).
+pub async fn change_user_balance_in_eco_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    amount: i64,
+    reason: LedgerReason,
+    reference: Option<String>
+) -> Result<Option<u64>, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+        let balance_after: Option<u64> = apply_balance_change(&transaction, discord_id, amount, reason, reference)?;
+        if balance_after.is_some() {
+            transaction.commit()?;
+        }
+        Ok(balance_after)
+    }).await
+}
+
+fn ledger_entry_from_row(row: &async_sqlite::rusqlite::Row) -> Result<DataLedgerEntry, async_sqlite::rusqlite::Error> {
+    Ok(DataLedgerEntry {
+        id: row.get(0)?,
+        discord_id: row.get(1)?,
+        amount: row.get(2)?,
+        balance_after: row.get(3)?,
+        reason: row.get(4)?,
+        reference: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+const LEDGER_ENTRY_COLUMNS: &str = "id, discord_id, amount, balance_after, reason, reference, created_at";
+
+pub async fn get_user_entries_in_ledger_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    limit: u64,
+    offset: u64
+) -> Result<Vec<DataLedgerEntry>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query = format!("SELECT {} FROM ledger WHERE discord_id=?1 ORDER BY id DESC LIMIT ?2 OFFSET ?3", LEDGER_ENTRY_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query((discord_id, limit, offset))?;
+        let mut entries = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            entries.push(ledger_entry_from_row(row)?);
+        }
+
+        Ok(entries)
+    }).await
+}
+
+/// Query ledger by user and/or time range (unix seconds, inclusive)
+pub async fn query_entries_in_ledger_db(
+    db_client: &async_sqlite::Client,
+    discord_id: Option<u64>,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: u64
+) -> Result<Vec<DataLedgerEntry>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query = format!(
+            "SELECT {} FROM ledger WHERE (?1 IS NULL OR discord_id=?1) AND (?2 IS NULL OR created_at>=?2) AND (?3 IS NULL OR created_at<=?3) ORDER BY id DESC LIMIT ?4",
+            LEDGER_ENTRY_COLUMNS
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query((discord_id, since, until, limit))?;
+        let mut entries = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            entries.push(ledger_entry_from_row(row)?);
+        }
+
+        Ok(entries)
+    }).await
+}
+
+/// Coins added (minted) and removed (burned) per UTC day and reason since `since` (unix seconds)
+pub async fn get_daily_totals_in_ledger_db(
+    db_client: &async_sqlite::Client,
+    since: u64
+) -> Result<Vec<DataLedgerDailyTotal>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare(
+            "SELECT created_at / 86400 AS day, reason, SUM(MAX(amount, 0)), SUM(MAX(-amount, 0)) FROM ledger WHERE created_at>=? GROUP BY day, reason ORDER BY day"
+        )?;
+        let mut rows = stmt.query([since])?;
+        let mut totals = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            totals.push(
+                DataLedgerDailyTotal {
+                    day: row.get(0)?,
+                    reason: row.get(1)?,
+                    minted: row.get(2)?,
+                    burned: row.get(3)?,
+                }
+            );
+        }
+
+        Ok(totals)
+    }).await
+}
+
+/// How many members earned coins with `/eco work` since `since` (unix seconds)
+pub async fn count_active_earners_in_ledger_db(
+    db_client: &async_sqlite::Client,
+    since: u64
+) -> Result<u64, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.query_row(
+            "SELECT COUNT(DISTINCT discord_id) FROM ledger WHERE reason=?1 AND amount>0 AND created_at>=?2",
+            (LedgerReason::Work.as_str(), since),
+            |row| row.get(0)
+        )
+    }).await
+}
\ No newline at end of file
@@ -0,0 +1,134 @@
+use crate::DataMaintenanceWindow;
+
+// Manual maintenance (`/adm maintenance on`) is kept in settings table under `maintenance.` keys
+pub async fn prepare_maintenance_windows_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS maintenance_windows (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                starts_at INTEGER,
+                ends_at INTEGER,
+                message TEXT,
+                created_by INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create maintenance_windows table in Loopchan's Database");
+}
+
+// `id` is either user or role ID, depending on `kind` ("user" or "role")
+pub async fn prepare_maintenance_allowlist_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS maintenance_allowlist (
+                id INTEGER PRIMARY KEY,
+                kind TEXT
+            )",
+            []
+        )
+    }).await.expect("Failed to create maintenance_allowlist table in Loopchan's Database");
+}
+
+/// Returns window ID
+pub async fn create_window_in_maintenance_db(
+    db_client: &async_sqlite::Client,
+    starts_at: u64,
+    ends_at: u64,
+    message: Option<String>,
+    created_by: u64
+) -> Result<i64, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO maintenance_windows (starts_at, ends_at, message, created_by) VALUES (?1, ?2, ?3, ?4)",
+            (starts_at, ends_at, message, created_by)
+        )?;
+        Ok(conn.last_insert_rowid())
+    }).await
+}
+
+pub async fn remove_window_in_maintenance_db(
+    db_client: &async_sqlite::Client,
+    id: i64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM maintenance_windows WHERE id=?",
+            [id]
+        )
+    }).await
+}
+
+pub async fn remove_ended_windows_in_maintenance_db(
+    db_client: &async_sqlite::Client,
+    now: u64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM maintenance_windows WHERE ends_at<=?",
+            [now]
+        )
+    }).await
+}
+
+/// Soonest first
+pub async fn get_windows_in_maintenance_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<DataMaintenanceWindow>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT id, starts_at, ends_at, message, created_by FROM maintenance_windows ORDER BY starts_at")?;
+        let mut rows = stmt.query([])?;
+
+        let mut windows: Vec<DataMaintenanceWindow> = vec![];
+        while let Some(row) = rows.next()? {
+            windows.push(DataMaintenanceWindow {
+                id: row.get(0)?,
+                starts_at: row.get(1)?,
+                ends_at: row.get(2)?,
+                message: row.get(3)?,
+                created_by: row.get(4)?
+            });
+        }
+        Ok(windows)
+    }).await
+}
+
+pub async fn add_to_allowlist_in_maintenance_db(
+    db_client: &async_sqlite::Client,
+    id: u64,
+    kind: &'static str
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT OR REPLACE INTO maintenance_allowlist (id, kind) VALUES (?1, ?2)",
+            (id, kind)
+        )
+    }).await
+}
+
+pub async fn remove_from_allowlist_in_maintenance_db(
+    db_client: &async_sqlite::Client,
+    id: u64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM maintenance_allowlist WHERE id=?",
+            [id]
+        )
+    }).await
+}
+
+/// (ID, kind)
+pub async fn get_allowlist_in_maintenance_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<(u64, String)>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT id, kind FROM maintenance_allowlist")?;
+        let mut rows = stmt.query([])?;
+
+        let mut allowlist: Vec<(u64, String)> = vec![];
+        while let Some(row) = rows.next()? {
+            allowlist.push((row.get(0)?, row.get(1)?));
+        }
+        Ok(allowlist)
+    }).await
+}
@@ -0,0 +1,44 @@
+// Members who left PTL server, kept for anti-raid checks (e.g. lots of quick leaves in short time)
+pub async fn prepare_member_leaves_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS member_leaves (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                discord_id INTEGER,
+                joined_at INTEGER,
+                left_at INTEGER,
+                quick_leave INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create member_leaves table in Loopchan's Database");
+}
+
+/// `joined_at` isn't known if member wasn't cached
+pub async fn log_member_leave_in_members_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    joined_at: Option<u64>,
+    left_at: u64,
+    quick_leave: bool
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO member_leaves (discord_id, joined_at, left_at, quick_leave) VALUES (?1, ?2, ?3, ?4)",
+            (discord_id, joined_at, left_at, quick_leave)
+        )
+    }).await
+}
+
+pub async fn count_quick_leaves_in_members_db(
+    db_client: &async_sqlite::Client,
+    since: u64
+) -> Result<u64, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.query_row(
+            "SELECT COUNT(*) FROM member_leaves WHERE quick_leave AND left_at >= ?",
+            [since],
+            |row| row.get(0)
+        )
+    }).await
+}
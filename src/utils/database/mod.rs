@@ -4,6 +4,17 @@ pub mod linking;
 pub mod economy;
 pub mod lastfm;
 pub mod fishing;
+pub mod cooldowns;
+pub mod ledger;
+pub mod roleshop;
+pub mod inventory;
+pub mod gambling;
+pub mod members;
+pub mod settings;
+pub mod blacklist;
+pub mod moderation;
+pub mod audit;
+pub mod maintenance;
 
 const DEFAULT_DATABASE_PATH: &'static str = "loopchan.db";
 
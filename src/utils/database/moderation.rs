@@ -0,0 +1,95 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::DataModCase;
+
+// Case number is `id`, so cases are numbered across all members
+pub async fn prepare_mod_cases_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS mod_cases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                discord_id INTEGER,
+                moderator INTEGER,
+                action TEXT,
+                reason TEXT,
+                duration INTEGER,
+                created_at INTEGER
+            )",
+            []
+        )
+    }).await.expect("Failed to create mod_cases table in Loopchan's Database");
+}
+
+/// Returns case number, `duration` is in seconds
+pub async fn create_case_in_moderation_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    moderator: u64,
+    action: &'static str,
+    reason: Option<String>,
+    duration: Option<u64>
+) -> Result<i64, async_sqlite::Error> {
+    let created_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO mod_cases (discord_id, moderator, action, reason, duration, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (discord_id, moderator, action, reason, duration, created_at)
+        )?;
+        Ok(conn.last_insert_rowid())
+    }).await
+}
+
+/// Used when action itself failed, so case doesn't claim it happened
+pub async fn remove_case_in_moderation_db(
+    db_client: &async_sqlite::Client,
+    id: i64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM mod_cases WHERE id=?",
+            [id]
+        )
+    }).await
+}
+
+/// Newest cases first
+pub async fn get_user_cases_in_moderation_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    limit: u64
+) -> Result<Vec<DataModCase>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT id, moderator, action, reason, duration, created_at FROM mod_cases WHERE discord_id=?1 ORDER BY id DESC LIMIT ?2")?;
+        let mut rows = stmt.query((discord_id, limit))?;
+
+        let mut cases: Vec<DataModCase> = vec![];
+        while let Some(row) = rows.next()? {
+            cases.push(DataModCase {
+                id: row.get(0)?,
+                moderator: row.get(1)?,
+                action: row.get(2)?,
+                reason: row.get(3)?,
+                duration: row.get(4)?,
+                created_at: row.get(5)?
+            });
+        }
+        Ok(cases)
+    }).await
+}
+
+/// How many cases of each action user has
+pub async fn count_user_cases_in_moderation_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<Vec<(String, u64)>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT action, COUNT(*) FROM mod_cases WHERE discord_id=? GROUP BY action ORDER BY action")?;
+        let mut rows = stmt.query([discord_id])?;
+
+        let mut counts: Vec<(String, u64)> = vec![];
+        while let Some(row) = rows.next()? {
+            counts.push((row.get(0)?, row.get(1)?));
+        }
+        Ok(counts)
+    }).await
+}
@@ -0,0 +1,294 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{DataRolePurchase, RoleShopItem};
+
+use super::ledger::{apply_balance_change, LedgerReason};
+
+#[derive(Debug)]
+pub enum RolePurchaseResult {
+    Purchased(DataRolePurchase),
+    InsufficientFunds,
+    OutOfStock,
+}
+
+pub async fn prepare_roleshop_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS role_purchases (
+                discord_id INTEGER,
+                role_id INTEGER,
+                price INTEGER,
+                purchased_at INTEGER,
+                expires_at INTEGER,
+                PRIMARY KEY (discord_id, role_id)
+            )",
+            []
+        )
+    }).await.expect("Failed to create role_purchases table in Loopchan's Database");
+}
+
+// Role shop catalogue, seeded from `economy.shop_items` in Config.toml on first start and managed with `/adm shop` afterwards
+pub async fn prepare_shop_items_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS shop_items (
+                id INTEGER PRIMARY KEY,
+                display_name TEXT,
+                icon_id INTEGER,
+                icon_name TEXT,
+                description TEXT,
+                price INTEGER,
+                duration INTEGER,
+                min_level INTEGER,
+                stock INTEGER,
+                sell_back REAL
+            )",
+            []
+        )
+    }).await.expect("Failed to create shop_items table in Loopchan's Database");
+}
+
+fn save_shop_item(conn: &async_sqlite::rusqlite::Connection, item: &RoleShopItem) -> Result<usize, async_sqlite::rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO shop_items (id, display_name, icon_id, icon_name, description, price, duration, min_level, stock, sell_back) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        ON CONFLICT DO UPDATE SET display_name = EXCLUDED.display_name, icon_id = EXCLUDED.icon_id, icon_name = EXCLUDED.icon_name, description = EXCLUDED.description, price = EXCLUDED.price, duration = EXCLUDED.duration, min_level = EXCLUDED.min_level, stock = EXCLUDED.stock, sell_back = EXCLUDED.sell_back",
+        (item.id, &item.display_name, item.icon_id, &item.icon_name, &item.description, item.price, item.duration, item.min_level, item.stock, item.sell_back)
+    )
+}
+
+fn shop_item_from_row(row: &async_sqlite::rusqlite::Row) -> Result<RoleShopItem, async_sqlite::rusqlite::Error> {
+    Ok(RoleShopItem {
+        id: row.get(0)?,
+        display_name: row.get(1)?,
+        icon_id: row.get(2)?,
+        icon_name: row.get(3)?,
+        description: row.get(4)?,
+        price: row.get(5)?,
+        duration: row.get(6)?,
+        min_level: row.get(7)?,
+        stock: row.get(8)?,
+        sell_back: row.get(9)?,
+    })
+}
+
+const SHOP_ITEM_COLUMNS: &str = "id, display_name, icon_id, icon_name, description, price, duration, min_level, stock, sell_back";
+
+/// Copies items from Config.toml into database, only if catalogue is empty (so items removed with `/adm shop` don't come back)
+pub async fn import_shop_items_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    items: Vec<RoleShopItem>
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let existing: u64 = transaction.query_row("SELECT COUNT(*) FROM shop_items", [], |row| row.get(0))?;
+        if existing > 0 {
+            return Ok(0);
+        }
+
+        for item in &items {
+            save_shop_item(&transaction, item)?;
+        }
+
+        transaction.commit()?;
+        Ok(items.len())
+    }).await
+}
+
+/// Adds new item or replaces existing one with the same role ID
+pub async fn save_shop_item_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    item: RoleShopItem
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        save_shop_item(conn, &item)
+    }).await
+}
+
+pub async fn remove_shop_item_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    role_id: u64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM shop_items WHERE id=?",
+            [role_id]
+        )
+    }).await
+}
+
+pub async fn get_shop_items_in_roleshop_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<RoleShopItem>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query = format!("SELECT {} FROM shop_items ORDER BY price, id", SHOP_ITEM_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query([])?;
+        let mut items = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            items.push(shop_item_from_row(row)?);
+        }
+
+        Ok(items)
+    }).await
+}
+
+pub async fn get_shop_item_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    role_id: u64
+) -> Result<Option<RoleShopItem>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let query = format!("SELECT {} FROM shop_items WHERE id=?", SHOP_ITEM_COLUMNS);
+        let mut stmt = conn.prepare(&query)?;
+        let mut rows = stmt.query([role_id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(shop_item_from_row(row)?)),
+            None => Ok(None)
+        }
+    }).await
+}
+
+fn purchase_from_row(row: &async_sqlite::rusqlite::Row) -> Result<DataRolePurchase, async_sqlite::rusqlite::Error> {
+    Ok(DataRolePurchase {
+        discord_id: row.get(0)?,
+        role_id: row.get(1)?,
+        price: row.get(2)?,
+        purchased_at: row.get(3)?,
+        expires_at: row.get(4)?,
+    })
+}
+
+/// Checks stock, takes coins and saves purchase in one transaction.
+/// `duration` is in seconds, `None` for permanent roles.
+pub async fn purchase_role_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    role_id: u64,
+    price: u64,
+    stock: Option<u64>,
+    duration: Option<u64>
+) -> Result<RolePurchaseResult, async_sqlite::Error> {
+    let purchased_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let expires_at: Option<u64> = duration.map(|duration| purchased_at + duration);
+
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        if stock.is_some() {
+            let owners: u64 = transaction.query_row(
+                "SELECT COUNT(*) FROM role_purchases WHERE role_id=?",
+                [role_id],
+                |row| row.get(0)
+            )?;
+            if owners >= stock.unwrap() {
+                return Ok(RolePurchaseResult::OutOfStock);
+            }
+        }
+
+        let debited: Option<u64> = apply_balance_change(&transaction, discord_id, -(price as i64), LedgerReason::Shop, Some(format!("role:{}", role_id)))?;
+        if debited.is_none() {
+            return Ok(RolePurchaseResult::InsufficientFunds);
+        }
+
+        transaction.execute(
+            "INSERT INTO role_purchases (discord_id, role_id, price, purchased_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5) ON CONFLICT DO UPDATE SET price = EXCLUDED.price, purchased_at = EXCLUDED.purchased_at, expires_at = EXCLUDED.expires_at",
+            (discord_id, role_id, price, purchased_at, expires_at)
+        )?;
+
+        transaction.commit()?;
+        Ok(RolePurchaseResult::Purchased(DataRolePurchase { discord_id, role_id, price, purchased_at, expires_at }))
+    }).await
+}
+
+/// Removes purchase and gives `refund` coins back in one transaction.
+/// Used both for refunding failed purchases and for selling roles back.
+/// Returns `None` if user doesn't own this role.
+pub async fn remove_role_purchase_with_refund_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    role_id: u64,
+    refund: u64,
+    reason: LedgerReason,
+    reference: String
+) -> Result<Option<u64>, async_sqlite::Error> {
+    db_client.conn_mut(move |conn: &mut async_sqlite::rusqlite::Connection| {
+        let transaction = conn.transaction()?;
+
+        let removed: usize = transaction.execute(
+            "DELETE FROM role_purchases WHERE discord_id=?1 AND role_id=?2",
+            (discord_id, role_id)
+        )?;
+        if removed == 0 {
+            return Ok(None);
+        }
+
+        let balance_after: Option<u64> = apply_balance_change(&transaction, discord_id, refund as i64, reason, Some(reference))?;
+        if balance_after.is_none() {
+            return Ok(None);
+        }
+
+        transaction.commit()?;
+        Ok(balance_after)
+    }).await
+}
+
+pub async fn remove_role_purchase_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64,
+    role_id: u64
+) -> Result<usize, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM role_purchases WHERE discord_id=?1 AND role_id=?2",
+            (discord_id, role_id)
+        )
+    }).await
+}
+
+pub async fn get_user_role_purchases_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    discord_id: u64
+) -> Result<Vec<DataRolePurchase>, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT discord_id, role_id, price, purchased_at, expires_at FROM role_purchases WHERE discord_id=?")?;
+        let mut rows = stmt.query([discord_id])?;
+        let mut purchases = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            purchases.push(purchase_from_row(row)?);
+        }
+
+        Ok(purchases)
+    }).await
+}
+
+pub async fn get_expired_role_purchases_in_roleshop_db(
+    db_client: &async_sqlite::Client
+) -> Result<Vec<DataRolePurchase>, async_sqlite::Error> {
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT discord_id, role_id, price, purchased_at, expires_at FROM role_purchases WHERE expires_at IS NOT NULL AND expires_at<=?")?;
+        let mut rows = stmt.query([now])?;
+        let mut purchases = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            purchases.push(purchase_from_row(row)?);
+        }
+
+        Ok(purchases)
+    }).await
+}
+
+pub async fn count_role_owners_in_roleshop_db(
+    db_client: &async_sqlite::Client,
+    role_id: u64
+) -> Result<u64, async_sqlite::Error> {
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.query_row(
+            "SELECT COUNT(*) FROM role_purchases WHERE role_id=?",
+            [role_id],
+            |row| row.get(0)
+        )
+    }).await
+}
@@ -0,0 +1,56 @@
+// Settings changed at runtime with commands, they override Config.toml and survive restarts
+pub async fn prepare_settings_db(db_client: &async_sqlite::Client) {
+    db_client.conn(|conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT
+            )",
+            []
+        )
+    }).await.expect("Failed to create settings table in Loopchan's Database");
+}
+
+pub async fn get_setting_in_settings_db(
+    db_client: &async_sqlite::Client,
+    key: &str
+) -> Result<Option<String>, async_sqlite::Error> {
+    let key: String = key.to_string();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?")?;
+        let mut rows = stmt.query([key])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None)
+        }
+    }).await
+}
+
+pub async fn set_setting_in_settings_db(
+    db_client: &async_sqlite::Client,
+    key: &str,
+    value: String
+) -> Result<usize, async_sqlite::Error> {
+    let key: String = key.to_string();
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            (key, value)
+        )
+    }).await
+}
+
+/// Removes every setting starting with `prefix` (e.g. `welcomecard.`), so Config.toml values are used again
+pub async fn reset_settings_in_settings_db(
+    db_client: &async_sqlite::Client,
+    prefix: &str
+) -> Result<usize, async_sqlite::Error> {
+    let pattern: String = format!("{}%", prefix);
+    db_client.conn(move |conn: &async_sqlite::rusqlite::Connection| {
+        conn.execute(
+            "DELETE FROM settings WHERE key LIKE ?",
+            [pattern]
+        )
+    }).await
+}
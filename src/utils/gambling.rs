@@ -0,0 +1,197 @@
+use std::time::Instant;
+
+use rand::{seq::SliceRandom, Rng};
+
+// House edge is always taken from winnings (profit), games themselves are fair
+
+pub fn apply_house_edge(bet: u64, payout: u64, house_edge: f32) -> u64 {
+    if payout <= bet {
+        return payout;
+    }
+    let profit: f64 = (payout - bet) as f64 * (1.0 - house_edge.clamp(0.0, 1.0) as f64);
+    bet + profit.floor() as u64
+}
+
+/// Returns `true` if user won
+pub fn flip_coin() -> bool {
+    rand::rng().random_bool(0.5)
+}
+
+// Emoji, weight, multiplier for three in a row (two in a row always pays `SLOTS_PAIR_MULTIPLIER`)
+// Multipliers are scaled by `slots_fair_scale` so that slots are fair before house edge
+const SLOTS_SYMBOLS: [(&str, u32, f64); 5] = [
+    ("🍒", 35, 5.0),
+    ("🍋", 30, 8.0),
+    ("🔔", 20, 15.0),
+    ("💎", 10, 40.0),
+    ("7️⃣", 5,  150.0),
+];
+const SLOTS_PAIR_MULTIPLIER: f64 = 0.5;
+
+fn slots_raw_multiplier(reels: &[usize; 3]) -> f64 {
+    if reels[0] == reels[1] && reels[1] == reels[2] {
+        return SLOTS_SYMBOLS[reels[0]].2;
+    }
+    if reels[0] == reels[1] || reels[1] == reels[2] || reels[0] == reels[2] {
+        return SLOTS_PAIR_MULTIPLIER;
+    }
+    0.0
+}
+
+/// Expected return of raw multipliers, used to make slots fair
+fn slots_fair_scale() -> f64 {
+    let total_weight: f64 = SLOTS_SYMBOLS.iter().map(|symbol| symbol.1 as f64).sum();
+    let mut expected: f64 = 0.0;
+    for first in 0..SLOTS_SYMBOLS.len() {
+        for second in 0..SLOTS_SYMBOLS.len() {
+            for third in 0..SLOTS_SYMBOLS.len() {
+                let chance: f64 = (SLOTS_SYMBOLS[first].1 as f64/total_weight) * (SLOTS_SYMBOLS[second].1 as f64/total_weight) * (SLOTS_SYMBOLS[third].1 as f64/total_weight);
+                expected += chance * slots_raw_multiplier(&[first, second, third]);
+            }
+        }
+    }
+    1.0/expected
+}
+
+/// Spins reels and returns their emojis and payout before house edge
+pub fn spin_slots(bet: u64) -> ([&'static str; 3], u64) {
+    let total_weight: u32 = SLOTS_SYMBOLS.iter().map(|symbol| symbol.1).sum();
+    let mut rng = rand::rng();
+    let mut reels: [usize; 3] = [0; 3];
+
+    for reel in reels.iter_mut() {
+        let mut roll: u32 = rng.random_range(0..total_weight);
+        for (index, symbol) in SLOTS_SYMBOLS.iter().enumerate() {
+            if roll < symbol.1 {
+                *reel = index;
+                break;
+            }
+            roll -= symbol.1;
+        }
+    }
+
+    let payout: f64 = bet as f64 * slots_raw_multiplier(&reels) * slots_fair_scale();
+    (reels.map(|index| SLOTS_SYMBOLS[index].0), payout.floor() as u64)
+}
+
+#[derive(Clone, Copy)]
+pub struct Card {
+    rank: u8, // 1 (Ace) - 13 (King)
+    suit: char
+}
+
+impl Card {
+    pub fn display(&self) -> String {
+        let rank: String = match self.rank {
+            1 => "A".to_string(),
+            11 => "J".to_string(),
+            12 => "Q".to_string(),
+            13 => "K".to_string(),
+            rank => rank.to_string()
+        };
+        format!("`{}{}`", rank, self.suit)
+    }
+}
+
+pub fn hand_value(hand: &Vec<Card>) -> u8 {
+    let mut value: u8 = 0;
+    let mut aces: u8 = 0;
+    for card in hand {
+        value += card.rank.min(10);
+        if card.rank == 1 {
+            aces += 1;
+        }
+    }
+    // Count one ace as 11 if it doesn't bust
+    if aces > 0 && value + 10 <= 21 {
+        value += 10;
+    }
+    value
+}
+
+pub fn format_hand(hand: &Vec<Card>) -> String {
+    hand.iter().map(|card| card.display()).collect::<Vec<String>>().join(" ")
+}
+
+pub enum BlackjackOutcome {
+    Blackjack,
+    Win,
+    Push,
+    Lose
+}
+
+pub struct BlackjackGame {
+    pub bet: u64,
+    pub player: Vec<Card>,
+    pub dealer: Vec<Card>,
+    deck: Vec<Card>,
+    pub started_at: Instant,
+    pub bet_placed: bool // Game is reserved before bet is placed, so two games can't be started at once
+}
+
+impl BlackjackGame {
+    pub fn new(bet: u64) -> BlackjackGame {
+        let mut deck: Vec<Card> = vec![];
+        for suit in ['♠', '♥', '♦', '♣'] {
+            for rank in 1..=13 {
+                deck.push(Card { rank, suit });
+            }
+        }
+        deck.shuffle(&mut rand::rng());
+
+        let mut game: BlackjackGame = BlackjackGame { bet, player: vec![], dealer: vec![], deck, started_at: Instant::now(), bet_placed: false };
+        game.hit();
+        game.dealer.push(game.deck.pop().unwrap());
+        game.hit();
+        game.dealer.push(game.deck.pop().unwrap());
+        game
+    }
+
+    pub fn hit(&mut self) {
+        self.player.push(self.deck.pop().unwrap());
+    }
+
+    pub fn is_natural(hand: &Vec<Card>) -> bool {
+        hand.len() == 2 && hand_value(hand) == 21
+    }
+
+    /// Dealer draws until 17 and game is decided
+    pub fn stand(&mut self) -> BlackjackOutcome {
+        let player_value: u8 = hand_value(&self.player);
+        if player_value > 21 {
+            return BlackjackOutcome::Lose;
+        }
+
+        let player_natural: bool = BlackjackGame::is_natural(&self.player);
+        let dealer_natural: bool = BlackjackGame::is_natural(&self.dealer);
+        if player_natural && dealer_natural {
+            return BlackjackOutcome::Push;
+        }
+        if player_natural {
+            return BlackjackOutcome::Blackjack;
+        }
+
+        while hand_value(&self.dealer) < 17 {
+            self.dealer.push(self.deck.pop().unwrap());
+        }
+
+        let dealer_value: u8 = hand_value(&self.dealer);
+        if dealer_value > 21 || player_value > dealer_value {
+            BlackjackOutcome::Win
+        } else if player_value == dealer_value {
+            BlackjackOutcome::Push
+        } else {
+            BlackjackOutcome::Lose
+        }
+    }
+
+    /// Payout before house edge
+    pub fn payout(&self, outcome: &BlackjackOutcome) -> u64 {
+        match outcome {
+            BlackjackOutcome::Blackjack => self.bet + self.bet*3/2,
+            BlackjackOutcome::Win => self.bet*2,
+            BlackjackOutcome::Push => self.bet,
+            BlackjackOutcome::Lose => 0
+        }
+    }
+}
@@ -0,0 +1,175 @@
+// Maintenance closes Loopchan for everyone except owner and allowlisted users/roles.
+// It's active while `maintenance = true` in Config.toml, after `/adm maintenance on` (until turned off or `until` passes)
+// or during scheduled window. State is kept in memory (it's checked on every message) and saved into database.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, Member, RoleId, UserId};
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+
+use crate::{Data, DataMaintenanceWindow, LoopchanConfig};
+use crate::utils::config::ConfigHandle;
+use crate::utils::database::maintenance::{get_allowlist_in_maintenance_db, get_windows_in_maintenance_db, remove_ended_windows_in_maintenance_db};
+use crate::utils::presence::{show_presence, PresenceState};
+use crate::utils::database::settings::{get_setting_in_settings_db, reset_settings_in_settings_db};
+
+pub const DEFAULT_MAINTENANCE_MESSAGE: &'static str = "Loopchan is currently closed for maintenance. Please check back later, or wait for announcement.";
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Started with `/adm maintenance on`
+pub struct ManualMaintenance {
+    pub until: Option<u64>,
+    pub message: Option<String>
+}
+
+#[derive(Default)]
+pub struct MaintenanceState {
+    pub manual: Option<ManualMaintenance>,
+    pub windows: Vec<DataMaintenanceWindow>, // Soonest first
+    pub allowed_users: HashSet<u64>,
+    pub allowed_roles: HashSet<u64>,
+    presence_active: Option<bool> // What presence currently shows, `None` before it was set
+}
+
+/// Currently active maintenance
+pub struct ActiveMaintenance {
+    pub message: Option<String>,
+    pub until: Option<u64>
+}
+
+impl MaintenanceState {
+    pub fn active(&self, config: &LoopchanConfig, now: u64) -> Option<ActiveMaintenance> {
+        if config.maintenance {
+            return Some(ActiveMaintenance { message: None, until: None });
+        }
+        if let Some(manual) = &self.manual {
+            if manual.until.map_or(true, |until| until > now) {
+                return Some(ActiveMaintenance { message: manual.message.clone(), until: manual.until });
+            }
+        }
+        self.windows.iter()
+            .find(|window| window.starts_at <= now && now < window.ends_at)
+            .map(|window| ActiveMaintenance { message: window.message.clone(), until: Some(window.ends_at) })
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+pub async fn load_maintenance_state(db_client: &async_sqlite::Client) -> Result<MaintenanceState, async_sqlite::Error> {
+    let mut state: MaintenanceState = MaintenanceState::default();
+
+    if get_setting_in_settings_db(db_client, "maintenance.enabled").await?.is_some() {
+        state.manual = Some(ManualMaintenance {
+            until: get_setting_in_settings_db(db_client, "maintenance.until").await?.and_then(|until| until.parse().ok()),
+            message: get_setting_in_settings_db(db_client, "maintenance.message").await?
+        });
+    }
+    state.windows = get_windows_in_maintenance_db(db_client).await?;
+    for (id, kind) in get_allowlist_in_maintenance_db(db_client).await? {
+        if kind == "role" {
+            state.allowed_roles.insert(id);
+        } else {
+            state.allowed_users.insert(id);
+        }
+    }
+
+    Ok(state)
+}
+
+pub fn format_maintenance_message(active: &ActiveMaintenance) -> String {
+    let message: &str = active.message.as_deref().unwrap_or(DEFAULT_MAINTENANCE_MESSAGE);
+    match active.until {
+        Some(until) => format!("{}\n-# Maintenance ends <t:{}:R>.", message, until),
+        None => message.to_string()
+    }
+}
+
+/// Message to show user if maintenance is active and they aren't allowed to bypass it
+pub async fn maintenance_block_message(ctx: &serenity::Context, data: &Data, user_id: UserId) -> Option<String> {
+    let config: Arc<LoopchanConfig> = data.config.get();
+    if user_id.get() == config.owner {
+        return None;
+    }
+
+    let allowed_roles: HashSet<u64> = {
+        let state = data.maintenance.lock().await;
+        let active: ActiveMaintenance = state.active(&config, now_secs())?;
+        if state.allowed_users.contains(&user_id.get()) {
+            return None;
+        }
+        if state.allowed_roles.is_empty() {
+            return Some(format_maintenance_message(&active));
+        }
+        state.allowed_roles.clone()
+    };
+
+    let member: Result<Member, serenity::Error> = GuildId::new(config.guild).member(ctx, user_id).await;
+    if member.is_ok_and(|member| member.roles.iter().any(|role: &RoleId| allowed_roles.contains(&role.get()))) {
+        return None;
+    }
+
+    let state = data.maintenance.lock().await;
+    state.active(&config, now_secs()).map(|active| format_maintenance_message(&active))
+}
+
+/// Switches presence if maintenance was started or ended since it was last set
+pub async fn sync_maintenance_presence(
+    ctx: &serenity::Context,
+    state: &mut MaintenanceState,
+    config: &LoopchanConfig,
+    db_client: &async_sqlite::Client,
+    presence: &Mutex<PresenceState>
+) {
+    let active: bool = state.active(config, now_secs()).is_some();
+    if state.presence_active == Some(active) {
+        return;
+    }
+    state.presence_active = Some(active);
+
+    show_presence(ctx, config, db_client, active, &mut *presence.lock().await, false).await;
+}
+
+/// Ends expired manual maintenance, forgets ended windows and keeps presence up to date
+pub async fn watch_maintenance(
+    ctx: serenity::Context,
+    config: ConfigHandle,
+    db_client: async_sqlite::Client,
+    state: Arc<Mutex<MaintenanceState>>,
+    presence: Arc<Mutex<PresenceState>>
+) {
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now: u64 = now_secs();
+        let mut state = state.lock().await;
+
+        if state.manual.as_ref().is_some_and(|manual| manual.until.is_some_and(|until| until <= now)) {
+            state.manual = None;
+            let reset: Result<usize, async_sqlite::Error> = reset_settings_in_settings_db(&db_client, "maintenance.").await;
+            if reset.is_err() {
+                error!("Failed to clear ended maintenance: {}", reset.unwrap_err().to_string());
+            }
+            warn!("Maintenance ended");
+        }
+
+        if state.windows.iter().any(|window| window.ends_at <= now) {
+            state.windows.retain(|window| window.ends_at > now);
+            let removed: Result<usize, async_sqlite::Error> = remove_ended_windows_in_maintenance_db(&db_client, now).await;
+            if removed.is_err() {
+                error!("Failed to remove ended maintenance windows: {}", removed.unwrap_err().to_string());
+            }
+        }
+
+        let was_active: Option<bool> = state.presence_active;
+        sync_maintenance_presence(&ctx, &mut state, &config.get(), &db_client, &presence).await;
+        if was_active.is_some() && was_active != state.presence_active {
+            warn!("Maintenance is now {}", if state.presence_active == Some(true) { "active" } else { "over" });
+        }
+    }
+}
@@ -1,3 +1,13 @@
 pub mod wordgen;
 pub mod basic;
+pub mod gambling;
+pub mod charts;
+pub mod profilecard;
+pub mod render;
+pub mod banlist;
+pub mod permissions;
+pub mod audit;
+pub mod config;
+pub mod maintenance;
+pub mod presence;
 pub mod database;
\ No newline at end of file
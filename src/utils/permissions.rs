@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use serenity::all::{GuildId, Member, RoleId, User, UserId};
+use tracing::warn;
+
+use crate::{Context, Data, Error, LoopchanConfig};
+use crate::utils::audit::audit_command;
+
+/// Who can use command. Set it with `custom_data = "PermissionLevel::Staff"` on command,
+/// subcommands also need levels of their parents and commands without it are open to everyone.
+/// Levels are given by roles from `[roles]` in Config.toml, higher level includes lower ones.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum PermissionLevel {
+    Everyone,
+    Member,
+    Qa,
+    Staff,
+    Owner
+}
+
+impl PermissionLevel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PermissionLevel::Everyone => "Everyone",
+            PermissionLevel::Member => "Member",
+            PermissionLevel::Qa => "QA",
+            PermissionLevel::Staff => "Staff",
+            PermissionLevel::Owner => "Owner"
+        }
+    }
+}
+
+/// Level given by member's roles in PTL server
+pub fn member_permission_level(config: &LoopchanConfig, member: &Member) -> PermissionLevel {
+    if member.user.id.get() == config.owner {
+        return PermissionLevel::Owner;
+    }
+
+    if member.roles.contains(&RoleId::new(config.roles.staff)) {
+        PermissionLevel::Staff
+    } else if member.roles.contains(&RoleId::new(config.roles.qa)) {
+        PermissionLevel::Qa
+    } else if member.roles.contains(&RoleId::new(config.roles.member)) {
+        PermissionLevel::Member
+    } else {
+        PermissionLevel::Everyone
+    }
+}
+
+/// Same as `user_permission_level`, for event handlers which don't have poise's context
+pub async fn permission_level_of(ctx: &serenity::prelude::Context, data: &Data, user_id: UserId) -> PermissionLevel {
+    let config: Arc<LoopchanConfig> = data.config.get();
+    if user_id.get() == config.owner {
+        return PermissionLevel::Owner;
+    }
+
+    let member: Result<Member, serenity::Error> = GuildId::new(config.guild).member(ctx, user_id).await;
+    if member.is_err() {
+        return PermissionLevel::Everyone;
+    }
+    member_permission_level(&config, &member.unwrap())
+}
+
+/// Highest level user has, `Everyone` if they aren't in PTL server
+pub async fn user_permission_level(ctx: Context<'_>, user: &User) -> PermissionLevel {
+    permission_level_of(ctx.serenity_context(), ctx.data(), user.id).await
+}
+
+fn command_permission_level(command: &poise::Command<Data, Error>) -> PermissionLevel {
+    command.custom_data.downcast_ref::<PermissionLevel>().copied().unwrap_or(PermissionLevel::Everyone)
+}
+
+/// Highest level required by invoked command or any of its parents
+pub fn required_permission_level(ctx: Context<'_>) -> PermissionLevel {
+    ctx.parent_commands().iter().copied()
+        .chain(std::iter::once(ctx.command()))
+        .map(command_permission_level)
+        .max()
+        .unwrap_or(PermissionLevel::Everyone)
+}
+
+/// Part of `command_check`, tells user why command didn't run since failed checks are only logged
+pub async fn check_permission_level(ctx: Context<'_>) -> Result<bool, Error> {
+    let required: PermissionLevel = required_permission_level(ctx);
+    if required == PermissionLevel::Everyone {
+        return Ok(true);
+    }
+
+    let level: PermissionLevel = user_permission_level(ctx, ctx.author()).await;
+    if level >= required {
+        return Ok(true);
+    }
+
+    if required > PermissionLevel::Member {
+        audit_command(ctx, format!("denied (needs {}, has {})", required.name(), level.name())).await;
+    } else {
+        warn!("@{} ({}) was denied command \"{}\" (needs {} permission level, has {})", ctx.author().name, ctx.author().id, ctx.command().qualified_name, required.name(), level.name());
+    }
+    ctx.send(poise::CreateReply::default()
+        .content(format!("🔒 You need **{}** permission level to use this command.", required.name()))
+        .ephemeral(true)
+    ).await?;
+    Ok(false)
+}
@@ -0,0 +1,176 @@
+// Bot presence: maintenance presence while it's active, otherwise owner's custom status (`/adm presence set`)
+// or entries from `[presence]` rotation in Config.toml, switched every `interval` seconds.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use poise::serenity_prelude as serenity;
+use serenity::all::{ActivityData, ActivityType, OnlineStatus};
+use tokio::sync::Mutex;
+use tracing::error;
+
+use crate::{LoopchanConfig, PresenceEntry, PresenceKind, PresenceStatus};
+use crate::utils::basic::weekday_exp_multiplier;
+use crate::utils::config::ConfigHandle;
+use crate::utils::database::fishing::count_catches_in_fishing_db;
+use crate::utils::maintenance::MaintenanceState;
+
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+
+// Shown if there's no `[presence]` in Config.toml
+static DEFAULT_PRESENCE: Lazy<PresenceEntry> = Lazy::new(|| PresenceEntry {
+    kind: PresenceKind::Playing,
+    name: "PARKOUR: The Loop".to_string(),
+    state: Some("700 ELO | In Queue".to_string())
+});
+
+/// Set with `/adm presence set`
+pub struct CustomPresence {
+    pub entry: PresenceEntry,
+    pub until: Option<u64>
+}
+
+#[derive(Default)]
+pub struct PresenceState {
+    pub custom: Option<CustomPresence>,
+    rotation_index: usize
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+impl PresenceEntry {
+    pub fn new(kind: PresenceKind, name: String) -> Self {
+        PresenceEntry { kind, name, state: None }
+    }
+
+    fn to_activity(&self, name: String) -> ActivityData {
+        let kind: ActivityType = match self.kind {
+            PresenceKind::Playing => ActivityType::Playing,
+            PresenceKind::Listening => ActivityType::Listening,
+            PresenceKind::Watching => ActivityType::Watching,
+            PresenceKind::Competing => ActivityType::Competing,
+            PresenceKind::Custom => return ActivityData::custom(name)
+        };
+        ActivityData { name, kind, state: self.state.clone(), url: None }
+    }
+}
+
+/// Name of currently running boost event, if there is one
+pub fn current_boost_event(config: &LoopchanConfig) -> Option<String> {
+    let multiplier: u64 = weekday_exp_multiplier(&config.leveling);
+    if multiplier > 1 {
+        return Some(format!("{}x EXP Weekend", multiplier));
+    }
+    None
+}
+
+/// Replaces {member_count}, {fish_caught} and {boost_event}, `None` if entry needs boost event and there's none
+async fn render_presence_name(ctx: &serenity::Context, config: &LoopchanConfig, db_client: &async_sqlite::Client, name: &str) -> Option<String> {
+    let mut name: String = name.to_string();
+
+    if name.contains("{boost_event}") {
+        name = name.replace("{boost_event}", &current_boost_event(config)?);
+    }
+    if name.contains("{member_count}") {
+        let member_count: u64 = ctx.cache.guild(config.guild).map(|guild| guild.member_count).unwrap_or(0);
+        name = name.replace("{member_count}", &member_count.to_string());
+    }
+    if name.contains("{fish_caught}") {
+        let fish_caught: Result<u64, async_sqlite::Error> = count_catches_in_fishing_db(db_client).await;
+        if fish_caught.is_err() {
+            error!("Failed to count caught fishes for presence: {}", fish_caught.unwrap_err().to_string());
+            return None;
+        }
+        name = name.replace("{fish_caught}", &fish_caught.unwrap().to_string());
+    }
+
+    Some(name)
+}
+
+fn online_status(config: &LoopchanConfig) -> OnlineStatus {
+    match config.presence.as_ref().map(|presence| presence.status) {
+        Some(PresenceStatus::Online) => OnlineStatus::Online,
+        Some(PresenceStatus::Dnd) => OnlineStatus::DoNotDisturb,
+        Some(PresenceStatus::Invisible) => OnlineStatus::Invisible,
+        Some(PresenceStatus::Idle) | None => OnlineStatus::Idle
+    }
+}
+
+/// Sets presence for current state, `advance` moves rotation to next entry
+pub async fn show_presence(
+    ctx: &serenity::Context,
+    config: &LoopchanConfig,
+    db_client: &async_sqlite::Client,
+    maintenance_active: bool,
+    state: &mut PresenceState,
+    advance: bool
+) {
+    if maintenance_active {
+        ctx.set_presence(Some(ActivityData::custom("🔧 Under maintenance")), OnlineStatus::DoNotDisturb);
+        return;
+    }
+
+    if state.custom.as_ref().is_some_and(|custom| custom.until.is_some_and(|until| until <= now_secs())) {
+        state.custom = None;
+    }
+    if let Some(custom) = &state.custom {
+        let name: String = render_presence_name(ctx, config, db_client, &custom.entry.name).await.unwrap_or(custom.entry.name.clone());
+        ctx.set_presence(Some(custom.entry.to_activity(name)), online_status(config));
+        return;
+    }
+
+    let rotation: &Vec<PresenceEntry> = match &config.presence {
+        Some(presence) if !presence.rotation.is_empty() => &presence.rotation,
+        _ => {
+            ctx.set_presence(Some(DEFAULT_PRESENCE.to_activity(DEFAULT_PRESENCE.name.clone())), online_status(config));
+            return;
+        }
+    };
+
+    if advance {
+        state.rotation_index += 1;
+    }
+    // Skip entries that can't be shown right now (like boost event when there's none)
+    for offset in 0..rotation.len() {
+        let index: usize = (state.rotation_index + offset) % rotation.len();
+        let name: Option<String> = render_presence_name(ctx, config, db_client, &rotation[index].name).await;
+        if name.is_some() {
+            state.rotation_index = index;
+            ctx.set_presence(Some(rotation[index].to_activity(name.unwrap())), online_status(config));
+            return;
+        }
+    }
+
+    ctx.set_presence(Some(DEFAULT_PRESENCE.to_activity(DEFAULT_PRESENCE.name.clone())), online_status(config));
+}
+
+/// Rotates presence every `[presence] interval` seconds and ends expired custom status
+pub async fn rotate_presence(
+    ctx: serenity::Context,
+    config: ConfigHandle,
+    db_client: async_sqlite::Client,
+    maintenance: Arc<Mutex<MaintenanceState>>,
+    presence: Arc<Mutex<PresenceState>>
+) {
+    let mut rotated_at: Instant = Instant::now();
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let config: Arc<LoopchanConfig> = config.get();
+        let maintenance = maintenance.lock().await;
+        let mut presence = presence.lock().await;
+
+        let custom_expired: bool = presence.custom.as_ref().is_some_and(|custom| custom.until.is_some_and(|until| until <= now_secs()));
+        let rotation_due: bool = config.presence.as_ref().is_some_and(|presence| rotated_at.elapsed().as_secs() >= presence.interval);
+        if !custom_expired && !rotation_due {
+            continue;
+        }
+
+        rotated_at = Instant::now();
+        let maintenance_active: bool = maintenance.active(&config, now_secs()).is_some();
+        show_presence(&ctx, &config, &db_client, maintenance_active, &mut presence, rotation_due).await;
+    }
+}
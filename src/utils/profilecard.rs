@@ -0,0 +1,97 @@
+use image::DynamicImage;
+use tracing::error;
+
+use crate::Error;
+use crate::utils::render::{load_layout, render_layout, Layout, LayoutValues};
+
+const PROFILECARD_LAYOUT: &str = "profilecard.toml";
+
+/// Everything shown on profile card. Card is re-rendered only when this changes (see `Data.profile_cards`),
+/// so it keeps IDs/URLs instead of downloaded avatar and fetched Roblox username.
+#[derive(Hash)]
+pub struct ProfileCard {
+    pub username: String,
+    pub display_name: String,
+    pub avatar_url: String,
+    pub level: u64,
+    pub experience: u64,
+    pub experience_needed: u64,
+    pub balance: u64,
+    pub level_placement: Option<u64>,
+    pub balance_placement: Option<u64>,
+    pub roblox_id: Option<u64>,
+    pub best_fish: Option<(String, String, u64)>, // Name, size (formatted), value
+    pub roles: Vec<String>,
+    pub background: Option<String> // Path to background image from equipped item
+}
+
+impl ProfileCard {
+    pub fn fingerprint(&self) -> u64 {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+        let mut hasher: DefaultHasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+fn format_placement(placement: Option<u64>) -> String {
+    match placement {
+        Some(placement) => format!("#{}", placement),
+        None => String::from("#-")
+    }
+}
+
+/// `avatar` is raw image file of user's avatar, `roblox_username` is shown instead of Roblox ID if set
+pub fn render_profile_card(card: &ProfileCard, avatar: Option<&[u8]>, roblox_username: Option<&str>) -> Result<Vec<u8>, Error> {
+    let layout: Layout = load_layout(PROFILECARD_LAYOUT)?;
+
+    let roblox_text: String = match (card.roblox_id, roblox_username) {
+        (Some(_), Some(username)) => format!("@{}", username),
+        (Some(roblox_id), None) => roblox_id.to_string(),
+        _ => String::from("not linked")
+    };
+    let fish_text: String = match &card.best_fish {
+        Some((name, size, value)) => format!("{} • {}cm (~${})", name, size, value),
+        None => String::from("none yet")
+    };
+    let roles_text: String = if card.roles.is_empty() {
+        String::from("none")
+    } else {
+        card.roles.join(", ")
+    };
+
+    let mut values: LayoutValues = LayoutValues::default()
+        .text("username", card.username.clone())
+        .text("display_name", card.display_name.clone())
+        .text("level", card.level.to_string())
+        .text("level_placement", format_placement(card.level_placement))
+        .text("experience", card.experience.to_string())
+        .text("experience_needed", card.experience_needed.to_string())
+        .text("balance", card.balance.to_string())
+        .text("balance_placement", format_placement(card.balance_placement))
+        .text("roblox", roblox_text)
+        .text("best_fish", fish_text)
+        .text("roles", roles_text)
+        .progress("experience", card.experience as f64 / card.experience_needed.max(1) as f64);
+
+    if card.background.is_some() {
+        let path: &String = card.background.as_ref().unwrap();
+        let background: Result<DynamicImage, image::ImageError> = image::open(path);
+        if background.is_ok() {
+            values = values.background(background.unwrap());
+        } else {
+            error!("Failed to open profile background {}: {}", path, background.unwrap_err().to_string());
+        }
+    }
+
+    if avatar.is_some() {
+        let avatar_image: Result<DynamicImage, image::ImageError> = image::load_from_memory(avatar.unwrap());
+        if avatar_image.is_ok() {
+            values = values.image("avatar", avatar_image.unwrap());
+        } else {
+            error!("Failed to decode avatar of {}: {}", card.username, avatar_image.unwrap_err().to_string());
+        }
+    }
+
+    Ok(render_layout(&layout, &values)?)
+}
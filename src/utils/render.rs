@@ -0,0 +1,605 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use ab_glyph::{Font, FontArc, FontRef, PxScale, VariableFont};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{overlay, FilterType};
+use image::{Delay, DynamicImage, Frame, ImageBuffer, Rgb, Rgba};
+use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut, text_size};
+use imageproc::rect::Rect;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use tracing::error;
+
+use crate::Error;
+
+// Card layouts are described in TOML files (see welcomecard.toml), so moving text or avatar around
+// doesn't need code changes. Everything is drawn in this order: background, bars, texts, images.
+
+const DEFAULT_FONT_WEIGHT: f32 = 400.0;
+
+// Fallback fonts are big (CJK ones have tens of MB), so they're read once per path. Changed font files need restart.
+static FALLBACK_FONTS: Lazy<Mutex<HashMap<PathBuf, FontArc>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageMask {
+    #[default]
+    None,
+    Circle,
+    Rounded
+}
+
+#[derive(Deserialize)]
+pub struct TextBox {
+    text: String, // `{key}` is replaced with value provided when rendering
+    x: i32,
+    y: i32,
+    width: u32, // Text is shrunk (down to `min_size`) and then cut to fit this
+    size: f32,
+    min_size: Option<f32>,
+    #[serde(default)]
+    align: TextAlign,
+    color: String // Colour token from `[colors]` or "#RRGGBB"
+}
+
+#[derive(Deserialize)]
+pub struct ImageSlot {
+    key: String,
+    x: i64,
+    y: i64,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    mask: ImageMask,
+    radius: Option<u32> // Corner radius for `rounded` mask
+}
+
+#[derive(Deserialize)]
+pub struct ProgressBar {
+    key: String, // Progress (0.0 - 1.0) provided when rendering
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    color: String,
+    fill: String
+}
+
+#[derive(Deserialize)]
+pub struct Layout {
+    template: Option<String>, // Template image, relative to layout file
+    width: u32,
+    height: u32,
+    background: Option<String>, // Colour used if there's no template
+    background_dim: Option<f32>, // How much background image provided when rendering is darkened (0.0 - 1.0)
+    #[serde(default)]
+    colors: HashMap<String, String>,
+    font_weight: Option<f32>, // CascadiaCode weight, from 200 (light) to 700 (bold), 400 if not set
+    #[serde(default)]
+    fonts: Vec<String>, // Fallback fonts (relative to layout file) for characters CascadiaCode doesn't have, like CJK.
+                        // Color emoji fonts aren't supported, use outline ones (e.g. Noto Emoji) instead.
+    #[serde(skip)]
+    font_chain: Vec<FontArc>, // CascadiaCode and then loaded `fonts`
+    #[serde(default)]
+    bars: Vec<ProgressBar>,
+    #[serde(default)]
+    texts: Vec<TextBox>,
+    #[serde(default)]
+    images: Vec<ImageSlot>
+}
+
+/// Values that are put into layout. Missing texts are left empty, missing images and bars aren't drawn.
+#[derive(Default)]
+pub struct LayoutValues {
+    texts: HashMap<String, String>,
+    images: HashMap<String, DynamicImage>,
+    progress: HashMap<String, f64>,
+    background: Option<DynamicImage> // Replaces template
+}
+
+impl LayoutValues {
+    pub fn text(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.texts.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn image(mut self, key: &str, image: DynamicImage) -> Self {
+        self.images.insert(key.to_string(), image);
+        self
+    }
+
+    pub fn progress(mut self, key: &str, progress: f64) -> Self {
+        self.progress.insert(key.to_string(), progress.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn background(mut self, background: DynamicImage) -> Self {
+        self.background = Some(background);
+        self
+    }
+}
+
+fn parse_hex_color(hex: &str) -> Option<Rgb<u8>> {
+    let hex: &str = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let value: u32 = u32::from_str_radix(hex, 16).ok()?;
+    Some(Rgb([(value >> 16) as u8, (value >> 8) as u8, value as u8]))
+}
+
+impl Layout {
+    fn color(&self, token: &str) -> Option<Rgb<u8>> {
+        match self.colors.get(token) {
+            Some(hex) => parse_hex_color(hex),
+            None => parse_hex_color(token)
+        }
+    }
+}
+
+/// Reads layout and checks that all of its colours are valid
+pub fn load_layout(path: &str) -> Result<Layout, Error> {
+    let mut layout: Layout = toml::from_str(&std::fs::read_to_string(path)?)?;
+
+    let mut colors: Vec<&String> = layout.texts.iter().map(|text| &text.color).collect();
+    colors.extend(layout.bars.iter().flat_map(|bar| [&bar.color, &bar.fill]));
+    colors.extend(layout.background.iter());
+    for color in colors {
+        if layout.color(color).is_none() {
+            return Err(format!("Unknown colour `{}` in layout {}", color, path).into());
+        }
+    }
+
+    let directory: &Path = Path::new(path).parent().unwrap_or(Path::new(""));
+    if layout.template.is_some() {
+        let template: PathBuf = directory.join(layout.template.as_ref().unwrap());
+        layout.template = Some(template.to_string_lossy().to_string());
+    }
+
+    let font_weight: f32 = layout.font_weight.unwrap_or(DEFAULT_FONT_WEIGHT);
+    if !(200.0..=700.0).contains(&font_weight) {
+        return Err(format!("`font_weight` in layout {} must be between 200 and 700", path).into());
+    }
+    let mut cascadia: FontRef<'static> = FontRef::try_from_slice(include_bytes!("../../CascadiaCode.ttf")).unwrap();
+    cascadia.set_variation(b"wght", font_weight);
+    layout.font_chain.push(FontArc::new(cascadia));
+    for font in &layout.fonts {
+        let font_path: PathBuf = directory.join(font);
+        let cached: Option<FontArc> = FALLBACK_FONTS.lock().unwrap().get(&font_path).cloned();
+        if cached.is_some() {
+            layout.font_chain.push(cached.unwrap());
+            continue;
+        }
+
+        let loaded: Result<FontArc, ab_glyph::InvalidFont> = FontArc::try_from_vec(std::fs::read(&font_path)?);
+        if loaded.is_err() {
+            return Err(format!("Invalid font {} in layout {}", font_path.to_string_lossy(), path).into());
+        }
+        let loaded: FontArc = loaded.unwrap();
+        FALLBACK_FONTS.lock().unwrap().insert(font_path, loaded.clone());
+        layout.font_chain.push(loaded);
+    }
+
+    Ok(layout)
+}
+
+fn fill_placeholders(text: &str, values: &HashMap<String, String>) -> String {
+    // Single pass, so values containing `{key}` themselves (e.g. usernames) aren't replaced again
+    let mut filled: String = String::new();
+    let mut rest: &str = text;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let end: Option<usize> = rest[start..].find('}');
+        if end.is_none() {
+            rest = &rest[start..];
+            break;
+        }
+        let key: &str = &rest[start + 1..start + end.unwrap()];
+        filled.push_str(values.get(key).map(|value| value.as_str()).unwrap_or(""));
+        rest = &rest[start + end.unwrap() + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+fn is_rtl(character: char) -> bool {
+    matches!(character as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF)
+}
+
+/// Reverses right-to-left (Hebrew, Arabic) parts of text so they're read correctly when drawn left to right.
+/// This isn't full bidi support (Arabic letters aren't joined), but it keeps such names readable.
+fn visual_order(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut ordered: String = String::new();
+    let mut index: usize = 0;
+    while index < chars.len() {
+        if !is_rtl(chars[index]) {
+            ordered.push(chars[index]);
+            index += 1;
+            continue;
+        }
+        // RTL part continues through spaces, as long as there's more RTL text after them
+        let mut end: usize = index;
+        let mut cursor: usize = index;
+        while cursor < chars.len() && (is_rtl(chars[cursor]) || chars[cursor].is_whitespace()) {
+            if is_rtl(chars[cursor]) {
+                end = cursor + 1;
+            }
+            cursor += 1;
+        }
+        ordered.extend(chars[index..end].iter().rev());
+        index = end;
+    }
+    ordered
+}
+
+/// Splits text into runs drawn with the first font that has all of their characters.
+/// Characters no font has are left to the first font (and drawn as boxes).
+fn split_into_font_runs(text: &str, fonts: &Vec<FontArc>) -> Vec<(usize, String)> {
+    let mut runs: Vec<(usize, String)> = Vec::new();
+    for character in text.chars() {
+        let font_index: usize = if character.is_whitespace() && !runs.is_empty() {
+            runs.last().unwrap().0
+        } else {
+            fonts.iter().position(|font| font.glyph_id(character).0 != 0).unwrap_or(0)
+        };
+
+        match runs.last_mut() {
+            Some((last_index, run)) if *last_index == font_index => run.push(character),
+            _ => runs.push((font_index, character.to_string()))
+        }
+    }
+    runs
+}
+
+fn measure_text(text: &str, scale: PxScale, fonts: &Vec<FontArc>) -> u32 {
+    split_into_font_runs(text, fonts).iter().map(|(font_index, run)| text_size(scale, &fonts[*font_index], run).0).sum()
+}
+
+fn draw_text_with_fallback(image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, color: Rgb<u8>, x: i32, y: i32, scale: PxScale, fonts: &Vec<FontArc>, text: &str) {
+    let mut x: i32 = x;
+    for (font_index, run) in split_into_font_runs(text, fonts) {
+        draw_text_mut(image, color, x, y, scale, &fonts[font_index], &run);
+        x += text_size(scale, &fonts[font_index], &run).0 as i32;
+    }
+}
+
+/// Biggest scale (down to `min_size`) at which `text` fits into `width`, with text cut and "..." added if it still doesn't fit.
+/// `text` is in logical order, so the end of it is cut (not what's drawn last), returned text is in visual order.
+fn fit_text(text: &str, width: u32, size: f32, min_size: f32, fonts: &Vec<FontArc>) -> (String, PxScale) {
+    let mut size: f32 = size;
+    while size > min_size && measure_text(&visual_order(text), PxScale::from(size), fonts) > width {
+        size = (size - 2.0).max(min_size);
+    }
+    let scale: PxScale = PxScale::from(size);
+
+    if measure_text(&visual_order(text), scale, fonts) <= width {
+        return (visual_order(text), scale);
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let cut: String = visual_order(&format!("{}...", chars.iter().collect::<String>().trim_end()));
+        if measure_text(&cut, scale, fonts) <= width {
+            return (cut, scale);
+        }
+    }
+    (String::from("..."), scale)
+}
+
+/// Anti-aliased mask, edge pixels are partially transparent depending on how much of them is inside
+fn apply_mask(image: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, mask: ImageMask, radius: u32) {
+    let (width, height): (f32, f32) = (image.width() as f32, image.height() as f32);
+    let radius: f32 = match mask {
+        ImageMask::None => return,
+        ImageMask::Circle => width.min(height) / 2.0,
+        ImageMask::Rounded => (radius as f32).min(width.min(height) / 2.0)
+    };
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        // Distance from pixel center to the nearest point of rectangle shrunk by radius
+        let (px, py): (f32, f32) = (x as f32 + 0.5, y as f32 + 0.5);
+        let dx: f32 = (radius - px).max(px - (width - radius)).max(0.0);
+        let dy: f32 = (radius - py).max(py - (height - radius)).max(0.0);
+        let coverage: f32 = (radius - f32::hypot(dx, dy) + 0.5).clamp(0.0, 1.0);
+        pixel.0[3] = (pixel.0[3] as f32 * coverage) as u8;
+    }
+}
+
+fn resize_to_layout(image: &DynamicImage, layout: &Layout) -> DynamicImage {
+    if image.width() == layout.width && image.height() == layout.height {
+        return image.clone();
+    }
+    image.resize_to_fill(layout.width, layout.height, FilterType::Triangle)
+}
+
+/// Draws everything except images into Rgba buffer
+fn draw_layout_base(layout: &Layout, values: &LayoutValues) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, image::ImageError> {
+    let mut image: ImageBuffer<Rgb<u8>, Vec<u8>> = if values.background.is_some() {
+        let mut background: ImageBuffer<Rgb<u8>, Vec<u8>> = resize_to_layout(values.background.as_ref().unwrap(), layout).into_rgb8();
+        let dim: f32 = layout.background_dim.unwrap_or(0.0).clamp(0.0, 1.0);
+        for pixel in background.pixels_mut() {
+            for channel in pixel.0.iter_mut() {
+                *channel = (*channel as f32 * (1.0 - dim)) as u8;
+            }
+        }
+        background
+    } else if layout.template.is_some() {
+        resize_to_layout(&image::open(layout.template.as_ref().unwrap())?, layout).into_rgb8()
+    } else {
+        let background: Rgb<u8> = layout.background.as_ref().and_then(|color| layout.color(color)).unwrap_or(Rgb([0, 0, 0]));
+        ImageBuffer::from_pixel(layout.width, layout.height, background)
+    };
+
+    for bar in &layout.bars {
+        let progress: Option<&f64> = values.progress.get(&bar.key);
+        if progress.is_none() {
+            continue;
+        }
+        draw_filled_rect_mut(&mut image, Rect::at(bar.x, bar.y).of_size(bar.width, bar.height), layout.color(&bar.color).unwrap());
+        let filled_width: u32 = (bar.width as f64 * progress.unwrap()) as u32;
+        if filled_width > 0 {
+            draw_filled_rect_mut(&mut image, Rect::at(bar.x, bar.y).of_size(filled_width, bar.height), layout.color(&bar.fill).unwrap());
+        }
+    }
+
+    for text_box in &layout.texts {
+        let text: String = fill_placeholders(&text_box.text, &values.texts);
+        let (text, scale): (String, PxScale) = fit_text(&text, text_box.width, text_box.size, text_box.min_size.unwrap_or(text_box.size), &layout.font_chain);
+        let text_width: u32 = measure_text(&text, scale, &layout.font_chain);
+        let x: i32 = match text_box.align {
+            TextAlign::Left => text_box.x,
+            TextAlign::Center => text_box.x + (text_box.width.saturating_sub(text_width) / 2) as i32,
+            TextAlign::Right => text_box.x + text_box.width.saturating_sub(text_width) as i32
+        };
+        draw_text_with_fallback(&mut image, layout.color(&text_box.color).unwrap(), x, text_box.y, scale, &layout.font_chain, &text);
+    }
+
+    // Text can't be drawn on Rgba buffer (it just breaks), so images are added after converting
+    Ok(DynamicImage::from(image).into())
+}
+
+/// Resizes image into slot (scaled by `scale`), masks it and puts it on top of `image`
+fn overlay_slot(image: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, slot: &ImageSlot, slot_image: &DynamicImage, scale: f32) {
+    let (width, height): (u32, u32) = (((slot.width as f32 * scale) as u32).max(1), ((slot.height as f32 * scale) as u32).max(1));
+    let mut slot_image: ImageBuffer<Rgba<u8>, Vec<u8>> = slot_image.resize_to_fill(width, height, FilterType::Lanczos3).into_rgba8();
+    apply_mask(&mut slot_image, slot.mask, (slot.radius.unwrap_or(0) as f32 * scale) as u32);
+    overlay(image, &slot_image, (slot.x as f32 * scale) as i64, (slot.y as f32 * scale) as i64);
+}
+
+/// Renders layout into PNG
+pub fn render_layout(layout: &Layout, values: &LayoutValues) -> Result<Vec<u8>, image::ImageError> {
+    let mut image: ImageBuffer<Rgba<u8>, Vec<u8>> = draw_layout_base(layout, values)?;
+    for slot in &layout.images {
+        let slot_image: Option<&DynamicImage> = values.images.get(&slot.key);
+        if slot_image.is_some() {
+            overlay_slot(&mut image, slot, slot_image.unwrap(), 1.0);
+        }
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+    Ok(bytes)
+}
+
+/// Renders layout into animated GIF, with `frames` (e.g. of animated avatar) put into `key` image slot.
+/// GIFs get big quickly, so card is scaled by `scale` and only first `max_frames` frames are used.
+pub fn render_layout_animated(
+    layout: &Layout,
+    values: &LayoutValues,
+    key: &str,
+    frames: Vec<Frame>,
+    scale: f32,
+    max_frames: usize
+) -> Result<Vec<u8>, image::ImageError> {
+    let mut base: ImageBuffer<Rgba<u8>, Vec<u8>> = draw_layout_base(layout, values)?;
+    if scale != 1.0 {
+        base = image::imageops::resize(&base, ((layout.width as f32 * scale) as u32).max(1), ((layout.height as f32 * scale) as u32).max(1), FilterType::Triangle);
+    }
+    for slot in layout.images.iter().filter(|slot| slot.key != key) {
+        let slot_image: Option<&DynamicImage> = values.images.get(&slot.key);
+        if slot_image.is_some() {
+            overlay_slot(&mut base, slot, slot_image.unwrap(), scale);
+        }
+    }
+
+    let animated_slot: Option<&ImageSlot> = layout.images.iter().find(|slot| slot.key == key);
+    let mut rendered_frames: Vec<Frame> = Vec::new();
+    for frame in frames.into_iter().take(max_frames) {
+        let delay: Delay = frame.delay();
+        let mut image: ImageBuffer<Rgba<u8>, Vec<u8>> = base.clone();
+        if animated_slot.is_some() {
+            overlay_slot(&mut image, animated_slot.unwrap(), &DynamicImage::from(frame.into_buffer()), scale);
+        }
+        rendered_frames.push(Frame::from_parts(image, 0, 0, delay));
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    {
+        let mut encoder: GifEncoder<&mut Vec<u8>> = GifEncoder::new_with_speed(&mut bytes, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(rendered_frames)?;
+    }
+    Ok(bytes)
+}
+
+/// Downloads image (e.g. avatar) into memory
+pub async fn download_image(url: &String) -> Option<Vec<u8>> {
+    let response: Result<roboat::reqwest::Response, roboat::reqwest::Error> = roboat::reqwest::get(url).await;
+    if response.is_err() {
+        error!("Failed to download image {}: {}", url, response.unwrap_err().to_string());
+        return None;
+    }
+    let response: roboat::reqwest::Response = response.unwrap();
+    if !response.status().is_success() {
+        error!("Failed to download image {}: {}", url, response.status());
+        return None;
+    }
+    response.bytes().await.ok().map(|bytes| bytes.to_vec())
+}
+
+/// Plain avatar used when member's avatar couldn't be downloaded (same colour as Discord's default avatar)
+pub fn default_avatar() -> DynamicImage {
+    DynamicImage::ImageRgba8(ImageBuffer::from_pixel(256, 256, Rgba([88, 101, 242, 255])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run with UPDATE_GOLDEN=1 to write new golden images after intended layout changes
+    fn assert_matches_golden(rendered: &[u8], golden_path: &str) {
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            std::fs::write(golden_path, rendered).unwrap();
+            return;
+        }
+
+        let rendered: ImageBuffer<Rgba<u8>, Vec<u8>> = image::load_from_memory(rendered).unwrap().into_rgba8();
+        let golden: ImageBuffer<Rgba<u8>, Vec<u8>> = image::open(golden_path).expect("Missing golden image, run with UPDATE_GOLDEN=1").into_rgba8();
+        assert_eq!(rendered.dimensions(), golden.dimensions(), "{} has different size", golden_path);
+
+        // Small tolerance for anti-aliasing differences between platforms
+        let different_pixels: usize = rendered.pixels().zip(golden.pixels())
+            .filter(|(rendered, golden)| rendered.0.iter().zip(golden.0.iter()).any(|(a, b)| a.abs_diff(*b) > 2))
+            .count();
+        assert_eq!(different_pixels, 0, "{} pixels differ from {}", different_pixels, golden_path);
+    }
+
+    fn cascadia() -> Vec<FontArc> {
+        vec![FontArc::try_from_slice(include_bytes!("../../CascadiaCode.ttf")).unwrap()]
+    }
+
+    // Test-only font with plain boxes for few CJK characters (名前長いのユーザーさんですテスト), used as fallback after CascadiaCode
+    fn cascadia_with_cjk() -> Vec<FontArc> {
+        let mut fonts: Vec<FontArc> = cascadia();
+        fonts.push(FontArc::try_from_slice(include_bytes!("../../tests/fonts/cjk-boxes.ttf")).unwrap());
+        fonts
+    }
+
+    #[test]
+    fn visual_order_keeps_left_to_right_text() {
+        assert_eq!(visual_order("frostx_official"), "frostx_official");
+        assert_eq!(visual_order("名前 テスト"), "名前 テスト");
+    }
+
+    #[test]
+    fn visual_order_reverses_right_to_left_parts() {
+        assert_eq!(visual_order("שלום"), "םולש");
+        // Space between RTL words belongs to RTL part, trailing one doesn't
+        assert_eq!(visual_order("abc שלום עולם def"), "abc םלוע םולש def");
+        assert_eq!(visual_order("שלום "), "םולש ");
+    }
+
+    #[test]
+    fn font_runs_join_whitespace_with_previous_run() {
+        let fonts: Vec<FontArc> = cascadia();
+        assert_eq!(split_into_font_runs("hello world", &fonts), vec![(0, String::from("hello world"))]);
+        assert_eq!(split_into_font_runs(" hi", &fonts), vec![(0, String::from(" hi"))]);
+        assert!(split_into_font_runs("", &fonts).is_empty());
+    }
+
+    #[test]
+    fn font_runs_use_fallback_font_for_missing_characters() {
+        let fonts: Vec<FontArc> = cascadia_with_cjk();
+        assert_eq!(split_into_font_runs("名前 abc", &fonts), vec![(1, String::from("名前 ")), (0, String::from("abc"))]);
+        assert_eq!(split_into_font_runs("abテスト", &fonts), vec![(0, String::from("ab")), (1, String::from("テスト"))]);
+    }
+
+    #[test]
+    fn font_runs_leave_unknown_characters_to_first_font() {
+        // Neither font has 漢, so it's drawn with the first one
+        let fonts: Vec<FontArc> = cascadia_with_cjk();
+        assert_eq!(split_into_font_runs("名漢", &fonts), vec![(1, String::from("名")), (0, String::from("漢"))]);
+    }
+
+    #[test]
+    fn fit_text_keeps_text_that_fits() {
+        let fonts: Vec<FontArc> = cascadia();
+        let (text, scale): (String, PxScale) = fit_text("FrostX", 600, 52.0, 36.0, &fonts);
+        assert_eq!(text, "FrostX");
+        assert_eq!(scale.y, 52.0);
+    }
+
+    #[test]
+    fn fit_text_shrinks_and_cuts_long_names() {
+        let fonts: Vec<FontArc> = cascadia();
+        let name: &str = "some_really_long_username_that_overflows_the_card";
+        let (text, scale): (String, PxScale) = fit_text(name, 400, 52.0, 36.0, &fonts);
+        assert_eq!(scale.y, 36.0);
+        assert!(text.ends_with("..."));
+        assert!(name.starts_with(text.trim_end_matches("...")));
+        assert!(measure_text(&text, scale, &fonts) <= 400);
+    }
+
+    #[test]
+    fn fit_text_cuts_right_to_left_names_in_logical_order() {
+        let fonts: Vec<FontArc> = cascadia();
+        let name: &str = "שלום עולם שלום עולם שלום עולם";
+        let (text, scale): (String, PxScale) = fit_text(name, 300, 40.0, 40.0, &fonts);
+        assert!(measure_text(&text, scale, &fonts) <= 300);
+        // Start of the name is kept (drawn on the right), only its end is cut
+        let kept: String = visual_order(&text).trim_end_matches("...").to_string();
+        assert!(!kept.is_empty());
+        assert!(name.starts_with(&kept));
+        assert_eq!(text, visual_order(&format!("{}...", kept)));
+    }
+
+    #[test]
+    fn fit_text_cuts_cjk_names() {
+        let fonts: Vec<FontArc> = cascadia_with_cjk();
+        let name: &str = "長い名前のユーザーさんです長い名前のユーザーさんです";
+        let (text, scale): (String, PxScale) = fit_text(name, 300, 40.0, 40.0, &fonts);
+        assert!(text.ends_with("..."));
+        assert!(name.starts_with(text.trim_end_matches("...")));
+        // Boxes are 1em wide, so only few characters fit
+        assert!(text.trim_end_matches("...").chars().count() < 8);
+        assert!(measure_text(&text, scale, &fonts) <= 300);
+    }
+
+    fn test_avatar() -> DynamicImage {
+        DynamicImage::ImageRgba8(ImageBuffer::from_fn(256, 256, |x, y| Rgba([x as u8, y as u8, 160, 255])))
+    }
+
+    #[test]
+    fn welcomecard_matches_golden() {
+        let layout: Layout = load_layout("welcomecard.toml").unwrap();
+        let values: LayoutValues = LayoutValues::default()
+            .text("username", "frostx_official")
+            .text("display_name", "FrostX")
+            .text("member_count", "1234")
+            .image("avatar", test_avatar());
+        assert_matches_golden(&render_layout(&layout, &values).unwrap(), "tests/golden/welcomecard.png");
+    }
+
+    #[test]
+    fn profilecard_matches_golden() {
+        let layout: Layout = load_layout("profilecard.toml").unwrap();
+        let values: LayoutValues = LayoutValues::default()
+            .text("username", "frostx_official")
+            .text("display_name", "FrostX")
+            .text("level", "12")
+            .text("level_placement", "#3")
+            .text("experience", "340")
+            .text("experience_needed", "900")
+            .text("balance", "12345")
+            .text("balance_placement", "#-")
+            .text("roblox", "@FrostX")
+            .text("best_fish", "Golden Carp • 42.13cm (~$1234)")
+            .text("roles", "Poslar, Rich Guy, Another Very Long Role, More Roles")
+            .progress("experience", 340.0 / 900.0)
+            .image("avatar", test_avatar());
+        assert_matches_golden(&render_layout(&layout, &values).unwrap(), "tests/golden/profilecard.png");
+    }
+}